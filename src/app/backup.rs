@@ -0,0 +1,558 @@
+use std::fs::{self, DirBuilder, OpenOptions};
+#[cfg(unix)]
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+use chrono::{Local, NaiveDateTime};
+use color_eyre::{Result, eyre::eyre};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+
+use super::compose::ComposeProject;
+use super::surface::Surface;
+use super::{App, AppState, KEYCLOAK_SERVICE};
+use crate::docker::DockerClient;
+use crate::runtime::Runtime;
+use crate::utils;
+
+pub const BACKUP_DIR: &str = "backups";
+const BACKUP_PREFIX: &str = "identity-";
+const BACKUP_EXTENSION: &str = ".dump";
+const TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S";
+pub const DATABASE_SERVICE: &str = "postgres";
+
+/// The PostgreSQL container and credentials, taken from the compose project.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DatabaseTarget {
+    pub container: String,
+    pub user: String,
+    pub database: String,
+}
+
+impl DatabaseTarget {
+    pub fn from_project(project: &ComposeProject) -> Self {
+        let env = |key: &str| project.environment(DATABASE_SERVICE, key);
+        let user = env("POSTGRES_USER").unwrap_or_else(|| "postgres".to_string());
+        let database = env("POSTGRES_DB").unwrap_or_else(|| user.clone());
+
+        Self {
+            container: project.container_name(DATABASE_SERVICE),
+            user,
+            database,
+        }
+    }
+
+    /// Runs `query` with `psql -tA` in the database container, through the
+    /// Docker Engine API, and returns its output.
+    pub async fn psql(&self, query: &str) -> Result<String> {
+        let output = DockerClient::from_env()?
+            .exec(
+                &self.container,
+                &[
+                    "psql",
+                    "-U",
+                    &self.user,
+                    "-d",
+                    &self.database,
+                    "-tAc",
+                    query,
+                ],
+            )
+            .await?;
+        if output.exit_code != 0 {
+            return Err(eyre!("psql failed: {}", output.stderr.trim()));
+        }
+        Ok(output.stdout)
+    }
+}
+
+/// A `pg_dump` custom-format archive (compressed by PostgreSQL itself).
+#[derive(Debug, Clone)]
+pub struct Backup {
+    pub path: PathBuf,
+    pub file_name: String,
+    pub created: NaiveDateTime,
+    pub size: u64,
+}
+
+pub fn backup_dir() -> PathBuf {
+    utils::project_root().join(BACKUP_DIR)
+}
+
+/// Creates [`backup_dir`] readable by the owner only, tightening an existing
+/// one: dumps hold password hashes and client secrets.
+pub fn create_backup_dir() -> Result<PathBuf> {
+    let dir = backup_dir();
+    let mut builder = DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    builder.mode(0o700);
+    builder.create(&dir)?;
+    #[cfg(unix)]
+    fs::set_permissions(&dir, fs::Permissions::from_mode(0o700))?;
+    Ok(dir)
+}
+
+pub fn backup_file_name(created: NaiveDateTime) -> String {
+    format!(
+        "{}{}{}",
+        BACKUP_PREFIX,
+        created.format(TIMESTAMP_FORMAT),
+        BACKUP_EXTENSION
+    )
+}
+
+fn parse_backup_name(name: &str) -> Option<NaiveDateTime> {
+    let timestamp = name
+        .strip_prefix(BACKUP_PREFIX)?
+        .strip_suffix(BACKUP_EXTENSION)?;
+    NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT).ok()
+}
+
+/// Backups in `dir`, newest first. A missing directory means no backups yet.
+pub fn list_backups(dir: &Path) -> Result<Vec<Backup>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut backups = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        if let Some(created) = parse_backup_name(&file_name) {
+            backups.push(Backup {
+                path: entry.path(),
+                file_name,
+                created,
+                size: entry.metadata()?.len(),
+            });
+        }
+    }
+
+    backups.sort_by_key(|backup| std::cmp::Reverse(backup.created));
+    Ok(backups)
+}
+
+/// Runs `pg_dump` inside the database container and streams the archive to
+/// `path`. The file only appears under its final name once the dump succeeded.
+/// This and the `pg_restore` calls below go through the CLI rather than
+/// [`DockerClient::exec`], which neither feeds stdin nor keeps binary output.
+pub async fn dump_database(target: &DatabaseTarget, path: &Path) -> Result<()> {
+    let partial = path.with_extension("partial");
+    let _ = fs::remove_file(&partial);
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let file = options.open(&partial)?;
+
+    let output = Runtime::current()
+        .command()
+        .args([
+            "exec",
+            &target.container,
+            "pg_dump",
+            "--format=custom",
+            "-U",
+            &target.user,
+            "-d",
+            &target.database,
+        ])
+        .stdout(Stdio::from(file))
+        .stderr(Stdio::piped())
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        let _ = fs::remove_file(&partial);
+        return Err(eyre!(
+            "pg_dump failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    fs::rename(&partial, path)?;
+    Ok(())
+}
+
+/// Reads the archive's table of contents back through `pg_restore --list`
+/// and returns the number of entries.
+pub async fn verify_backup(target: &DatabaseTarget, path: &Path) -> Result<usize> {
+    let output = Runtime::current()
+        .command()
+        .args(["exec", "-i", &target.container, "pg_restore", "--list"])
+        .stdin(Stdio::from(fs::File::open(path)?))
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        return Err(eyre!(
+            "backup {} is not readable: {}",
+            path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let entries = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| !line.is_empty() && !line.starts_with(';'))
+        .count();
+    if entries == 0 {
+        return Err(eyre!("backup {} contains no objects", path.display()));
+    }
+    Ok(entries)
+}
+
+pub async fn restore_database(target: &DatabaseTarget, path: &Path) -> Result<()> {
+    let output = Runtime::current()
+        .command()
+        .args([
+            "exec",
+            "-i",
+            &target.container,
+            "pg_restore",
+            "--clean",
+            "--if-exists",
+            "--no-owner",
+            "--single-transaction",
+            "-U",
+            &target.user,
+            "-d",
+            &target.database,
+        ])
+        .stdin(Stdio::from(fs::File::open(path)?))
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        return Err(eyre!(
+            "pg_restore failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// Number of Keycloak realms in the database, used to check a restore.
+pub async fn count_realms(target: &DatabaseTarget) -> Result<usize> {
+    Ok(target
+        .psql("SELECT count(*) FROM realm")
+        .await?
+        .trim()
+        .parse()?)
+}
+
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+pub(super) enum BackupAction {
+    Create,
+    Restore,
+    UpgradePostgres,
+    Back,
+}
+
+impl App {
+    pub(super) fn load_backups(&mut self) {
+        match list_backups(&backup_dir()) {
+            Ok(backups) => {
+                self.backups = backups;
+                if self.backups.is_empty() {
+                    self.backup_message = Some(format!(
+                        "No backups yet. Press B to create one in {}",
+                        backup_dir().display()
+                    ));
+                }
+            }
+            Err(e) => {
+                self.backups.clear();
+                self.backup_message = Some(format!("Failed to list backups: {}", e));
+            }
+        }
+        self.backup_selection = self
+            .backup_selection
+            .min(self.backups.len().saturating_sub(1));
+        self.restore_pending = false;
+    }
+
+    pub(super) fn handle_backups_events(&mut self) -> Result<Option<BackupAction>> {
+        if event::poll(std::time::Duration::from_millis(100))?
+            && let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            if self.restore_pending {
+                self.restore_pending = false;
+                return Ok(match key.code {
+                    KeyCode::Char('y') | KeyCode::Char('Y') => Some(BackupAction::Restore),
+                    _ => {
+                        self.backup_message = Some("Restore cancelled".to_string());
+                        None
+                    }
+                });
+            }
+
+            match key.code {
+                KeyCode::Up => {
+                    self.backup_selection = self.backup_selection.saturating_sub(1);
+                }
+                KeyCode::Down if self.backup_selection + 1 < self.backups.len() => {
+                    self.backup_selection += 1;
+                }
+                KeyCode::Char('b') | KeyCode::Char('B') => return Ok(Some(BackupAction::Create)),
+                KeyCode::Char('u') | KeyCode::Char('U') => {
+                    return Ok(Some(BackupAction::UpgradePostgres));
+                }
+                KeyCode::Enter | KeyCode::Char('r') | KeyCode::Char('R') => {
+                    if let Some(backup) = self.backups.get(self.backup_selection) {
+                        self.restore_pending = true;
+                        self.backup_message = Some(format!(
+                            "Restore {}? The current database is replaced. Press Y to confirm.",
+                            backup.file_name
+                        ));
+                    }
+                }
+                KeyCode::Esc => return Ok(Some(BackupAction::Back)),
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    self.running = false;
+                }
+                _ => {}
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Dumps the identity database into `backups/` and checks the archive can
+    /// be read back. Returns the path of the new backup.
+    pub(super) async fn create_backup(&mut self, terminal: &mut impl Surface) -> Result<PathBuf> {
+        self.load_project().await?;
+        let target = DatabaseTarget::from_project(&self.project);
+
+        let dir = create_backup_dir()?;
+        let path = dir.join(backup_file_name(Local::now().naive_local()));
+
+        self.current_service = DATABASE_SERVICE.to_string();
+        self.progress = 10.0;
+        self.add_log_and_redraw(
+            terminal,
+            &format!(
+                "💾 Dumping database {} from {} to {}",
+                target.database,
+                target.container,
+                path.display()
+            ),
+        );
+        dump_database(&target, &path).await?;
+
+        self.progress = 70.0;
+        self.add_log_and_redraw(terminal, "🔍 Verifying backup archive...");
+        let entries = verify_backup(&target, &path).await?;
+
+        let size = fs::metadata(&path)?.len();
+        self.progress = 100.0;
+        self.add_log_and_redraw(
+            terminal,
+            &format!(
+                "✅ Backup complete: {} ({}, {} objects)",
+                path.display(),
+                format_size(size),
+                entries
+            ),
+        );
+        Ok(path)
+    }
+
+    /// Stops Keycloak, replaces the database with `path` and brings Keycloak
+    /// back. When `recreate` names a service, that service is recreated from
+    /// its local image as part of the restore (used to roll back a failed
+    /// update). Keycloak is restarted even when the restore or the recreate
+    /// fails; the first error is returned after that.
+    pub(super) async fn restore_backup(
+        &mut self,
+        terminal: &mut impl Surface,
+        path: &Path,
+        recreate: Option<&str>,
+    ) -> Result<()> {
+        let compose_cmd = self.load_project().await?;
+        let target = DatabaseTarget::from_project(&self.project);
+
+        if recreate == Some(DATABASE_SERVICE) {
+            self.recreate_service(terminal, &compose_cmd, DATABASE_SERVICE)
+                .await?;
+            self.init_service_health();
+            self.service_health
+                .retain(|entry| entry.service == DATABASE_SERVICE);
+            self.wait_for_health(terminal).await?;
+        }
+
+        self.add_log_and_redraw(
+            terminal,
+            &format!("⏹  Stopping {} before restore...", KEYCLOAK_SERVICE),
+        );
+        if !self
+            .run_compose_step(terminal, &compose_cmd, &["stop", KEYCLOAK_SERVICE], &[])
+            .await?
+        {
+            return Err(eyre!("Failed to stop {}", KEYCLOAK_SERVICE));
+        }
+
+        self.progress = 10.0;
+        self.current_service = DATABASE_SERVICE.to_string();
+        self.add_log_and_redraw(
+            terminal,
+            &format!(
+                "♻️  Restoring {} into {}...",
+                path.display(),
+                target.database
+            ),
+        );
+        let restored = restore_database(&target, path).await;
+        if let Err(e) = &restored {
+            self.add_log(&format!("❌ {}", e));
+        }
+
+        self.progress = 60.0;
+        let recreated = match recreate {
+            Some(service) if service != DATABASE_SERVICE => {
+                self.recreate_service(terminal, &compose_cmd, service).await
+            }
+            _ => Ok(()),
+        };
+        if let Err(e) = &recreated {
+            self.add_log(&format!("❌ {}", e));
+        }
+        // A Keycloak that failed to recreate is started from its old container.
+        let started = if recreate != Some(KEYCLOAK_SERVICE) || recreated.is_err() {
+            self.add_log_and_redraw(terminal, &format!("▶️  Starting {}...", KEYCLOAK_SERVICE));
+            match self
+                .run_compose_step(terminal, &compose_cmd, &["start", KEYCLOAK_SERVICE], &[])
+                .await
+            {
+                Ok(true) => Ok(()),
+                Ok(false) => Err(eyre!("Failed to start {}", KEYCLOAK_SERVICE)),
+                Err(e) => Err(e),
+            }
+        } else {
+            Ok(())
+        };
+        restored?;
+        recreated?;
+        started?;
+
+        self.progress = 80.0;
+        self.init_service_health();
+        self.wait_for_health(terminal).await?;
+
+        let realms = count_realms(&target).await?;
+        if realms == 0 {
+            return Err(eyre!("The restored database contains no realms"));
+        }
+        self.add_log_and_redraw(
+            terminal,
+            &format!("✅ Restore verified: {} realm(s) present", realms),
+        );
+        Ok(())
+    }
+
+    pub(super) async fn run_backup_task(&mut self, terminal: &mut impl Surface, restore: bool) {
+        self.logs.clear();
+        self.progress = 0.0;
+        self.service_health.clear();
+
+        let result = if restore {
+            self.state = AppState::Restoring;
+            match self.backups.get(self.backup_selection).cloned() {
+                Some(backup) => self
+                    .restore_backup(terminal, &backup.path, None)
+                    .await
+                    .map(|_| format!("Restored {}", backup.file_name)),
+                None => Err(eyre!("No backup selected")),
+            }
+        } else {
+            self.state = AppState::BackingUp;
+            self.create_backup(terminal)
+                .await
+                .map(|path| format!("Created {}", path.display()))
+        };
+
+        match result {
+            Ok(message) => {
+                self.state = AppState::Backups;
+                self.load_backups();
+                self.backup_message = Some(message);
+            }
+            Err(e) => {
+                let action = if restore { "Restore" } else { "Backup" };
+                self.state = AppState::Error(format!("{} failed: {}", action, e));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_backups_newest_first() {
+        let dir = std::env::temp_dir().join(format!("nqrust-identity-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let older = NaiveDateTime::parse_from_str("20240101-090000", TIMESTAMP_FORMAT).unwrap();
+        let newer = NaiveDateTime::parse_from_str("20240315-183000", TIMESTAMP_FORMAT).unwrap();
+        fs::write(dir.join(backup_file_name(older)), b"old").unwrap();
+        fs::write(dir.join(backup_file_name(newer)), b"newer").unwrap();
+        fs::write(dir.join("identity-20240401-000000.partial"), b"").unwrap();
+        fs::write(dir.join("notes.txt"), b"").unwrap();
+
+        let backups = list_backups(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(backups.len(), 2);
+        assert_eq!(backups[0].file_name, "identity-20240315-183000.dump");
+        assert_eq!(backups[0].size, 5);
+        assert_eq!(backups[1].created, older);
+        assert!(list_backups(&dir).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_database_target_from_project() {
+        let project = ComposeProject::parse(
+            r#"{
+                "name": "identity",
+                "services": {
+                    "postgres": {
+                        "container_name": "identity-db",
+                        "image": "postgres:16-alpine",
+                        "environment": { "POSTGRES_DB": "identity", "POSTGRES_USER": "identity" }
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            DatabaseTarget::from_project(&project),
+            DatabaseTarget {
+                container: "identity-db".to_string(),
+                user: "identity".to_string(),
+                database: "identity".to_string(),
+            }
+        );
+        assert_eq!(format_size(1536), "1.5 KB");
+    }
+}
@@ -0,0 +1,397 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use color_eyre::{Result, eyre::eyre};
+use ring::digest::{Context, SHA256};
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+
+use super::App;
+use super::compose::ComposeProject;
+use super::surface::Surface;
+use crate::docker::DockerClient;
+use crate::env_file::EnvFile;
+use crate::registry::RegistryConfig;
+use crate::runtime::Runtime;
+use crate::utils;
+
+const BUNDLE_VERSION: u32 = 1;
+const MANIFEST_FILE: &str = "manifest.json";
+const IMAGES_FILE: &str = "images.tar";
+const COMPOSE_FILE: &str = "docker-compose.yaml";
+const COMPOSE_CANDIDATES: [&str; 4] = [
+    "docker-compose.yaml",
+    "docker-compose.yml",
+    "compose.yaml",
+    "compose.yml",
+];
+
+/// `manifest.json` at the root of a bundle.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub version: u32,
+    pub created: DateTime<Utc>,
+    pub installer_version: String,
+    /// `sha256:<hex>` of `images.tar`.
+    pub images_sha256: String,
+    pub images: Vec<BundleImage>,
+}
+
+/// One service image saved in a bundle.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleImage {
+    pub service: String,
+    pub reference: String,
+    /// Variable the compose file reads the image from, pinned on install.
+    #[serde(default)]
+    pub env_key: Option<String>,
+    /// Local image ID (`sha256:...`), which `docker load` preserves.
+    pub id: String,
+    /// `repo@sha256:...` the image was pulled as, for reference.
+    #[serde(default)]
+    pub repo_digest: Option<String>,
+}
+
+impl BundleManifest {
+    /// Images whose loaded ID differs from the manifest, as messages.
+    /// `loaded` maps references to the IDs `docker image inspect` reports.
+    pub fn image_mismatches(&self, loaded: &BTreeMap<String, String>) -> Vec<String> {
+        self.images
+            .iter()
+            .filter_map(|image| match loaded.get(&image.reference) {
+                Some(id) if *id == image.id => None,
+                Some(id) => Some(format!(
+                    "{} loaded as {} but the manifest lists {}",
+                    image.reference, id, image.id
+                )),
+                None => Some(format!("{} is missing after docker load", image.reference)),
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct InspectedImage {
+    #[serde(rename = "Id")]
+    id: String,
+    #[serde(rename = "RepoDigests", default)]
+    repo_digests: Vec<String>,
+}
+
+async fn inspect_image(reference: &str) -> Result<Option<InspectedImage>> {
+    Ok(DockerClient::from_env()?.inspect_image(reference).await?)
+}
+
+async fn run(program: &str, args: &[&str]) -> Result<()> {
+    let output = Command::new(program).args(args).output().await?;
+    if !output.status.success() {
+        return Err(eyre!(
+            "{} {} failed: {}",
+            program,
+            args.first().copied().unwrap_or_default(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// `sha256:<hex>` of a file, read in chunks so multi-gigabyte image archives
+/// are not loaded into memory.
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = fs::File::open(path)?;
+    let mut context = Context::new(&SHA256);
+    let mut buffer = vec![0u8; 1 << 20];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        context.update(&buffer[..read]);
+    }
+    let hex: String = context
+        .finish()
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    Ok(format!("sha256:{}", hex))
+}
+
+fn staging_dir() -> Result<PathBuf> {
+    let dir = std::env::temp_dir().join(format!("nqrust-identity-bundle-{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+fn path_arg(path: &Path) -> Result<&str> {
+    path.to_str()
+        .ok_or_else(|| eyre!("{} is not valid UTF-8", path.display()))
+}
+
+impl App {
+    /// Pulls every stack image and writes them, the compose file and a
+    /// manifest with their IDs into a gzipped tarball at `path`.
+    pub(super) async fn create_bundle(
+        &mut self,
+        terminal: &mut impl Surface,
+        path: &Path,
+    ) -> Result<BundleManifest> {
+        // tar runs with `-C`, so relative paths must be resolved up front.
+        let path = &std::path::absolute(path)?;
+        let root = utils::project_root();
+        self.ensure_compose_bundle().await?;
+        let compose_cmd = self.detect_compose_command().await?;
+        let config = RegistryConfig::configured()?;
+        self.apply_image_rewrites(&config, &compose_cmd).await?;
+        let compose_cmd = self.load_project().await?;
+        let variables = ComposeProject::load_uninterpolated(&compose_cmd, &root)
+            .await
+            .map(|raw| raw.image_variables())
+            .unwrap_or_default();
+
+        let build_services = self.project.build_services();
+        if !build_services.is_empty() {
+            self.add_log_and_redraw(
+                terminal,
+                &format!(
+                    "⚠️  {} build locally and are not bundled",
+                    build_services.join(", ")
+                ),
+            );
+        }
+
+        let mut images = Vec::new();
+        for service in self.project.pull_services() {
+            let Some(reference) = self.project.image(&service).map(str::to_string) else {
+                continue;
+            };
+            self.current_service = service.clone();
+            self.pull_service(terminal, &service).await?;
+            let inspected = inspect_image(&reference)
+                .await?
+                .ok_or_else(|| eyre!("{} is not available locally after pulling", reference))?;
+            self.add_log_and_redraw(terminal, &format!("📦 {} is {}", reference, inspected.id));
+            images.push(BundleImage {
+                env_key: variables.get(&service).cloned(),
+                service,
+                reference,
+                id: inspected.id,
+                repo_digest: inspected.repo_digests.into_iter().next(),
+            });
+        }
+        if images.is_empty() {
+            return Err(eyre!(
+                "The compose project has no registry images to bundle"
+            ));
+        }
+
+        let staging = staging_dir()?;
+        let result = async {
+            let compose_path = COMPOSE_CANDIDATES
+                .iter()
+                .map(|name| root.join(name))
+                .find(|path| path.exists())
+                .ok_or_else(|| eyre!("No compose file found in {}", root.display()))?;
+            fs::copy(&compose_path, staging.join(COMPOSE_FILE))?;
+
+            let images_path = staging.join(IMAGES_FILE);
+            self.add_log_and_redraw(terminal, &format!("💾 Saving {} image(s)...", images.len()));
+            let runtime = Runtime::current();
+            let mut args = vec!["save", "-o", path_arg(&images_path)?];
+            if runtime == Runtime::Podman {
+                // Podman only writes several images into one archive when asked.
+                args.push("--multi-image-archive");
+            }
+            args.extend(images.iter().map(|image| image.reference.as_str()));
+            run(runtime.binary(), &args).await?;
+
+            let manifest = BundleManifest {
+                version: BUNDLE_VERSION,
+                created: Utc::now(),
+                installer_version: env!("CARGO_PKG_VERSION").to_string(),
+                images_sha256: sha256_file(&images_path)?,
+                images,
+            };
+            fs::write(
+                staging.join(MANIFEST_FILE),
+                serde_json::to_string_pretty(&manifest)?,
+            )?;
+
+            self.add_log_and_redraw(terminal, &format!("🗜️  Writing {}...", path.display()));
+            run(
+                "tar",
+                &[
+                    "-czf",
+                    path_arg(path)?,
+                    "-C",
+                    path_arg(&staging)?,
+                    MANIFEST_FILE,
+                    COMPOSE_FILE,
+                    IMAGES_FILE,
+                ],
+            )
+            .await?;
+            Ok(manifest)
+        }
+        .await;
+
+        let _ = fs::remove_dir_all(&staging);
+        result
+    }
+
+    /// Unpacks a bundle, checks the image archive against the manifest,
+    /// loads it into docker and verifies every image ID. Writes the bundle's
+    /// compose file when the project has none, and pins the bundled
+    /// references in `.env` so compose uses exactly those images.
+    pub(super) async fn load_bundle(
+        &mut self,
+        terminal: &mut impl Surface,
+        path: &Path,
+    ) -> Result<BundleManifest> {
+        if !path.exists() {
+            return Err(eyre!("Bundle {} does not exist", path.display()));
+        }
+        let path = &std::path::absolute(path)?;
+
+        let staging = staging_dir()?;
+        let result = async {
+            self.add_log_and_redraw(terminal, &format!("📂 Unpacking {}...", path.display()));
+            run("tar", &["-xf", path_arg(path)?, "-C", path_arg(&staging)?]).await?;
+
+            let manifest: BundleManifest = serde_json::from_str(
+                &fs::read_to_string(staging.join(MANIFEST_FILE)).map_err(|e| {
+                    eyre!(
+                        "{} has no readable {}: {}",
+                        path.display(),
+                        MANIFEST_FILE,
+                        e
+                    )
+                })?,
+            )?;
+            if manifest.version != BUNDLE_VERSION {
+                return Err(eyre!(
+                    "Bundle format {} is not supported (expected {})",
+                    manifest.version,
+                    BUNDLE_VERSION
+                ));
+            }
+
+            let images_path = staging.join(IMAGES_FILE);
+            self.add_log_and_redraw(terminal, "🔍 Checking the image archive checksum...");
+            let checksum = sha256_file(&images_path)?;
+            if checksum != manifest.images_sha256 {
+                return Err(eyre!(
+                    "{} is corrupt: checksum {} does not match the manifest's {}",
+                    IMAGES_FILE,
+                    checksum,
+                    manifest.images_sha256
+                ));
+            }
+
+            self.add_log_and_redraw(
+                terminal,
+                &format!(
+                    "📥 Loading {} image(s) into docker...",
+                    manifest.images.len()
+                ),
+            );
+            run(
+                Runtime::current().binary(),
+                &["load", "-i", path_arg(&images_path)?],
+            )
+            .await?;
+
+            let mut loaded = BTreeMap::new();
+            for image in &manifest.images {
+                if let Some(inspected) = inspect_image(&image.reference).await? {
+                    loaded.insert(image.reference.clone(), inspected.id);
+                }
+            }
+            let mismatches = manifest.image_mismatches(&loaded);
+            if !mismatches.is_empty() {
+                return Err(eyre!(
+                    "Image verification failed: {}",
+                    mismatches.join("; ")
+                ));
+            }
+            self.add_log_and_redraw(terminal, "✅ All bundled image IDs match the manifest");
+
+            let root = utils::project_root();
+            if !COMPOSE_CANDIDATES
+                .iter()
+                .any(|name| root.join(name).exists())
+            {
+                fs::copy(staging.join(COMPOSE_FILE), root.join(COMPOSE_FILE))?;
+                self.add_log(&format!("📄 Wrote {} from the bundle", COMPOSE_FILE));
+            }
+
+            let env_path = Self::env_path();
+            let mut env = EnvFile::load(&env_path)?;
+            for image in &manifest.images {
+                if let Some(key) = &image.env_key {
+                    env.set(key, &image.reference);
+                    self.add_log(&format!("📌 Pinned {}={} in .env", key, image.reference));
+                }
+            }
+            env.save(&env_path)?;
+            Ok(manifest)
+        }
+        .await;
+
+        let _ = fs::remove_dir_all(&staging);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest_verification_and_checksum() {
+        let manifest: BundleManifest = serde_json::from_str(
+            r#"{
+                "version": 1,
+                "created": "2026-10-01T12:00:00Z",
+                "installer_version": "0.1.0",
+                "images_sha256": "sha256:00",
+                "images": [
+                    { "service": "identity", "reference": "ghcr.io/nexusquantum/nqrust-identity:1.4.2",
+                      "env_key": "KEYCLOAK_IMAGE", "id": "sha256:aaaa" },
+                    { "service": "postgres", "reference": "postgres:16-alpine", "id": "sha256:bbbb" }
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(manifest.images[1].env_key, None);
+
+        let mut loaded = BTreeMap::from([
+            (
+                "ghcr.io/nexusquantum/nqrust-identity:1.4.2".to_string(),
+                "sha256:aaaa".to_string(),
+            ),
+            ("postgres:16-alpine".to_string(), "sha256:bbbb".to_string()),
+        ]);
+        assert!(manifest.image_mismatches(&loaded).is_empty());
+
+        loaded.insert("postgres:16-alpine".to_string(), "sha256:cccc".to_string());
+        loaded.remove("ghcr.io/nexusquantum/nqrust-identity:1.4.2");
+        let mismatches = manifest.image_mismatches(&loaded);
+        assert_eq!(mismatches.len(), 2);
+        assert!(mismatches[0].contains("missing"));
+        assert!(mismatches[1].contains("sha256:cccc"));
+
+        let dir = std::env::temp_dir().join(format!("nqrust-identity-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("abc");
+        fs::write(&file, "abc").unwrap();
+        assert_eq!(
+            sha256_file(&file).unwrap(),
+            "sha256:ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
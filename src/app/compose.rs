@@ -0,0 +1,710 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use color_eyre::{Result, eyre::eyre};
+use serde::Deserialize;
+use serde_json::Value;
+use tokio::process::Command;
+
+use crate::utils;
+
+/// The standalone podman-compose. `podman compose` handing over to it is
+/// run as this directly, so the rest of the installer can tell them apart.
+pub const PODMAN_COMPOSE: &str = "podman-compose";
+
+/// The compose project as resolved by `compose config --format json`, with
+/// `.env` substitution and defaults already applied by Compose itself.
+/// podman-compose only prints YAML, which is read into the same shape.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ComposeProject {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub services: BTreeMap<String, ComposeService>,
+    #[serde(default, deserialize_with = "resources")]
+    pub volumes: BTreeMap<String, ComposeResource>,
+    #[serde(default, deserialize_with = "resources")]
+    pub networks: BTreeMap<String, ComposeResource>,
+    /// podman-compose names containers `<project>_<service>_1`.
+    #[serde(skip)]
+    underscore_names: bool,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ComposeService {
+    #[serde(default)]
+    pub container_name: Option<String>,
+    #[serde(default)]
+    pub image: Option<String>,
+    #[serde(default)]
+    pub build: Option<Value>,
+    #[serde(default)]
+    pub pull_policy: Option<String>,
+    #[serde(default, deserialize_with = "environment")]
+    pub environment: BTreeMap<String, Option<String>>,
+    #[serde(default)]
+    pub volumes: Vec<ServiceMount>,
+}
+
+/// A service's entry under `volumes:` in long syntax, as Compose prints it.
+/// The short `source:target[:mode]` form podman-compose keeps is read too.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(from = "MountSpec")]
+pub struct ServiceMount {
+    #[serde(rename = "type", default)]
+    pub kind: String,
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub target: String,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MountSpec {
+    Short(String),
+    Long {
+        #[serde(rename = "type", default)]
+        kind: String,
+        #[serde(default)]
+        source: Option<String>,
+        #[serde(default)]
+        target: String,
+    },
+}
+
+impl From<MountSpec> for ServiceMount {
+    fn from(spec: MountSpec) -> Self {
+        match spec {
+            MountSpec::Long {
+                kind,
+                source,
+                target,
+            } => Self {
+                kind,
+                source,
+                target,
+            },
+            MountSpec::Short(spec) => {
+                let mut parts = spec.splitn(3, ':');
+                let first = parts.next().unwrap_or_default().to_string();
+                match parts.next() {
+                    Some(target) => Self {
+                        kind: if first.starts_with(['/', '.', '~']) {
+                            "bind".to_string()
+                        } else {
+                            "volume".to_string()
+                        },
+                        source: Some(first),
+                        target: target.to_string(),
+                    },
+                    None => Self {
+                        kind: "volume".to_string(),
+                        source: None,
+                        target: first,
+                    },
+                }
+            }
+        }
+    }
+}
+
+/// `environment:` as a map, or as the `KEY=value` list compose files may use.
+/// Values are kept as strings whatever YAML type they were written as.
+fn environment<'de, D>(deserializer: D) -> Result<BTreeMap<String, Option<String>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Scalar {
+        String(String),
+        Bool(bool),
+        Integer(i64),
+        Float(f64),
+    }
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Spec {
+        Map(BTreeMap<String, Option<Scalar>>),
+        List(Vec<String>),
+    }
+
+    Ok(match Spec::deserialize(deserializer)? {
+        Spec::Map(map) => map
+            .into_iter()
+            .map(|(key, value)| {
+                let value = value.map(|value| match value {
+                    Scalar::String(value) => value,
+                    Scalar::Bool(value) => value.to_string(),
+                    Scalar::Integer(value) => value.to_string(),
+                    Scalar::Float(value) => value.to_string(),
+                });
+                (key, value)
+            })
+            .collect(),
+        Spec::List(list) => list
+            .into_iter()
+            .map(|entry| match entry.split_once('=') {
+                Some((key, value)) => (key.to_string(), Some(value.to_string())),
+                None => (entry, None),
+            })
+            .collect(),
+    })
+}
+
+/// Top-level `volumes:`/`networks:`, where a written compose file leaves
+/// entries without options empty (`identity_pgdata:`).
+fn resources<'de, D>(deserializer: D) -> Result<BTreeMap<String, ComposeResource>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let resources = BTreeMap::<String, Option<ComposeResource>>::deserialize(deserializer)?;
+    Ok(resources
+        .into_iter()
+        .map(|(key, resource)| (key, resource.unwrap_or_default()))
+        .collect())
+}
+
+/// A top-level volume or network; `name` is the resolved Docker object name.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ComposeResource {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub external: bool,
+}
+
+impl ComposeProject {
+    pub async fn load(compose_cmd: &[String], root: &Path) -> Result<Self> {
+        if is_podman_compose(compose_cmd) {
+            // podman-compose has no `--format json`; its plain `config` is
+            // the interpolated project as YAML.
+            let yaml = config_output(compose_cmd, root, &["config"]).await?;
+            return Self::parse_yaml(&yaml, root);
+        }
+        let json = config_output(compose_cmd, root, &["config", "--format", "json"]).await?;
+        Self::parse(&json)
+    }
+
+    pub fn parse(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Reads a podman-compose project from YAML. Anything podman-compose
+    /// printed before the document (1.0.x logs its version there) is
+    /// skipped, and the project is named after `root` when the file does not
+    /// name it, as podman-compose does.
+    pub fn parse_yaml(yaml: &str, root: &Path) -> Result<Self> {
+        let start = yaml
+            .lines()
+            .position(is_top_level_key)
+            .ok_or_else(|| eyre!("podman-compose printed no compose project"))?;
+        let document = yaml.lines().skip(start).collect::<Vec<_>>().join("\n");
+
+        let mut project: Self = serde_yaml::from_str(&document)?;
+        if project.name.is_empty() {
+            project.name = root
+                .file_name()
+                .map(|name| name.to_string_lossy().to_lowercase())
+                .unwrap_or_default()
+                .chars()
+                .filter(|c| c.is_ascii_alphanumeric() || *c == '_' || *c == '-')
+                .collect();
+        }
+        project.underscore_names = true;
+        Ok(project)
+    }
+
+    /// Services that declare a build context and must be built locally.
+    pub fn build_services(&self) -> Vec<String> {
+        self.services
+            .iter()
+            .filter(|(_, service)| service.build.is_some())
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// Services whose prebuilt image should be pulled from a registry.
+    pub fn pull_services(&self) -> Vec<String> {
+        self.services
+            .iter()
+            .filter(|(_, service)| {
+                service.image.is_some()
+                    && service.build.is_none()
+                    && !matches!(service.pull_policy.as_deref(), Some("never" | "build"))
+            })
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    pub fn service_names(&self) -> Vec<String> {
+        self.services.keys().cloned().collect()
+    }
+
+    /// Container name of `service`, falling back to Compose's
+    /// `<project>-<service>-1` naming when none is set explicitly.
+    pub fn container_name(&self, service: &str) -> String {
+        self.services
+            .get(service)
+            .and_then(|service| service.container_name.clone())
+            .unwrap_or_else(|| {
+                if self.underscore_names {
+                    format!("{}_{}_1", self.name, service)
+                } else {
+                    format!("{}-{}-1", self.name, service)
+                }
+            })
+    }
+
+    /// Finds the service a line of compose output refers to, either by service
+    /// name (pull/build lines) or container name (`Container identity-db  Started`).
+    pub fn service_for_line(&self, line: &str) -> Option<&str> {
+        line.split(|c: char| c.is_whitespace() || c == '"' || c == ':')
+            .filter(|token| !token.is_empty())
+            .find_map(|token| {
+                self.services.keys().find(|service| {
+                    token == service.as_str() || token == self.container_name(service)
+                })
+            })
+            .map(String::as_str)
+    }
+
+    /// Value of an environment variable of `service` after interpolation.
+    pub fn environment(&self, service: &str, key: &str) -> Option<String> {
+        self.services
+            .get(service)?
+            .environment
+            .get(key)
+            .cloned()
+            .flatten()
+    }
+
+    /// Docker names of the volumes and networks `compose down` owns, skipping
+    /// external ones.
+    pub fn volume_names(&self) -> Vec<String> {
+        Self::resource_names(&self.name, &self.volumes)
+    }
+
+    pub fn network_names(&self) -> Vec<String> {
+        Self::resource_names(&self.name, &self.networks)
+    }
+
+    fn resource_names(project: &str, resources: &BTreeMap<String, ComposeResource>) -> Vec<String> {
+        resources
+            .iter()
+            .filter(|(_, resource)| !resource.external)
+            .map(|(key, resource)| {
+                resource
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| format!("{}_{}", project, key))
+            })
+            .collect()
+    }
+
+    /// The service whose image belongs to `repository` (any tag or digest).
+    pub fn service_for_image(&self, repository: &str) -> Option<&str> {
+        self.services
+            .iter()
+            .find(|(_, service)| {
+                service
+                    .image
+                    .as_deref()
+                    .is_some_and(|image| image_repository(image) == repository)
+            })
+            .map(|(name, _)| name.as_str())
+    }
+
+    pub fn image(&self, service: &str) -> Option<&str> {
+        self.services
+            .get(service)
+            .and_then(|service| service.image.as_deref())
+    }
+
+    /// The project as written, without `.env` substitution, so `${VAR:-...}`
+    /// expressions can be inspected. podman-compose cannot print that, so
+    /// the compose file is read directly for it.
+    pub async fn load_uninterpolated(compose_cmd: &[String], root: &Path) -> Result<Self> {
+        if is_podman_compose(compose_cmd) {
+            let path = utils::compose_file(root)
+                .ok_or_else(|| eyre!("No compose file in {}", root.display()))?;
+            return Self::parse_yaml(&tokio::fs::read_to_string(&path).await?, root);
+        }
+        let json = config_output(
+            compose_cmd,
+            root,
+            &["config", "--no-interpolate", "--format", "json"],
+        )
+        .await?;
+        Self::parse(&json)
+    }
+
+    /// For each service whose `image:` is a single variable such as
+    /// `${KEYCLOAK_IMAGE:-ghcr.io/...}`, the variable's name. Only meaningful
+    /// on an uninterpolated project.
+    pub fn image_variables(&self) -> BTreeMap<String, String> {
+        self.services
+            .iter()
+            .filter_map(|(name, service)| {
+                let variable = variable_name(service.image.as_deref()?)?;
+                Some((name.clone(), variable.to_string()))
+            })
+            .collect()
+    }
+
+    /// The variable a top-level volume's `name:` is read from, on an
+    /// uninterpolated project.
+    pub fn volume_variable(&self, volume: &str) -> Option<&str> {
+        variable_name(self.volumes.get(volume)?.name.as_deref()?)
+    }
+
+    /// The named volume `service` mounts, as `(volume key, Docker volume name,
+    /// mount path)`. Bind mounts are skipped.
+    pub fn named_mount(&self, service: &str) -> Option<(String, String, String)> {
+        let mount = self
+            .services
+            .get(service)?
+            .volumes
+            .iter()
+            .find(|mount| mount.kind == "volume")?;
+        let key = mount.source.clone()?;
+        let name = self
+            .volumes
+            .get(&key)
+            .and_then(|volume| volume.name.clone())
+            .unwrap_or_else(|| format!("{}_{}", self.name, key));
+        Some((key, name, mount.target.clone()))
+    }
+}
+
+/// Name of the variable when `value` is exactly one `${NAME...}` or `$NAME`
+/// expression.
+pub fn variable_name(value: &str) -> Option<&str> {
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+
+    let name = if let Some(braced) = value.strip_prefix("${") {
+        let body = braced.strip_suffix('}')?;
+        if body.contains("${") {
+            return None;
+        }
+        let end = body.find(|c: char| !is_name_char(c)).unwrap_or(body.len());
+        let rest = &body[end..];
+        if !(rest.is_empty() || [":-", "-", ":?", "?"].iter().any(|op| rest.starts_with(op))) {
+            return None;
+        }
+        &body[..end]
+    } else {
+        let name = value.strip_prefix('$')?;
+        if !name.chars().all(is_name_char) {
+            return None;
+        }
+        name
+    };
+
+    (!name.is_empty()).then_some(name)
+}
+
+/// Strips the tag and digest from an image reference:
+/// `ghcr.io/org/app:1.2@sha256:...` becomes `ghcr.io/org/app`.
+pub fn image_repository(reference: &str) -> &str {
+    let reference = reference.split('@').next().unwrap_or(reference);
+    match reference.rfind(':') {
+        Some(colon) if !reference[colon..].contains('/') => &reference[..colon],
+        _ => reference,
+    }
+}
+
+/// Tag of an image reference, if it names one:
+/// `ghcr.io/org/app:1.2@sha256:...` gives `1.2`.
+pub fn image_tag(reference: &str) -> Option<&str> {
+    let repository = image_repository(reference);
+    let rest = reference.strip_prefix(repository)?;
+    let tag = rest.strip_prefix(':')?;
+    Some(tag.split('@').next().unwrap_or(tag))
+}
+
+/// Starts a compose invocation (`docker compose` or `docker-compose`) rooted
+/// at the project directory.
+pub fn command(compose_cmd: &[String], root: &Path) -> Command {
+    let mut cmd = Command::new(&compose_cmd[0]);
+    cmd.args(&compose_cmd[1..]).current_dir(root);
+    cmd
+}
+
+/// Whether `compose_cmd` runs podman-compose.
+pub fn is_podman_compose(compose_cmd: &[String]) -> bool {
+    compose_cmd
+        .first()
+        .and_then(|program| Path::new(program).file_name())
+        .is_some_and(|name| name == PODMAN_COMPOSE)
+}
+
+/// Whether `compose version` output comes from podman-compose, run directly
+/// or as the provider behind `podman compose`.
+pub fn reports_podman_compose(version_output: &str) -> bool {
+    version_output.contains(PODMAN_COMPOSE)
+}
+
+/// `args` as `compose_cmd` understands them. podman-compose has no
+/// `--pull <policy>` (podman pulls missing images itself), no `ps --all`
+/// (it always lists stopped containers) and no `down --rmi`, so those are
+/// dropped for it.
+pub fn args<'a>(compose_cmd: &[String], args: &[&'a str]) -> Vec<&'a str> {
+    if !is_podman_compose(compose_cmd) {
+        return args.to_vec();
+    }
+    let mut kept = Vec::new();
+    let mut args = args.iter();
+    while let Some(&arg) = args.next() {
+        match arg {
+            "--all" => {}
+            "--pull" | "--rmi" => {
+                args.next();
+            }
+            _ => kept.push(arg),
+        }
+    }
+    kept
+}
+
+async fn config_output(compose_cmd: &[String], root: &Path, args: &[&str]) -> Result<String> {
+    let output = command(compose_cmd, root).args(args).output().await?;
+    if !output.status.success() {
+        return Err(eyre!(
+            "compose config failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// A line starting a compose document, such as `services:` or `name: x`.
+fn is_top_level_key(line: &str) -> bool {
+    line.split_once(':').is_some_and(|(key, rest)| {
+        !key.is_empty()
+            && key.chars().all(|c| c.is_ascii_lowercase() || c == '_')
+            && (rest.is_empty() || rest.starts_with(' '))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"{
+        "name": "identity",
+        "services": {
+            "identity": {
+                "container_name": "identity",
+                "image": "ghcr.io/nexusquantum/nqrust-identity:latest",
+                "pull_policy": "always"
+            },
+            "postgres": {
+                "container_name": "identity-db",
+                "image": "postgres:16-alpine",
+                "volumes": [
+                    { "type": "bind", "source": "/srv/identity/init", "target": "/docker-entrypoint-initdb.d" },
+                    { "type": "volume", "source": "identity_pgdata", "target": "/var/lib/postgresql/data" }
+                ]
+            },
+            "theme-builder": {
+                "build": { "context": "/srv/identity/theme", "dockerfile": "Dockerfile" },
+                "image": "identity-theme:local"
+            }
+        },
+        "networks": { "default": { "name": "identity_default" } },
+        "volumes": {
+            "identity_pgdata": { "name": "identity_identity_pgdata" },
+            "shared": { "name": "shared-certs", "external": true }
+        }
+    }"#;
+
+    #[test]
+    fn test_pull_and_build_services() {
+        let project = ComposeProject::parse(CONFIG).unwrap();
+
+        assert_eq!(project.pull_services(), vec!["identity", "postgres"]);
+        assert_eq!(project.build_services(), vec!["theme-builder"]);
+        assert_eq!(project.image("postgres"), Some("postgres:16-alpine"));
+        assert_eq!(project.volume_names(), vec!["identity_identity_pgdata"]);
+        assert_eq!(project.network_names(), vec!["identity_default"]);
+        assert_eq!(
+            project.named_mount("postgres"),
+            Some((
+                "identity_pgdata".to_string(),
+                "identity_identity_pgdata".to_string(),
+                "/var/lib/postgresql/data".to_string()
+            ))
+        );
+        assert_eq!(
+            project.service_for_image("ghcr.io/nexusquantum/nqrust-identity"),
+            Some("identity")
+        );
+        assert_eq!(image_repository("localhost:5000/app"), "localhost:5000/app");
+        assert_eq!(
+            image_repository("localhost:5000/app:1.2@sha256:abc"),
+            "localhost:5000/app"
+        );
+        assert_eq!(image_tag("localhost:5000/app:1.2@sha256:abc"), Some("1.2"));
+        assert_eq!(image_tag("localhost:5000/app"), None);
+    }
+
+    #[test]
+    fn test_variable_name() {
+        assert_eq!(
+            variable_name("${KEYCLOAK_IMAGE:-ghcr.io/nexusquantum/nqrust-identity:latest}"),
+            Some("KEYCLOAK_IMAGE")
+        );
+        assert_eq!(variable_name("${POSTGRES_IMAGE}"), Some("POSTGRES_IMAGE"));
+        assert_eq!(variable_name("$APP_IMAGE"), Some("APP_IMAGE"));
+        assert_eq!(variable_name("${REGISTRY}/app:${TAG}"), None);
+        assert_eq!(variable_name("postgres:16-alpine"), None);
+    }
+
+    #[test]
+    fn test_service_for_line() {
+        let project = ComposeProject::parse(CONFIG).unwrap();
+
+        assert_eq!(project.container_name("postgres"), "identity-db");
+        assert_eq!(
+            project.container_name("theme-builder"),
+            "identity-theme-builder-1"
+        );
+        assert_eq!(
+            project.service_for_line(" Container identity-db  Started"),
+            Some("postgres")
+        );
+        assert_eq!(
+            project.service_for_line(" identity Pulling"),
+            Some("identity")
+        );
+        assert_eq!(
+            project.service_for_line(" Container identity-theme-builder-1  Created"),
+            Some("theme-builder")
+        );
+        assert_eq!(
+            project.service_for_line(" Network identity_default  Created"),
+            None
+        );
+    }
+
+    /// podman-compose 1.0.6 `config`: its banner, then the interpolated
+    /// project with short volume syntax and options-less volumes.
+    #[cfg(unix)]
+    const PODMAN_COMPOSE_CONFIG: &str = "podman-compose version: 1.0.6
+['podman', '--version', '']
+using podman version: 4.9.4
+services:
+  identity:
+    environment:
+      KC_DB_PASSWORD: s3cr3t
+      KC_HEALTH_ENABLED: 'true'
+    image: ghcr.io/nexusquantum/nqrust-identity:latest
+    pull_policy: always
+    container_name: identity
+  postgres:
+    environment:
+      - POSTGRES_USER=identity
+      - POSTGRES_PASSWORD=s3cr3t
+    image: postgres:16-alpine
+    volumes:
+    - identity_pgdata:/var/lib/postgresql/data
+  theme:
+    image: identity-theme:local
+    build: ./theme
+volumes:
+  identity_pgdata: null
+";
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_podman_compose_project() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = std::env::temp_dir().join(format!("identity-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("docker-compose.yaml"), utils::COMPOSE_TEMPLATE).unwrap();
+        let script = root.join(PODMAN_COMPOSE);
+        std::fs::write(
+            &script,
+            format!(
+                "#!/bin/sh\ncase \"$*\" in\n  config) cat <<'EOF'\n{}EOF\n  ;;\n  *) echo \"podman-compose: error: unrecognized arguments: $*\" >&2; exit 2 ;;\nesac\n",
+                PODMAN_COMPOSE_CONFIG
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let compose_cmd = vec![script.to_string_lossy().to_string()];
+
+        let loaded = ComposeProject::load(&compose_cmd, &root).await;
+        let raw = ComposeProject::load_uninterpolated(&compose_cmd, &root).await;
+        let _ = std::fs::remove_dir_all(&root);
+
+        let project = loaded.unwrap();
+        assert!(project.name.starts_with("identity-"));
+        assert_eq!(project.pull_services(), vec!["identity", "postgres"]);
+        assert_eq!(project.build_services(), vec!["theme"]);
+        assert_eq!(
+            project
+                .environment("identity", "KC_HEALTH_ENABLED")
+                .as_deref(),
+            Some("true")
+        );
+        assert_eq!(
+            project
+                .environment("postgres", "POSTGRES_PASSWORD")
+                .as_deref(),
+            Some("s3cr3t")
+        );
+        assert_eq!(project.container_name("identity"), "identity");
+        assert_eq!(
+            project.container_name("postgres"),
+            format!("{}_postgres_1", project.name)
+        );
+        let (key, volume, target) = project.named_mount("postgres").unwrap();
+        assert_eq!(key, "identity_pgdata");
+        assert_eq!(volume, format!("{}_identity_pgdata", project.name));
+        assert_eq!(target, "/var/lib/postgresql/data");
+
+        let raw = raw.unwrap();
+        assert_eq!(raw.name, "identity");
+        assert_eq!(
+            raw.image_variables().get("identity").map(String::as_str),
+            Some("KEYCLOAK_IMAGE")
+        );
+        assert_eq!(raw.container_name("postgres"), "identity-db");
+
+        assert!(reports_podman_compose(
+            ">>>> Executing external compose provider \"/usr/bin/podman-compose\". <<<<\npodman-compose version: 1.0.6\n"
+        ));
+        assert!(!reports_podman_compose("Docker Compose version v2.29.7\n"));
+        assert_eq!(
+            args(
+                &compose_cmd,
+                &[
+                    "up",
+                    "-d",
+                    "--no-deps",
+                    "--pull",
+                    "never",
+                    "--force-recreate",
+                    "identity"
+                ]
+            ),
+            vec!["up", "-d", "--no-deps", "--force-recreate", "identity"]
+        );
+        assert_eq!(
+            args(&compose_cmd, &["down", "--remove-orphans", "--rmi", "all"]),
+            vec!["down", "--remove-orphans"]
+        );
+        assert_eq!(
+            args(
+                &["docker".to_string(), "compose".to_string()],
+                &["ps", "--all"]
+            ),
+            vec!["ps", "--all"]
+        );
+    }
+}
@@ -0,0 +1,159 @@
+use super::form::{Form, FormField};
+use crate::env_file::EnvFile;
+use crate::utils::ADMIN_USER_KEY;
+
+pub const HTTP_PORT_KEY: &str = "KC_HTTP_PORT";
+pub const PG_PORT_KEY: &str = "PG_PORT";
+pub const HOSTNAME_KEY: &str = "KC_HOSTNAME";
+pub const THEME_KEY: &str = "KC_THEME_DEFAULT";
+pub const PROXY_KEY: &str = "KC_PROXY_HEADERS";
+
+pub const DEFAULT_HTTP_PORT: &str = "8080";
+pub const DEFAULT_PG_PORT: &str = "5432";
+const DEFAULT_THEME: &str = "keycloakify-starter";
+const DEFAULT_ADMIN_USER: &str = "admin";
+const PROXY_MODES: &[&str] = &["none", "xforwarded", "forwarded"];
+
+/// Builds the pre-install configuration form, prefilled from an existing `.env`.
+pub fn config_form(env: &EnvFile) -> Form {
+    let value = |key: &str, default: &str| env.get(key).unwrap_or(default).to_string();
+    let proxy = match env.get(PROXY_KEY) {
+        Some(mode) if !mode.is_empty() => mode.to_string(),
+        _ => "none".to_string(),
+    };
+
+    Form::new(
+        vec![
+            FormField::new(HTTP_PORT_KEY, "Keycloak HTTP port")
+                .value(value(HTTP_PORT_KEY, DEFAULT_HTTP_PORT))
+                .validator(validate_port),
+            FormField::new(PG_PORT_KEY, "PostgreSQL port")
+                .value(value(PG_PORT_KEY, DEFAULT_PG_PORT))
+                .validator(validate_port),
+            FormField::new(HOSTNAME_KEY, "Public hostname")
+                .value(value(HOSTNAME_KEY, ""))
+                .placeholder("<none, accept any host>")
+                .validator(validate_hostname),
+            FormField::new(THEME_KEY, "Default theme")
+                .value(value(THEME_KEY, DEFAULT_THEME))
+                .validator(validate_name),
+            FormField::new(PROXY_KEY, "Reverse proxy headers")
+                .value(proxy)
+                .placeholder("none | xforwarded | forwarded")
+                .validator(validate_proxy),
+            FormField::new(ADMIN_USER_KEY, "Admin username")
+                .value(value(ADMIN_USER_KEY, DEFAULT_ADMIN_USER))
+                .validator(validate_name),
+        ],
+        "Save and install",
+    )
+}
+
+/// Copies the form values into `env`; secrets already present are left alone.
+pub fn apply_config(form: &Form, env: &mut EnvFile) {
+    for field in &form.fields {
+        let value = field.value.trim();
+        let value = if field.key == PROXY_KEY && value == "none" {
+            ""
+        } else {
+            value
+        };
+        env.set(field.key, value);
+    }
+}
+
+pub fn console_url(env: &EnvFile) -> String {
+    let host = env
+        .get(HOSTNAME_KEY)
+        .filter(|host| !host.is_empty())
+        .unwrap_or("localhost");
+    let port = env.get(HTTP_PORT_KEY).unwrap_or(DEFAULT_HTTP_PORT);
+    format!("http://{}:{}", host, port)
+}
+
+/// URL of Keycloak as seen from the host running the installer.
+pub fn local_url(env: &EnvFile) -> String {
+    let port = env.get(HTTP_PORT_KEY).unwrap_or(DEFAULT_HTTP_PORT);
+    format!("http://localhost:{}", port)
+}
+
+fn validate_port(value: &str) -> Result<(), String> {
+    match value.parse::<u16>() {
+        Ok(port) if port > 0 => Ok(()),
+        _ => Err("must be a number between 1 and 65535".to_string()),
+    }
+}
+
+fn validate_hostname(value: &str) -> Result<(), String> {
+    let valid = value.split('.').all(|label| {
+        !label.is_empty()
+            && label.len() <= 63
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    });
+
+    if value.is_empty() || (value.len() <= 253 && valid) {
+        Ok(())
+    } else {
+        Err("must be a DNS name such as auth.example.com".to_string())
+    }
+}
+
+fn validate_name(value: &str) -> Result<(), String> {
+    if value.is_empty() {
+        return Err("a value is required".to_string());
+    }
+    if value
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c))
+    {
+        Ok(())
+    } else {
+        Err("only letters, digits, '-', '_' and '.' are allowed".to_string())
+    }
+}
+
+fn validate_proxy(value: &str) -> Result<(), String> {
+    if PROXY_MODES.contains(&value) {
+        Ok(())
+    } else {
+        Err(format!("must be one of {}", PROXY_MODES.join(", ")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_round_trips_through_env() {
+        let mut env = EnvFile::parse("KEYCLOAK_ADMIN_PASSWORD=secret\nKC_HTTP_PORT=8081\n");
+        let mut form = config_form(&env);
+        assert_eq!(form.value(HTTP_PORT_KEY), "8081");
+        assert_eq!(form.value(PROXY_KEY), "none");
+
+        form.set_value(HOSTNAME_KEY, "auth.example.com");
+        assert!(form.validate());
+        apply_config(&form, &mut env);
+
+        assert_eq!(env.get("KEYCLOAK_ADMIN_PASSWORD"), Some("secret"));
+        assert_eq!(env.get(PROXY_KEY), Some(""));
+        assert_eq!(console_url(&env), "http://auth.example.com:8081");
+    }
+
+    #[test]
+    fn test_invalid_fields_are_reported() {
+        let mut form = config_form(&EnvFile::default());
+        form.set_value(PG_PORT_KEY, "70000");
+        form.set_value(PROXY_KEY, "edge");
+
+        assert!(!form.validate());
+        assert_eq!(form.current_field, 1);
+        assert!(form.error_message.starts_with("PostgreSQL port"));
+
+        form.set_value(PG_PORT_KEY, "5433");
+        assert!(!form.validate());
+        assert!(form.error_message.starts_with("Reverse proxy headers"));
+    }
+}
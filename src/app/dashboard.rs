@@ -0,0 +1,560 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use color_eyre::{Result, eyre::eyre};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use serde::Deserialize;
+
+use super::compose::{self, ComposeProject};
+use super::rollout::{RunningImage, running_image};
+use super::surface::Surface;
+use super::{App, AppState};
+use crate::registry::RegistryConfig;
+use crate::utils;
+
+/// How often the dashboard re-reads container state while it is shown.
+pub const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+const NOT_CREATED: &str = "not created";
+
+/// A port a container publishes on the host, as `compose ps` prints it.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Publisher {
+    #[serde(rename = "URL", default)]
+    pub url: String,
+    #[serde(rename = "TargetPort", default)]
+    pub target_port: u16,
+    #[serde(rename = "PublishedPort", default)]
+    pub published_port: u16,
+    #[serde(rename = "Protocol", default)]
+    pub protocol: String,
+}
+
+/// One service of the deployed stack, from `compose ps --all --format json`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ServiceStatus {
+    #[serde(rename = "Name", default)]
+    pub name: String,
+    #[serde(rename = "Service", default)]
+    pub service: String,
+    #[serde(rename = "State", default)]
+    pub state: String,
+    #[serde(rename = "Health", default)]
+    pub health: String,
+    #[serde(rename = "Image", default)]
+    pub image: String,
+    /// Docker's summary, e.g. `Up 2 hours (healthy)` or `Exited (1) 3 minutes ago`.
+    #[serde(rename = "Status", default)]
+    pub status: String,
+    #[serde(rename = "Publishers", default)]
+    pub publishers: Option<Vec<Publisher>>,
+    /// Image the container runs, filled in by `deployment_status`.
+    #[serde(skip)]
+    pub running: Option<RunningImage>,
+}
+
+impl ServiceStatus {
+    fn not_created(service: &str, image: Option<&str>) -> Self {
+        Self {
+            service: service.to_string(),
+            state: NOT_CREATED.to_string(),
+            image: image.unwrap_or_default().to_string(),
+            ..Self::default()
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.state == "running"
+    }
+
+    pub fn is_created(&self) -> bool {
+        self.state != NOT_CREATED
+    }
+
+    /// `running` containers without a healthcheck count as healthy.
+    pub fn is_healthy(&self) -> bool {
+        self.is_running() && (self.health.is_empty() || self.health == "healthy")
+    }
+
+    /// How long the container has been up, from Docker's status summary.
+    pub fn uptime(&self) -> Option<&str> {
+        let up = self.status.strip_prefix("Up ")?;
+        Some(up.split(" (").next().unwrap_or(up))
+    }
+
+    pub fn tag(&self) -> &str {
+        compose::image_tag(&self.image).unwrap_or("latest")
+    }
+
+    /// Published ports as `8080→8080/tcp`, with the host address when the
+    /// port is bound to a single interface. IPv4 and IPv6 bindings of the
+    /// same port are shown once.
+    pub fn ports(&self) -> Vec<String> {
+        let mut ports: Vec<String> = Vec::new();
+        for publisher in self.publishers.iter().flatten() {
+            if publisher.published_port == 0 {
+                continue;
+            }
+            let host = match publisher.url.as_str() {
+                "" | "0.0.0.0" | "::" => String::new(),
+                url => format!("{}:", url),
+            };
+            let port = format!(
+                "{}{}→{}/{}",
+                host, publisher.published_port, publisher.target_port, publisher.protocol
+            );
+            if !ports.contains(&port) {
+                ports.push(port);
+            }
+        }
+        ports
+    }
+}
+
+/// What can be done to a single service from the dashboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServiceAction {
+    Start,
+    Stop,
+    Restart,
+    Reinstall,
+}
+
+impl ServiceAction {
+    pub fn verb(self) -> &'static str {
+        match self {
+            ServiceAction::Start => "Starting",
+            ServiceAction::Stop => "Stopping",
+            ServiceAction::Restart => "Restarting",
+            ServiceAction::Reinstall => "Reinstalling",
+        }
+    }
+
+    /// Stopping takes the service offline and reinstalling recreates its
+    /// container, so both ask first.
+    fn needs_confirmation(self) -> bool {
+        matches!(self, ServiceAction::Stop | ServiceAction::Reinstall)
+    }
+}
+
+pub(super) enum DashboardAction {
+    Service(ServiceAction),
+    Logs(String),
+    Refresh,
+    Menu,
+}
+
+/// `compose ps --format json` prints a JSON array on older Compose v2 releases
+/// and one object per line on newer ones.
+pub fn parse_compose_ps(stdout: &str) -> Result<Vec<ServiceStatus>> {
+    let trimmed = stdout.trim();
+
+    if trimmed.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    if trimmed.starts_with('[') {
+        return Ok(serde_json::from_str(trimmed)?);
+    }
+
+    trimmed
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).map_err(Into::into))
+        .collect()
+}
+
+/// A container as `podman ps --format json` prints it, which is what
+/// podman-compose's `ps` passes through.
+#[derive(Debug, Deserialize)]
+struct PodmanContainer {
+    #[serde(rename = "Names", default)]
+    names: Vec<String>,
+    #[serde(rename = "State", default)]
+    state: String,
+    #[serde(rename = "Image", default)]
+    image: String,
+    #[serde(rename = "Status", default)]
+    status: String,
+    #[serde(rename = "Labels", default)]
+    labels: Option<BTreeMap<String, String>>,
+    #[serde(rename = "Ports", default)]
+    ports: Option<Vec<PodmanPort>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PodmanPort {
+    #[serde(default)]
+    host_ip: String,
+    #[serde(default)]
+    container_port: u16,
+    #[serde(default)]
+    host_port: u16,
+    #[serde(default)]
+    protocol: String,
+}
+
+/// Reads podman-compose `ps` output into the rows `compose ps` gives. The
+/// health is taken from the status summary, e.g. `Up 2 minutes (healthy)`.
+fn parse_podman_ps(stdout: &str) -> Result<Vec<ServiceStatus>> {
+    let trimmed = stdout.trim();
+    if trimmed.is_empty() {
+        return Ok(Vec::new());
+    }
+    let containers: Vec<PodmanContainer> = serde_json::from_str(trimmed)?;
+    Ok(containers
+        .into_iter()
+        .map(|container| {
+            let health = ["healthy", "unhealthy", "starting"]
+                .into_iter()
+                .find(|health| container.status.contains(&format!("({})", health)))
+                .unwrap_or_default();
+            ServiceStatus {
+                name: container.names.first().cloned().unwrap_or_default(),
+                service: container
+                    .labels
+                    .as_ref()
+                    .and_then(|labels| labels.get("com.docker.compose.service"))
+                    .cloned()
+                    .unwrap_or_default(),
+                state: container.state,
+                health: health.to_string(),
+                image: container.image,
+                status: container.status,
+                publishers: container.ports.map(|ports| {
+                    ports
+                        .into_iter()
+                        .map(|port| Publisher {
+                            url: port.host_ip,
+                            target_port: port.container_port,
+                            published_port: port.host_port,
+                            protocol: port.protocol,
+                        })
+                        .collect()
+                }),
+                running: None,
+            }
+        })
+        .collect())
+}
+
+/// Containers of the compose project in `root`, whatever their state.
+pub async fn compose_ps(compose_cmd: &[String], root: &Path) -> Result<Vec<ServiceStatus>> {
+    let output = compose::command(compose_cmd, root)
+        .args(compose::args(
+            compose_cmd,
+            &["ps", "--all", "--format", "json"],
+        ))
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        return Err(eyre!(
+            "compose ps failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if compose::is_podman_compose(compose_cmd) {
+        parse_podman_ps(&stdout)
+    } else {
+        parse_compose_ps(&stdout)
+    }
+}
+
+/// One row per service of `project`, in service order, with services that
+/// have no container yet marked as not created.
+fn dashboard_rows(project: &ComposeProject, containers: Vec<ServiceStatus>) -> Vec<ServiceStatus> {
+    let mut rows: Vec<ServiceStatus> = project
+        .service_names()
+        .iter()
+        .map(|service| {
+            containers
+                .iter()
+                .find(|container| &container.service == service)
+                .cloned()
+                .unwrap_or_else(|| ServiceStatus::not_created(service, project.image(service)))
+        })
+        .collect();
+    rows.extend(
+        containers
+            .into_iter()
+            .filter(|container| !project.services.contains_key(&container.service)),
+    );
+    rows
+}
+
+impl App {
+    /// Whether the identity compose project already has containers. Used at
+    /// startup to open the dashboard instead of the install menu.
+    pub(super) async fn detect_deployment(&mut self) -> bool {
+        let Ok(compose_cmd) = self.detect_compose_command().await else {
+            return false;
+        };
+        self.deployed = compose_ps(&compose_cmd, &utils::project_root())
+            .await
+            .is_ok_and(|containers| !containers.is_empty());
+        self.deployed
+    }
+
+    /// Re-reads the state and image of every service.
+    pub(super) async fn refresh_dashboard(&mut self) {
+        self.dashboard_refreshed = Some(Instant::now());
+        match self.deployment_status().await {
+            Ok(services) => {
+                self.deployed = services.iter().any(ServiceStatus::is_created);
+                self.services = services;
+            }
+            Err(e) => {
+                self.services.clear();
+                self.dashboard_message = Some(format!("Failed to read service state: {}", e));
+            }
+        }
+        self.dashboard_selection = self
+            .dashboard_selection
+            .min(self.services.len().saturating_sub(1));
+    }
+
+    pub(super) fn dashboard_refresh_due(&self) -> bool {
+        self.dashboard_refreshed
+            .is_none_or(|refreshed| refreshed.elapsed() >= REFRESH_INTERVAL)
+    }
+
+    async fn deployment_status(&mut self) -> Result<Vec<ServiceStatus>> {
+        let compose_cmd = self.load_project().await?;
+        let containers = compose_ps(&compose_cmd, &utils::project_root()).await?;
+        let mut rows = dashboard_rows(&self.project, containers);
+        for row in rows.iter_mut().filter(|row| row.is_created()) {
+            row.running = running_image(&row.name).await.ok().flatten();
+        }
+        Ok(rows)
+    }
+
+    pub(super) fn handle_dashboard_events(&mut self) -> Result<Option<DashboardAction>> {
+        if event::poll(std::time::Duration::from_millis(100))?
+            && let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            if let Some(action) = self.dashboard_pending.take() {
+                return Ok(match key.code {
+                    KeyCode::Char('y') | KeyCode::Char('Y') => {
+                        Some(DashboardAction::Service(action))
+                    }
+                    _ => {
+                        self.dashboard_message = Some(format!("{} cancelled", action.verb()));
+                        None
+                    }
+                });
+            }
+
+            let action = match key.code {
+                KeyCode::Up => {
+                    self.dashboard_selection = self.dashboard_selection.saturating_sub(1);
+                    None
+                }
+                KeyCode::Down if self.dashboard_selection + 1 < self.services.len() => {
+                    self.dashboard_selection += 1;
+                    None
+                }
+                KeyCode::Char('s') | KeyCode::Char('S') => Some(ServiceAction::Start),
+                KeyCode::Char('x') | KeyCode::Char('X') => Some(ServiceAction::Stop),
+                KeyCode::Char('r') | KeyCode::Char('R') => Some(ServiceAction::Restart),
+                KeyCode::Char('i') | KeyCode::Char('I') => Some(ServiceAction::Reinstall),
+                KeyCode::Char('l') | KeyCode::Char('L') | KeyCode::Enter => {
+                    return Ok(self
+                        .services
+                        .get(self.dashboard_selection)
+                        .map(|service| DashboardAction::Logs(service.service.clone())));
+                }
+                KeyCode::Char('f') | KeyCode::Char('F') => {
+                    return Ok(Some(DashboardAction::Refresh));
+                }
+                KeyCode::Esc | KeyCode::Char('m') | KeyCode::Char('M') => {
+                    return Ok(Some(DashboardAction::Menu));
+                }
+                KeyCode::Char('q') => {
+                    self.running = false;
+                    None
+                }
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    self.running = false;
+                    None
+                }
+                _ => None,
+            };
+
+            let Some(action) = action else {
+                return Ok(None);
+            };
+            let Some(service) = self.services.get(self.dashboard_selection) else {
+                return Ok(None);
+            };
+            if !service.is_created() && action != ServiceAction::Reinstall {
+                self.dashboard_message = Some(format!(
+                    "{} has no container yet; press I to install it",
+                    service.service
+                ));
+                return Ok(None);
+            }
+            if action.needs_confirmation() {
+                self.dashboard_message = Some(format!(
+                    "{} {}? Press Y to confirm.",
+                    action.verb(),
+                    service.service
+                ));
+                self.dashboard_pending = Some(action);
+                return Ok(None);
+            }
+            return Ok(Some(DashboardAction::Service(action)));
+        }
+
+        Ok(None)
+    }
+
+    /// Runs `action` on the selected service, then returns to the dashboard
+    /// with the outcome.
+    pub(super) async fn run_service_action(
+        &mut self,
+        terminal: &mut impl Surface,
+        action: ServiceAction,
+    ) {
+        let Some(service) = self
+            .services
+            .get(self.dashboard_selection)
+            .map(|row| row.service.clone())
+        else {
+            return;
+        };
+
+        self.logs.clear();
+        self.progress = 0.0;
+        self.service_health.clear();
+        self.service_action = Some(action);
+        self.current_service = service.clone();
+        self.state = AppState::ManagingService;
+
+        let result = self.apply_service_action(terminal, action, &service).await;
+        self.dashboard_message = Some(match result {
+            Ok(()) => format!("✅ {} {}: done", action.verb(), service),
+            Err(e) => format!("❌ {} {} failed: {}", action.verb(), service, e),
+        });
+        self.service_action = None;
+        self.state = AppState::Dashboard;
+        self.refresh_dashboard().await;
+    }
+
+    async fn apply_service_action(
+        &mut self,
+        terminal: &mut impl Surface,
+        action: ServiceAction,
+        service: &str,
+    ) -> Result<()> {
+        let mut compose_cmd = self.load_project().await?;
+        self.add_log_and_redraw(terminal, &format!("⚙️  {} {}...", action.verb(), service));
+
+        match action {
+            ServiceAction::Start | ServiceAction::Stop | ServiceAction::Restart => {
+                let command = match action {
+                    ServiceAction::Start => "start",
+                    ServiceAction::Stop => "stop",
+                    _ => "restart",
+                };
+                if !self
+                    .run_compose_step(terminal, &compose_cmd, &[command, service], &[])
+                    .await?
+                {
+                    return Err(eyre!("compose {} {} failed", command, service));
+                }
+            }
+            ServiceAction::Reinstall => {
+                if !self.offline {
+                    self.apply_image_rewrites(&RegistryConfig::configured()?, &compose_cmd)
+                        .await?;
+                    compose_cmd = self.load_project().await?;
+                }
+                if !self.offline && self.project.pull_services().iter().any(|s| s == service) {
+                    self.progress = 20.0;
+                    self.pull_service(terminal, service).await?;
+                }
+                self.progress = 50.0;
+                self.recreate_service(terminal, &compose_cmd, service)
+                    .await?;
+            }
+        }
+
+        if action != ServiceAction::Stop {
+            self.progress = 70.0;
+            self.init_service_health();
+            self.service_health.retain(|entry| entry.service == service);
+            self.wait_for_health(terminal).await?;
+        }
+        self.progress = 100.0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_compose_ps_lines() {
+        let stdout = concat!(
+            r#"{"Name":"identity","Service":"identity","State":"running","Health":"starting"}"#,
+            "\n",
+            r#"{"Name":"identity-db","Service":"postgres","State":"running","Health":"healthy"}"#,
+            "\n"
+        );
+
+        let containers = parse_compose_ps(stdout).unwrap();
+        assert_eq!(containers.len(), 2);
+        assert_eq!(containers[0].health, "starting");
+        assert_eq!(containers[1].service, "postgres");
+    }
+
+    #[test]
+    fn test_parse_compose_ps_array() {
+        let stdout = r#"[{"Name":"identity-db","Service":"postgres","State":"exited"}]"#;
+
+        let containers = parse_compose_ps(stdout).unwrap();
+        assert_eq!(containers.len(), 1);
+        assert_eq!(containers[0].state, "exited");
+        assert!(containers[0].health.is_empty());
+
+        let stdout = r#"[{"Names":["identity"],"State":"running","Image":"ghcr.io/nexusquantum/nqrust-identity:1.4.2","Status":"Up 2 minutes (healthy)","Labels":{"com.docker.compose.service":"identity","io.podman.compose.project":"identity"},"Ports":[{"host_ip":"","container_port":8080,"host_port":8080,"range":1,"protocol":"tcp"}]}]"#;
+        let containers = parse_podman_ps(stdout).unwrap();
+        assert_eq!(containers[0].service, "identity");
+        assert!(containers[0].is_healthy());
+        assert_eq!(containers[0].ports(), vec!["8080→8080/tcp"]);
+    }
+
+    #[test]
+    fn test_dashboard_rows() {
+        let stdout = r#"{"Name":"identity","Service":"identity","State":"running","Health":"healthy","Image":"ghcr.io/nexusquantum/nqrust-identity:1.4.2","Status":"Up 2 hours (healthy)","Publishers":[{"URL":"0.0.0.0","TargetPort":8080,"PublishedPort":8080,"Protocol":"tcp"},{"URL":"::","TargetPort":8080,"PublishedPort":8080,"Protocol":"tcp"},{"URL":"","TargetPort":9000,"PublishedPort":0,"Protocol":"tcp"}]}"#;
+        let project = ComposeProject::parse(
+            r#"{
+                "name": "identity",
+                "services": {
+                    "identity": { "container_name": "identity", "image": "ghcr.io/nexusquantum/nqrust-identity:1.4.2" },
+                    "postgres": { "container_name": "identity-db", "image": "postgres:16-alpine" }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let rows = dashboard_rows(&project, parse_compose_ps(stdout).unwrap());
+        assert_eq!(rows.len(), 2);
+
+        let identity = &rows[0];
+        assert!(identity.is_healthy());
+        assert_eq!(identity.uptime(), Some("2 hours"));
+        assert_eq!(identity.tag(), "1.4.2");
+        assert_eq!(identity.ports(), vec!["8080→8080/tcp"]);
+
+        let postgres = &rows[1];
+        assert!(!postgres.is_created());
+        assert_eq!(postgres.tag(), "16-alpine");
+        assert_eq!(postgres.uptime(), None);
+    }
+}
@@ -0,0 +1,184 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Checks a trimmed field value, returning a short reason when it is invalid.
+pub type Validator = fn(&str) -> Result<(), String>;
+
+#[derive(Debug, Clone)]
+pub struct FormField {
+    pub key: &'static str,
+    pub label: &'static str,
+    pub value: String,
+    pub placeholder: &'static str,
+    pub masked: bool,
+    pub validator: Option<Validator>,
+}
+
+impl FormField {
+    pub fn new(key: &'static str, label: &'static str) -> Self {
+        Self {
+            key,
+            label,
+            value: String::new(),
+            placeholder: "",
+            masked: false,
+            validator: None,
+        }
+    }
+
+    pub fn value(mut self, value: impl Into<String>) -> Self {
+        self.value = value.into();
+        self
+    }
+
+    pub fn placeholder(mut self, placeholder: &'static str) -> Self {
+        self.placeholder = placeholder;
+        self
+    }
+
+    pub fn masked(mut self) -> Self {
+        self.masked = true;
+        self
+    }
+
+    pub fn validator(mut self, validator: Validator) -> Self {
+        self.validator = Some(validator);
+        self
+    }
+}
+
+pub enum FormAction {
+    Submit,
+    Cancel,
+}
+
+/// A vertical list of text inputs followed by a submit button.
+#[derive(Debug, Clone)]
+pub struct Form {
+    pub fields: Vec<FormField>,
+    pub submit_label: &'static str,
+    pub current_field: usize,
+    pub editing: bool,
+    pub error_message: String,
+}
+
+impl Form {
+    pub fn new(fields: Vec<FormField>, submit_label: &'static str) -> Self {
+        Self {
+            fields,
+            submit_label,
+            current_field: 0,
+            editing: false,
+            error_message: String::new(),
+        }
+    }
+
+    pub fn total_items(&self) -> usize {
+        self.fields.len() + 1
+    }
+
+    pub fn is_input_field(&self, index: usize) -> bool {
+        index < self.fields.len()
+    }
+
+    pub fn get_current_value_mut(&mut self) -> Option<&mut String> {
+        self.fields
+            .get_mut(self.current_field)
+            .map(|field| &mut field.value)
+    }
+
+    pub fn value(&self, key: &str) -> &str {
+        self.fields
+            .iter()
+            .find(|field| field.key == key)
+            .map(|field| field.value.as_str())
+            .unwrap_or_default()
+    }
+
+    pub fn set_value(&mut self, key: &str, value: impl Into<String>) {
+        if let Some(field) = self.fields.iter_mut().find(|field| field.key == key) {
+            field.value = value.into();
+        }
+    }
+
+    pub fn reset_cursor(&mut self) {
+        self.current_field = 0;
+        self.editing = false;
+        self.error_message.clear();
+    }
+
+    /// Runs every validator and moves the cursor to the first invalid field.
+    pub fn validate(&mut self) -> bool {
+        for (index, field) in self.fields.iter().enumerate() {
+            if let Some(validator) = field.validator
+                && let Err(reason) = validator(field.value.trim())
+            {
+                self.error_message = format!("{}: {}", field.label, reason);
+                self.current_field = index;
+                self.editing = false;
+                return false;
+            }
+        }
+
+        self.error_message.clear();
+        true
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<FormAction> {
+        if self.editing && self.is_input_field(self.current_field) {
+            match key.code {
+                KeyCode::Enter | KeyCode::Esc => {
+                    self.editing = false;
+                }
+                KeyCode::Backspace => {
+                    if let Some(value) = self.get_current_value_mut() {
+                        value.pop();
+                    }
+                }
+                KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    if let Some(value) = self.get_current_value_mut() {
+                        value.push(c);
+                    }
+                }
+                _ => {}
+            }
+            return None;
+        }
+
+        match key.code {
+            KeyCode::Up => {
+                if self.current_field == 0 {
+                    self.current_field = self.total_items() - 1;
+                } else {
+                    self.current_field -= 1;
+                }
+            }
+            KeyCode::Down | KeyCode::Tab => {
+                self.current_field = (self.current_field + 1) % self.total_items();
+            }
+            KeyCode::Enter => {
+                if self.is_input_field(self.current_field) {
+                    self.editing = true;
+                } else {
+                    return Some(FormAction::Submit);
+                }
+            }
+            KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return Some(FormAction::Submit);
+            }
+            KeyCode::Esc | KeyCode::Char('q') => {
+                return Some(FormAction::Cancel);
+            }
+            _ => {}
+        }
+
+        None
+    }
+}
+
+pub fn required(value: &str) -> Result<(), String> {
+    if value.is_empty() {
+        Err("a value is required".to_string())
+    } else {
+        Ok(())
+    }
+}
@@ -0,0 +1,298 @@
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use color_eyre::{Result, eyre::eyre};
+
+use super::App;
+use super::dashboard;
+use super::preflight::CheckStatus;
+use super::surface::Headless;
+use crate::cli::{BundleCommand, Command as CliCommand, OutputFormat};
+use crate::utils;
+
+/// Exit code for commands that succeeded but found something needing attention:
+/// updates are available (`update --check`) or services are not running (`status`).
+const EXIT_ATTENTION: u8 = 3;
+
+impl App {
+    fn headless(format: OutputFormat) -> Self {
+        let mut app = Self::new();
+        app.echo = Some(format);
+        app
+    }
+}
+
+pub async fn run(command: CliCommand) -> Result<ExitCode> {
+    let (name, format, result) = match command {
+        CliCommand::Install {
+            yes,
+            from_bundle,
+            output,
+        } => (
+            "install",
+            output.output,
+            install(yes, from_bundle, output.output).await,
+        ),
+        CliCommand::Update { check, output } => {
+            ("update", output.output, update(check, output.output).await)
+        }
+        CliCommand::Status { output } => ("status", output.output, status(output.output).await),
+        CliCommand::Bundle {
+            command: BundleCommand::Create { file, output },
+        } => (
+            "bundle create",
+            output.output,
+            bundle_create(&file, output.output).await,
+        ),
+    };
+
+    match result {
+        Ok(code) => Ok(code),
+        Err(e) => {
+            emit_result(format, name, false, &e.to_string());
+            Ok(ExitCode::FAILURE)
+        }
+    }
+}
+
+async fn install(
+    yes: bool,
+    from_bundle: Option<PathBuf>,
+    format: OutputFormat,
+) -> Result<ExitCode> {
+    let project_root = utils::project_root();
+
+    if !yes
+        && !confirm(&format!(
+            "Install the NQRust Identity stack in {}?",
+            project_root.display()
+        ))?
+    {
+        return Err(eyre!("Installation aborted"));
+    }
+
+    let mut app = App::headless(format);
+
+    let report = app.run_preflight_checks().await;
+    for check in &report.checks {
+        let icon = match check.status {
+            CheckStatus::Pass => continue,
+            CheckStatus::Warn => "⚠️ ",
+            CheckStatus::Fail => "❌",
+        };
+        app.add_log(&format!(
+            "{} {}: {} ({})",
+            icon,
+            check.name,
+            check.detail,
+            check.hint.as_deref().unwrap_or_default()
+        ));
+    }
+    if !report.passed() {
+        let failures: Vec<String> = report
+            .failures()
+            .map(|check| format!("{}: {}", check.name, check.detail))
+            .collect();
+        return Err(eyre!("Preflight checks failed: {}", failures.join("; ")));
+    }
+
+    if let Some(bundle) = &from_bundle {
+        let manifest = app.load_bundle(&mut Headless, bundle).await?;
+        app.add_log(&format!(
+            "✅ Loaded {} image(s) from a bundle created {}",
+            manifest.images.len(),
+            manifest.created.format("%Y-%m-%d %H:%M UTC")
+        ));
+        app.offline = true;
+    } else if app.ghcr_token.is_some() {
+        let logged_in = app.try_registry_login().await?;
+        let status = app.registry_status.clone().unwrap_or_default();
+        if !logged_in {
+            return Err(eyre!(status));
+        }
+        app.add_log(&format!("✅ {}", status));
+        if let Some(notice) = app.token_notice.clone() {
+            app.add_log(&notice);
+        }
+    } else {
+        app.add_log(
+            "⚠️  No GHCR token found in GHCR_TOKEN, GITHUB_TOKEN, GH_TOKEN or the secret store; relying on existing docker credentials",
+        );
+    }
+
+    app.add_log("🚀 Starting Identity installation...");
+    app.run_docker_compose(&mut Headless)
+        .await
+        .map_err(|e| eyre!("Installation failed: {}", e))?;
+
+    emit_result(format, "install", true, "Identity stack installed");
+    Ok(ExitCode::SUCCESS)
+}
+
+async fn update(check: bool, format: OutputFormat) -> Result<ExitCode> {
+    let mut app = App::headless(format);
+    app.load_updates().await?;
+
+    for info in &app.update_infos {
+        let state = if let Some(note) = &info.status_note {
+            note.clone()
+        } else if info.has_update {
+            "Update available".to_string()
+        } else {
+            "Up to date".to_string()
+        };
+
+        match format {
+            OutputFormat::Plain => println!(
+                "{:<28} {:<12} {}",
+                info.display_name, info.current_tag, state
+            ),
+            OutputFormat::Json => println!(
+                "{}",
+                serde_json::json!({
+                    "event": "service",
+                    "name": info.display_name,
+                    "image": info.image,
+                    "tag": info.current_tag,
+                    "latest_release": info.latest_release_tag,
+                    "update_available": info.has_update,
+                    "remote_digest": info.remote_digest,
+                    "note": info.status_note,
+                })
+            ),
+        }
+    }
+
+    // The installer itself is only replaced from the TUI, which can prompt for sudo.
+    let pending: Vec<usize> = app
+        .update_infos
+        .iter()
+        .enumerate()
+        .filter(|(_, info)| info.has_update && !info.is_self)
+        .map(|(index, _)| index)
+        .collect();
+
+    if check {
+        let message = format!("{} service update(s) available", pending.len());
+        emit_result(format, "update", true, &message);
+        return Ok(if pending.is_empty() {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::from(EXIT_ATTENTION)
+        });
+    }
+
+    let mut reports = Vec::new();
+    for index in &pending {
+        app.update_selection_index = *index;
+        app.apply_selected_update(&mut Headless).await?;
+        reports.extend(app.update_message.take());
+    }
+
+    let message = if pending.is_empty() {
+        "All services are up to date".to_string()
+    } else {
+        reports.join("; ")
+    };
+    emit_result(format, "update", true, &message);
+    Ok(ExitCode::SUCCESS)
+}
+
+async fn bundle_create(file: &Path, format: OutputFormat) -> Result<ExitCode> {
+    let mut app = App::headless(format);
+    app.add_log("📦 Creating an offline bundle...");
+    let manifest = app.create_bundle(&mut Headless, file).await?;
+
+    let message = format!(
+        "Wrote {} with {} image(s)",
+        file.display(),
+        manifest.images.len()
+    );
+    emit_result(format, "bundle create", true, &message);
+    Ok(ExitCode::SUCCESS)
+}
+
+async fn status(format: OutputFormat) -> Result<ExitCode> {
+    let app = App::headless(format);
+    let compose_cmd = app.detect_compose_command().await?;
+    let project_root = utils::project_root();
+
+    let containers = dashboard::compose_ps(&compose_cmd, &project_root).await?;
+
+    for container in &containers {
+        match format {
+            OutputFormat::Plain => println!(
+                "{:<12} {:<16} {:<10} {}",
+                container.service,
+                container.name,
+                container.state,
+                if container.health.is_empty() {
+                    "-"
+                } else {
+                    &container.health
+                }
+            ),
+            OutputFormat::Json => println!(
+                "{}",
+                serde_json::json!({
+                    "event": "container",
+                    "service": container.service,
+                    "name": container.name,
+                    "state": container.state,
+                    "health": container.health,
+                })
+            ),
+        }
+    }
+
+    if containers.is_empty() {
+        let message = format!(
+            "Identity stack is not deployed in {}",
+            project_root.display()
+        );
+        emit_result(format, "status", false, &message);
+        return Ok(ExitCode::from(EXIT_ATTENTION));
+    }
+
+    let healthy = containers.iter().all(dashboard::ServiceStatus::is_healthy);
+
+    if healthy {
+        emit_result(format, "status", true, "All services are running");
+        Ok(ExitCode::SUCCESS)
+    } else {
+        emit_result(format, "status", false, "Some services are not running");
+        Ok(ExitCode::from(EXIT_ATTENTION))
+    }
+}
+
+fn confirm(prompt: &str) -> Result<bool> {
+    if !io::stdin().is_terminal() {
+        return Err(eyre!(
+            "stdin is not a terminal; pass --yes to run non-interactively"
+        ));
+    }
+
+    eprint!("{} [y/N] ", prompt);
+    io::stderr().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes" | "YES"))
+}
+
+fn emit_result(format: OutputFormat, command: &str, ok: bool, message: &str) {
+    match format {
+        OutputFormat::Plain if ok => println!("{}", message),
+        OutputFormat::Plain => eprintln!("{}", message),
+        OutputFormat::Json => println!(
+            "{}",
+            serde_json::json!({
+                "event": "result",
+                "command": command,
+                "ok": ok,
+                "message": message,
+            })
+        ),
+    }
+}
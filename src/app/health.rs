@@ -0,0 +1,216 @@
+use color_eyre::{Result, eyre::eyre};
+use reqwest::Client;
+use serde::Deserialize;
+
+use crate::docker::DockerClient;
+use crate::runtime::Runtime;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthState {
+    Waiting,
+    Starting,
+    Healthy,
+    Unhealthy,
+    Exited,
+}
+
+impl HealthState {
+    pub fn is_failed(self) -> bool {
+        matches!(self, HealthState::Unhealthy | HealthState::Exited)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            HealthState::Waiting => "waiting",
+            HealthState::Starting => "starting",
+            HealthState::Healthy => "healthy",
+            HealthState::Unhealthy => "unhealthy",
+            HealthState::Exited => "exited",
+        }
+    }
+}
+
+/// Readiness of one compose service while the installer waits for it.
+#[derive(Debug, Clone)]
+pub struct ServiceHealth {
+    pub service: String,
+    pub container: String,
+    /// HTTP endpoint that must answer 2xx before the service counts as ready.
+    pub ready_url: Option<String>,
+    pub state: HealthState,
+    pub detail: String,
+}
+
+impl ServiceHealth {
+    pub fn new(service: &str, container: &str, ready_url: Option<String>) -> Self {
+        Self {
+            service: service.to_string(),
+            container: container.to_string(),
+            ready_url,
+            state: HealthState::Waiting,
+            detail: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ContainerState {
+    #[serde(rename = "Status", default)]
+    status: String,
+    #[serde(rename = "ExitCode", default)]
+    exit_code: i64,
+    /// Older Podman releases name it `Healthcheck`.
+    #[serde(rename = "Health", alias = "Healthcheck", default)]
+    health: Option<ContainerHealthStatus>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ContainerHealthStatus {
+    #[serde(rename = "Status", default)]
+    status: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ContainerDetails {
+    #[serde(rename = "State")]
+    state: ContainerState,
+    /// Restarts done by the restart policy since the container was started.
+    #[serde(rename = "RestartCount", default)]
+    restart_count: u32,
+}
+
+/// Restarts after which a container that is not up yet counts as crash
+/// looping instead of starting.
+const CRASH_LOOP_RESTARTS: u32 = 3;
+
+/// Reads the container state from the Docker Engine API. Containers without
+/// a healthcheck count as healthy as soon as they are running.
+pub async fn inspect_container_health(container: &str) -> Result<(HealthState, String)> {
+    let details: ContainerDetails = DockerClient::from_env()?
+        .inspect_container(container)
+        .await?
+        .ok_or_else(|| eyre!("No such container: {}", container))?;
+    Ok(classify_state(&details.state, details.restart_count))
+}
+
+fn classify_state(state: &ContainerState, restart_count: u32) -> (HealthState, String) {
+    let crash_loop = || {
+        (
+            HealthState::Exited,
+            format!(
+                "container keeps restarting (exit code {}, {} restarts)",
+                state.exit_code, restart_count
+            ),
+        )
+    };
+    match state.status.as_str() {
+        "exited" | "dead" => (
+            HealthState::Exited,
+            format!("container exited with code {}", state.exit_code),
+        ),
+        "restarting" if state.exit_code != 0 || restart_count >= CRASH_LOOP_RESTARTS => {
+            crash_loop()
+        }
+        "restarting" => (HealthState::Starting, "container is restarting".to_string()),
+        "running" => match state.health.as_ref().map(|h| h.status.as_str()) {
+            None | Some("healthy") => (HealthState::Healthy, String::new()),
+            Some("unhealthy") => (
+                HealthState::Unhealthy,
+                "healthcheck reports unhealthy".to_string(),
+            ),
+            Some(_) if restart_count >= CRASH_LOOP_RESTARTS => crash_loop(),
+            Some(other) => (HealthState::Starting, format!("healthcheck {}", other)),
+        },
+        other => (HealthState::Waiting, format!("container {}", other)),
+    }
+}
+
+/// Runs the container's healthcheck once. Podman schedules healthchecks with
+/// systemd timers, which rootless hosts without a user session do not have,
+/// so the status would stay `starting`; Docker runs them itself.
+pub async fn run_healthcheck(container: &str) {
+    let runtime = Runtime::current();
+    if runtime == Runtime::Podman {
+        let _ = runtime
+            .command()
+            .args(["healthcheck", "run", container])
+            .output()
+            .await;
+    }
+}
+
+pub async fn http_ready(client: &Client, url: &str) -> bool {
+    match client.get(url).send().await {
+        Ok(response) => response.status().is_success(),
+        Err(_) => false,
+    }
+}
+
+/// Returns the last `tail` lines the container wrote to stdout and stderr.
+pub async fn container_logs(container: &str, tail: usize) -> Result<Vec<String>> {
+    let mut lines = Vec::new();
+    DockerClient::from_env()?
+        .logs(container, tail, false, |_, line| {
+            lines.push(line);
+            true
+        })
+        .await?;
+    Ok(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(json: &str) -> (HealthState, String) {
+        classify_state(&serde_json::from_str(json).unwrap(), 0)
+    }
+
+    #[test]
+    fn test_classify_state() {
+        assert_eq!(
+            state(r#"{"Status":"running","Health":{"Status":"starting"}}"#).0,
+            HealthState::Starting
+        );
+        assert_eq!(
+            state(r#"{"Status":"running","Health":{"Status":"healthy"}}"#).0,
+            HealthState::Healthy
+        );
+        assert_eq!(state(r#"{"Status":"running"}"#).0, HealthState::Healthy);
+        assert_eq!(
+            state(r#"{"Status":"running","Healthcheck":{"Status":"unhealthy"}}"#).0,
+            HealthState::Unhealthy
+        );
+        assert_eq!(
+            state(r#"{"Status":"running","Health":{"Status":"unhealthy"}}"#).0,
+            HealthState::Unhealthy
+        );
+
+        let (exited, detail) = state(r#"{"Status":"exited","ExitCode":137}"#);
+        assert_eq!(exited, HealthState::Exited);
+        assert_eq!(detail, "container exited with code 137");
+    }
+
+    #[test]
+    fn test_classify_crash_loop() {
+        let details: ContainerDetails = serde_json::from_str(
+            r#"{"State":{"Status":"restarting","ExitCode":1},"RestartCount":1}"#,
+        )
+        .unwrap();
+        let (crashed, detail) = classify_state(&details.state, details.restart_count);
+        assert!(crashed.is_failed());
+        assert_eq!(
+            detail,
+            "container keeps restarting (exit code 1, 1 restarts)"
+        );
+
+        assert_eq!(
+            state(r#"{"Status":"restarting","ExitCode":0}"#).0,
+            HealthState::Starting
+        );
+        let starting: ContainerState =
+            serde_json::from_str(r#"{"Status":"running","Health":{"Status":"starting"}}"#).unwrap();
+        assert!(classify_state(&starting, CRASH_LOOP_RESTARTS).0.is_failed());
+        assert!(!classify_state(&starting, 1).0.is_failed());
+    }
+}
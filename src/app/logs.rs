@@ -0,0 +1,287 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+
+use chrono::Local;
+use color_eyre::Result;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use tokio::sync::mpsc::{self, UnboundedReceiver, error::TryRecvError};
+use tokio::task::JoinHandle;
+
+use super::{App, AppState};
+use crate::docker::DockerClient;
+use crate::utils;
+
+/// Lines kept in the viewer; older ones are dropped as new ones stream in.
+pub const MAX_LINES: usize = 10_000;
+/// History loaded when the viewer opens, before following.
+const TAIL_LINES: usize = 500;
+pub const LOG_DIR: &str = "logs";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogLevel {
+    Error,
+    Warn,
+}
+
+/// Level of a Keycloak (`ERROR [org.keycloak...]`) or PostgreSQL
+/// (`ERROR:  relation ...`) log line. Only the first few words are looked at
+/// so a message that merely mentions an error is not flagged.
+pub fn log_level(line: &str) -> Option<LogLevel> {
+    line.split_whitespace().take(6).find_map(|word| {
+        match word.trim_matches(|c: char| !c.is_ascii_alphabetic()) {
+            "ERROR" | "FATAL" | "PANIC" | "SEVERE" => Some(LogLevel::Error),
+            "WARN" | "WARNING" => Some(LogLevel::Warn),
+            _ => None,
+        }
+    })
+}
+
+/// A followed log stream of one service's container and the viewer's
+/// position in it.
+#[derive(Debug)]
+pub struct LogViewer {
+    pub service: String,
+    pub lines: VecDeque<String>,
+    /// How many lines the view sits above the newest one; 0 means following.
+    pub offset: usize,
+    /// Search being typed after `/`.
+    pub input: Option<String>,
+    pub query: Option<String>,
+    pub message: Option<String>,
+    /// Set once the stream ended, e.g. because the container stopped.
+    pub ended: bool,
+    receiver: UnboundedReceiver<String>,
+    /// Reads the stream from the Docker Engine API; aborted on drop.
+    stream: JoinHandle<()>,
+}
+
+impl Drop for LogViewer {
+    fn drop(&mut self) {
+        self.stream.abort();
+    }
+}
+
+impl LogViewer {
+    pub fn is_following(&self) -> bool {
+        self.offset == 0
+    }
+
+    /// Index one past the newest visible line.
+    pub fn bottom(&self) -> usize {
+        self.lines.len().saturating_sub(self.offset)
+    }
+
+    fn push(&mut self, line: String) {
+        if self.lines.len() == MAX_LINES {
+            self.lines.pop_front();
+        } else if !self.is_following() {
+            // Keep the same lines in view while new ones arrive below.
+            self.offset += 1;
+        }
+        self.lines.push_back(line);
+    }
+
+    /// Moves the view by `delta` lines; positive scrolls back in time.
+    fn scroll(&mut self, delta: isize) {
+        let max = self.lines.len().saturating_sub(1);
+        self.offset = self.offset.saturating_add_signed(delta).min(max);
+    }
+
+    fn follow(&mut self) {
+        self.offset = 0;
+    }
+
+    /// Brings the next line matching the query into view, searching older
+    /// lines when `backwards`. The match becomes the bottom line.
+    fn find(&mut self, backwards: bool) {
+        let Some(query) = self.query.as_deref() else {
+            return;
+        };
+        let query = query.to_lowercase();
+        let current = self.bottom().saturating_sub(1);
+        let matches = |index: &usize| self.lines[*index].to_lowercase().contains(&query);
+        let found = if backwards {
+            (0..current).rev().find(matches)
+        } else {
+            (current + 1..self.lines.len()).find(matches)
+        };
+        match found {
+            Some(index) => {
+                self.offset = self.lines.len() - 1 - index;
+                self.message = None;
+            }
+            None => {
+                self.message = Some(format!(
+                    "No {} match for \"{}\"",
+                    if backwards { "earlier" } else { "later" },
+                    query
+                ));
+            }
+        }
+    }
+
+    /// Writes every buffered line to `logs/<service>-<timestamp>.log`.
+    fn save(&self) -> Result<PathBuf> {
+        let dir = utils::project_root().join(LOG_DIR);
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(format!(
+            "{}-{}.log",
+            self.service,
+            Local::now().format("%Y%m%d-%H%M%S")
+        ));
+        let mut contents = self.lines.iter().cloned().collect::<Vec<_>>().join("\n");
+        contents.push('\n');
+        fs::write(&path, contents)?;
+        Ok(path)
+    }
+}
+
+impl App {
+    /// Starts following the logs of `service` and switches to the viewer.
+    pub(super) async fn open_log_viewer(&mut self, service: &str) -> Result<()> {
+        let docker = DockerClient::from_env()?;
+        let container = self
+            .services
+            .iter()
+            .find(|row| row.service == service && row.is_created())
+            .map(|row| row.name.clone())
+            .unwrap_or_else(|| self.project.container_name(service));
+
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let stream = tokio::spawn(async move {
+            let followed = docker
+                .logs(&container, TAIL_LINES, true, |_, line| {
+                    sender.send(line).is_ok()
+                })
+                .await;
+            if let Err(e) = followed {
+                let _ = sender.send(format!("❌ {}", e));
+            }
+        });
+
+        self.log_viewer = Some(LogViewer {
+            service: service.to_string(),
+            lines: VecDeque::new(),
+            offset: 0,
+            input: None,
+            query: None,
+            message: None,
+            ended: false,
+            receiver,
+            stream,
+        });
+        self.state = AppState::LogViewer;
+        Ok(())
+    }
+
+    /// Moves lines that arrived since the last frame into the viewer.
+    pub(super) fn drain_log_viewer(&mut self) {
+        let Some(viewer) = self.log_viewer.as_mut() else {
+            return;
+        };
+        loop {
+            match viewer.receiver.try_recv() {
+                Ok(line) => viewer.push(line),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    viewer.ended = true;
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Handles keys on the log viewer. Returns `true` when the user leaves it.
+    pub(super) fn handle_log_viewer_events(&mut self) -> Result<bool> {
+        if !event::poll(std::time::Duration::from_millis(100))? {
+            return Ok(false);
+        }
+        let Event::Key(key) = event::read()? else {
+            return Ok(false);
+        };
+        if key.kind != KeyEventKind::Press {
+            return Ok(false);
+        }
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.running = false;
+            return Ok(false);
+        }
+        let Some(viewer) = self.log_viewer.as_mut() else {
+            return Ok(true);
+        };
+
+        if let Some(input) = viewer.input.as_mut() {
+            match key.code {
+                KeyCode::Enter => {
+                    let query = viewer.input.take().unwrap_or_default();
+                    viewer.query = (!query.is_empty()).then_some(query);
+                    viewer.find(true);
+                }
+                KeyCode::Esc => viewer.input = None,
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Char(c) => input.push(c),
+                _ => {}
+            }
+            return Ok(false);
+        }
+
+        let page = crossterm::terminal::size()
+            .map(|(_, rows)| rows.saturating_sub(12).max(1) as isize)
+            .unwrap_or(20);
+        match key.code {
+            KeyCode::Up => viewer.scroll(1),
+            KeyCode::Down => viewer.scroll(-1),
+            KeyCode::PageUp => viewer.scroll(page),
+            KeyCode::PageDown => viewer.scroll(-page),
+            KeyCode::Home => viewer.scroll(isize::MAX),
+            KeyCode::End | KeyCode::Char('f') | KeyCode::Char('F') => viewer.follow(),
+            KeyCode::Char('/') => viewer.input = Some(String::new()),
+            KeyCode::Char('n') => viewer.find(true),
+            KeyCode::Char('N') => viewer.find(false),
+            KeyCode::Char('s') | KeyCode::Char('S') => {
+                viewer.message = Some(match viewer.save() {
+                    Ok(path) => format!("Saved {} lines to {}", viewer.lines.len(), path.display()),
+                    Err(e) => format!("Failed to save logs: {}", e),
+                });
+            }
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.log_viewer = None;
+                return Ok(true);
+            }
+            _ => {}
+        }
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_level() {
+        assert_eq!(
+            log_level(
+                "2024-05-01 10:00:00,123 ERROR [org.keycloak.services] (executor-thread-1) KC-SERVICES0010: Failed"
+            ),
+            Some(LogLevel::Error)
+        );
+        assert_eq!(
+            log_level(
+                "2024-05-01 10:00:00,123 WARN  [io.quarkus.config] Unrecognized configuration key"
+            ),
+            Some(LogLevel::Warn)
+        );
+        assert_eq!(
+            log_level("2024-05-01 10:00:00.123 UTC [42] FATAL:  password authentication failed"),
+            Some(LogLevel::Error)
+        );
+        assert_eq!(
+            log_level("2024-05-01 10:00:00,123 INFO  [org.keycloak] Listening; no ERROR here"),
+            None
+        );
+    }
+}
@@ -2,35 +2,77 @@ use color_eyre::{Result, eyre::eyre};
 use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
 use ratatui::{DefaultTerminal, Frame};
 use reqwest::Client;
-use serde::Deserialize;
-#[cfg(unix)]
-use std::os::unix::fs::PermissionsExt;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use std::process::Stdio;
 use std::{env, fs};
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::Command;
 
+use crate::cli::OutputFormat;
+use crate::docker::{DockerClient, RegistryAuth};
+use crate::env_file::EnvFile;
+use crate::registry::{self, RegistryConfig};
+use crate::runtime::Runtime;
+use crate::secrets;
 use crate::ui::{
-    self, ConfirmationView, ErrorView, InstallingView,
-    RegistrySetupView, SuccessView, UpdateListView,
+    self, BackupsView, ConfigureView, ConfirmationView, DashboardView, ErrorView, InstallingView,
+    LogViewerView, PgUpgradeView, PreflightView, RegistrySetupView, SuccessView, UninstallView,
+    UpdateListView,
 };
-use crate::utils;
-
-pub mod registry_form;
+use crate::utils::{self, GeneratedCredentials};
+
+pub mod backup;
+mod bundle;
+mod compose;
+mod configure;
+pub mod dashboard;
+pub mod form;
+pub mod headless;
+pub mod health;
+pub mod logs;
+pub mod pg_upgrade;
+pub mod preflight;
+mod progress;
+mod rollout;
 pub mod state;
+mod surface;
+mod token_check;
+pub mod uninstall;
 mod updates;
-use registry_form::RegistryForm;
+use backup::{Backup, BackupAction};
+use compose::ComposeProject;
+use dashboard::{DashboardAction, ServiceAction, ServiceStatus};
+use form::{Form, FormAction, FormField};
+use health::{HealthState, ServiceHealth};
+use logs::LogViewer;
+use pg_upgrade::{PgUpgradeAction, PgUpgradePlan, tag_major};
+use preflight::{PreflightAction, PreflightReport};
+use progress::{ContainerStatus, ProgressEvent, PullProgress};
+use rollout::ImagePin;
 pub use state::{AppState, MenuSelection};
-pub use updates::UpdateInfo;
-use updates::{collect_update_infos, get_local_image_created};
+use surface::Surface;
+pub use token_check::{TokenCheck, TokenKind};
+use uninstall::{UNINSTALL_OPTIONS, UninstallOption, UninstallPlan};
+use updates::{Registry, collect_update_infos, inspect_local_image};
+pub use updates::{TagPicker, UpdateInfo};
 
 enum UpdateListAction {
     Pull,
+    Apply,
+    Pin(String),
     Refresh,
     Back,
 }
 
+const TOKEN_FIELD: &str = "token";
+
+/// Compose service running Keycloak; it must also answer `/health/ready`.
+const KEYCLOAK_SERVICE: &str = "identity";
+const HEALTH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(300);
+const HEALTH_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+const FAILURE_LOG_LINES: usize = 40;
+
 enum RegistryAction {
     Submit,
     Skip,
@@ -45,13 +87,51 @@ pub struct App {
     current_service: String,
     total_services: usize,
     completed_services: usize,
+    project: ComposeProject,
+    /// Position of the image being pulled, as (index, count), and its layers.
+    pull_slot: Option<(usize, usize)>,
+    pull_progress: PullProgress,
+    service_health: Vec<ServiceHealth>,
     pub(crate) menu_selection: MenuSelection,
     update_infos: Vec<UpdateInfo>,
     update_selection_index: usize,
     update_message: Option<String>,
-    registry_form: RegistryForm,
+    tag_picker: Option<TagPicker>,
+    registry_form: Form,
+    config_form: Form,
     registry_status: Option<String>,
     ghcr_token: Option<String>,
+    /// What GitHub last reported about `ghcr_token`.
+    token_check: Option<TokenCheck>,
+    /// Expiry or permission warning for the saved token, shown in the menu.
+    token_notice: Option<String>,
+    echo: Option<OutputFormat>,
+    generated_credentials: Option<GeneratedCredentials>,
+    uninstall_plan: UninstallPlan,
+    uninstall_selection: UninstallOption,
+    backups: Vec<Backup>,
+    backup_selection: usize,
+    backup_message: Option<String>,
+    /// Set while the restore confirmation prompt is shown.
+    restore_pending: bool,
+    pg_upgrade: Option<PgUpgradePlan>,
+    pg_upgrade_message: Option<String>,
+    /// Result of the last preflight run; `None` while the checks run.
+    preflight: Option<PreflightReport>,
+    /// Set when installing from an offline bundle: images are already loaded
+    /// and nothing may be pulled.
+    offline: bool,
+    /// Whether the identity compose project has containers on this host.
+    deployed: bool,
+    services: Vec<ServiceStatus>,
+    dashboard_selection: usize,
+    dashboard_message: Option<String>,
+    /// Action waiting for the user to press Y.
+    dashboard_pending: Option<ServiceAction>,
+    dashboard_refreshed: Option<std::time::Instant>,
+    /// Action running while in `ManagingService`.
+    service_action: Option<ServiceAction>,
+    log_viewer: Option<LogViewer>,
 }
 
 impl App {
@@ -60,12 +140,13 @@ impl App {
             .or_else(|_| env::var("GITHUB_TOKEN"))
             .or_else(|_| env::var("GH_TOKEN"))
             .ok();
-        let token_from_disk = App::load_token_from_disk();
+        let token_from_disk = secrets::load_token();
         let initial_token = token_from_env.clone().or(token_from_disk.clone());
 
-        let mut registry_form = RegistryForm::new();
+        let registry_config = RegistryConfig::configured().unwrap_or_default();
+        let mut registry_form = Self::registry_form(&registry_config);
         if let Some(token) = initial_token.clone() {
-            registry_form.token = token;
+            registry_form.set_value(TOKEN_FIELD, token);
         }
 
         let initial_state = if initial_token.is_some() {
@@ -80,15 +161,43 @@ impl App {
             logs: Vec::new(),
             progress: 0.0,
             current_service: String::new(),
-            total_services: 2,  // postgres + identity
+            total_services: 0,
             completed_services: 0,
+            project: ComposeProject::default(),
+            pull_slot: None,
+            pull_progress: PullProgress::default(),
+            service_health: Vec::new(),
             menu_selection: MenuSelection::Proceed,
             update_infos: Vec::new(),
             update_selection_index: 0,
             update_message: None,
+            tag_picker: None,
             registry_form,
+            config_form: configure::config_form(&EnvFile::default()),
             registry_status: None,
             ghcr_token: initial_token,
+            token_check: None,
+            token_notice: None,
+            echo: None,
+            generated_credentials: None,
+            uninstall_plan: UninstallPlan::default(),
+            uninstall_selection: UninstallOption::Back,
+            backups: Vec::new(),
+            backup_selection: 0,
+            backup_message: None,
+            restore_pending: false,
+            pg_upgrade: None,
+            pg_upgrade_message: None,
+            preflight: None,
+            offline: false,
+            deployed: false,
+            services: Vec::new(),
+            dashboard_selection: 0,
+            dashboard_message: None,
+            dashboard_pending: None,
+            dashboard_refreshed: None,
+            service_action: None,
+            log_viewer: None,
         };
 
         app.ensure_menu_selection();
@@ -96,6 +205,14 @@ impl App {
     }
 
     pub async fn run(mut self, mut terminal: DefaultTerminal) -> Result<()> {
+        if self.ghcr_token.is_some() {
+            self.check_saved_token().await;
+        }
+        if self.detect_deployment().await {
+            self.state = AppState::Dashboard;
+            self.refresh_dashboard().await;
+        }
+
         while self.running {
             terminal.draw(|frame| self.render(frame))?;
 
@@ -110,8 +227,11 @@ impl App {
                                 }
                                 Ok(false) => {}
                                 Err(e) => {
-                                    self.registry_status =
-                                        Some(format!("Failed to run docker login: {}", e));
+                                    self.registry_status = Some(format!(
+                                        "Failed to run {} login: {}",
+                                        Runtime::current().binary(),
+                                        e
+                                    ));
                                 }
                             },
                             RegistryAction::Skip => {
@@ -129,25 +249,10 @@ impl App {
                     if let Some(action) = self.handle_confirmation_events()? {
                         match action {
                             MenuSelection::Proceed => {
-                                // Directly start installation
-                                self.state = AppState::Installing;
-                                self.logs
-                                    .push("🚀 Starting Identity installation...".to_string());
-
-                                let result = self.run_docker_compose(&mut terminal).await;
-
-                                match result {
-                                    Ok(_) => {
-                                        self.state = AppState::Success;
-                                        self.progress = 100.0;
-                                    }
-                                    Err(e) => {
-                                        self.state = AppState::Error(format!(
-                                            "Installation failed: {}",
-                                            e
-                                        ));
-                                    }
-                                }
+                                self.preflight = None;
+                                self.state = AppState::Preflight;
+                                terminal.draw(|frame| self.render(frame))?;
+                                self.start_preflight().await;
                             }
                             MenuSelection::CheckUpdates => {
                                 if self.ghcr_token.is_none() {
@@ -155,8 +260,7 @@ impl App {
                                         "Authentication required to check for updates.".to_string(),
                                     );
                                     self.state = AppState::RegistrySetup;
-                                    self.registry_form.current_field = 0;
-                                    self.registry_form.editing = false;
+                                    self.registry_form.reset_cursor();
                                 } else {
                                     match self.load_updates().await {
                                         Ok(_) => {
@@ -176,19 +280,134 @@ impl App {
                                 self.registry_status = Some(
                                     "Update token and submit (Ctrl+S). Esc to cancel.".to_string(),
                                 );
-                                self.registry_form.current_field = 0;
-                                self.registry_form.editing = false;
-                                self.registry_form.error_message.clear();
-                                self.registry_form.token =
-                                    self.ghcr_token.clone().unwrap_or_default();
+                                self.registry_form.reset_cursor();
+                                self.registry_form.set_value(
+                                    TOKEN_FIELD,
+                                    self.ghcr_token.clone().unwrap_or_default(),
+                                );
                                 self.state = AppState::RegistrySetup;
                             }
+                            MenuSelection::Dashboard => {
+                                self.dashboard_message = None;
+                                self.state = AppState::Dashboard;
+                                self.refresh_dashboard().await;
+                            }
+                            MenuSelection::Backups => {
+                                self.backup_message = None;
+                                self.load_backups();
+                                self.state = AppState::Backups;
+                            }
+                            MenuSelection::Uninstall => match self.prepare_uninstall().await {
+                                Ok(()) => self.state = AppState::Uninstall,
+                                Err(e) => {
+                                    self.state = AppState::Error(format!(
+                                        "Failed to prepare uninstall: {}",
+                                        e
+                                    ));
+                                }
+                            },
                             MenuSelection::Cancel => {
                                 self.running = false;
                             }
                         }
                     }
                 }
+                AppState::Dashboard => match self.handle_dashboard_events()? {
+                    Some(DashboardAction::Service(action)) => {
+                        self.run_service_action(&mut terminal, action).await;
+                    }
+                    Some(DashboardAction::Logs(service)) => {
+                        if let Err(e) = self.open_log_viewer(&service).await {
+                            self.dashboard_message =
+                                Some(format!("Cannot follow logs of {}: {}", service, e));
+                        }
+                    }
+                    Some(DashboardAction::Refresh) => self.refresh_dashboard().await,
+                    Some(DashboardAction::Menu) => {
+                        self.state = AppState::Confirmation;
+                        self.ensure_menu_selection();
+                    }
+                    None if self.dashboard_refresh_due() => self.refresh_dashboard().await,
+                    None => {}
+                },
+                AppState::LogViewer => {
+                    self.drain_log_viewer();
+                    if self.handle_log_viewer_events()? {
+                        self.state = AppState::Dashboard;
+                        self.refresh_dashboard().await;
+                    }
+                }
+                AppState::Uninstall => {
+                    if let Some(action) = self.handle_uninstall_events()? {
+                        match action {
+                            UninstallOption::Confirm => self.run_uninstall(&mut terminal).await,
+                            _ => {
+                                self.state = AppState::Confirmation;
+                                self.ensure_menu_selection();
+                            }
+                        }
+                    }
+                }
+                AppState::Backups => {
+                    if let Some(action) = self.handle_backups_events()? {
+                        match action {
+                            BackupAction::Create => {
+                                self.run_backup_task(&mut terminal, false).await
+                            }
+                            BackupAction::Restore => {
+                                self.run_backup_task(&mut terminal, true).await
+                            }
+                            BackupAction::UpgradePostgres => {
+                                match self.prepare_pg_upgrade().await {
+                                    Ok(()) => self.state = AppState::PgUpgrade,
+                                    Err(e) => {
+                                        self.backup_message =
+                                            Some(format!("Cannot prepare the upgrade: {}", e));
+                                    }
+                                }
+                            }
+                            BackupAction::Back => {
+                                self.state = AppState::Confirmation;
+                                self.ensure_menu_selection();
+                            }
+                        }
+                    }
+                }
+                AppState::Uninstalled => {
+                    if event::poll(std::time::Duration::from_millis(100))?
+                        && let Event::Key(key) = event::read()?
+                        && key.kind == KeyEventKind::Press
+                    {
+                        match key.code {
+                            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                                self.running = false;
+                            }
+                            KeyCode::Enter | KeyCode::Esc => {
+                                self.state = AppState::Confirmation;
+                                self.ensure_menu_selection();
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                AppState::Configure => {
+                    if let Some(action) = self.handle_configure_events()? {
+                        match action {
+                            FormAction::Submit => {
+                                if let Err(e) = self.save_config() {
+                                    self.config_form.error_message =
+                                        format!("Failed to save configuration: {}", e);
+                                } else if self.config_form.error_message.is_empty() {
+                                    self.start_installation(&mut terminal).await;
+                                }
+                            }
+                            FormAction::Cancel => {
+                                self.state = AppState::Confirmation;
+                                self.ensure_menu_selection();
+                            }
+                        }
+                    }
+                }
                 AppState::UpdateList => {
                     if let Some(action) = self.handle_update_list_events()? {
                         match action {
@@ -199,10 +418,27 @@ impl App {
                                         AppState::Error(format!("Failed to pull image: {}", e));
                                 } else {
                                     self.state = AppState::UpdateList;
-                                    self.update_message = Some(
-                                        "Image refreshed. Press R to fetch remote metadata again."
-                                            .to_string(),
-                                    );
+                                }
+                            }
+                            UpdateListAction::Apply => {
+                                self.state = AppState::UpdatePulling;
+                                if let Err(e) = self.apply_selected_update(&mut terminal).await {
+                                    self.state =
+                                        AppState::Error(format!("Failed to apply update: {}", e));
+                                } else {
+                                    self.state = AppState::UpdateList;
+                                }
+                            }
+                            UpdateListAction::Pin(tag) => {
+                                self.state = AppState::UpdatePulling;
+                                if let Err(e) = self.pin_selected_update(&mut terminal, &tag).await
+                                {
+                                    self.state = AppState::Error(format!(
+                                        "Failed to switch to {}: {}",
+                                        tag, e
+                                    ));
+                                } else {
+                                    self.state = AppState::UpdateList;
                                 }
                             }
                             UpdateListAction::Refresh => {
@@ -221,42 +457,70 @@ impl App {
                     }
                 }
                 AppState::UpdatePulling => {
-                    if event::poll(std::time::Duration::from_millis(100))? {
-                        if let Event::Key(key) = event::read()? {
-                            if key.kind == KeyEventKind::Press {
-                                if let KeyCode::Char('c') = key.code {
-                                    if key.modifiers.contains(KeyModifiers::CONTROL) {
-                                        self.running = false;
-                                    }
-                                }
+                    if event::poll(std::time::Duration::from_millis(100))?
+                        && let Event::Key(key) = event::read()?
+                        && key.kind == KeyEventKind::Press
+                        && let KeyCode::Char('c') = key.code
+                        && key.modifiers.contains(KeyModifiers::CONTROL)
+                    {
+                        self.running = false;
+                    }
+                }
+                AppState::Preflight => {
+                    if let Some(action) = self.handle_preflight_events()? {
+                        match action {
+                            PreflightAction::Continue => {
+                                let env = EnvFile::load(&Self::env_path()).unwrap_or_default();
+                                self.config_form = configure::config_form(&env);
+                                self.state = AppState::Configure;
+                            }
+                            PreflightAction::Rerun => {
+                                self.preflight = None;
+                                terminal.draw(|frame| self.render(frame))?;
+                                self.start_preflight().await;
+                            }
+                            PreflightAction::Back => {
+                                self.state = AppState::Confirmation;
+                                self.ensure_menu_selection();
                             }
                         }
                     }
                 }
-                AppState::Installing => {
-                    if event::poll(std::time::Duration::from_millis(100))? {
-                        if let Event::Key(key) = event::read()? {
-                            if key.kind == KeyEventKind::Press {
-                                if let KeyCode::Char('c') = key.code {
-                                    if key.modifiers.contains(KeyModifiers::CONTROL) {
-                                        self.running = false;
-                                    }
-                                }
+                AppState::PgUpgrade => {
+                    if let Some(action) = self.handle_pg_upgrade_events()? {
+                        match action {
+                            PgUpgradeAction::Start => self.run_pg_upgrade(&mut terminal).await,
+                            PgUpgradeAction::DeleteOldVolume => self.delete_old_pg_volume().await,
+                            PgUpgradeAction::Back => {
+                                self.state = AppState::Backups;
+                                self.load_backups();
                             }
                         }
                     }
                 }
+                AppState::Installing
+                | AppState::ManagingService
+                | AppState::Uninstalling
+                | AppState::BackingUp
+                | AppState::Restoring
+                | AppState::PgUpgrading => {
+                    if event::poll(std::time::Duration::from_millis(100))?
+                        && let Event::Key(key) = event::read()?
+                        && key.kind == KeyEventKind::Press
+                        && let KeyCode::Char('c') = key.code
+                        && key.modifiers.contains(KeyModifiers::CONTROL)
+                    {
+                        self.running = false;
+                    }
+                }
                 AppState::Success | AppState::Error(_) => {
-                    if event::poll(std::time::Duration::from_millis(100))? {
-                        if let Event::Key(key) = event::read()? {
-                            if key.kind == KeyEventKind::Press {
-                                if let KeyCode::Char('c') = key.code {
-                                    if key.modifiers.contains(KeyModifiers::CONTROL) {
-                                        self.running = false;
-                                    }
-                                }
-                            }
-                        }
+                    if event::poll(std::time::Duration::from_millis(100))?
+                        && let Event::Key(key) = event::read()?
+                        && key.kind == KeyEventKind::Press
+                        && let KeyCode::Char('c') = key.code
+                        && key.modifiers.contains(KeyModifiers::CONTROL)
+                    {
+                        self.running = false;
                     }
                 }
             }
@@ -267,12 +531,18 @@ impl App {
     fn menu_options(&self) -> Vec<MenuSelection> {
         let mut options = Vec::new();
 
+        if self.deployed {
+            options.push(MenuSelection::Dashboard);
+        }
+
         if self.ghcr_token.is_some() {
             options.push(MenuSelection::UpdateToken);
         }
 
         options.push(MenuSelection::CheckUpdates);
-        options.push(MenuSelection::Proceed);  // Always available
+        options.push(MenuSelection::Proceed); // Always available
+        options.push(MenuSelection::Backups);
+        options.push(MenuSelection::Uninstall);
         options.push(MenuSelection::Cancel);
         options
     }
@@ -280,10 +550,10 @@ impl App {
     fn ensure_menu_selection(&mut self) {
         let options = self.menu_options();
 
-        if !options.contains(&self.menu_selection) {
-            if let Some(first) = options.first() {
-                self.menu_selection = first.clone();
-            }
+        if !options.contains(&self.menu_selection)
+            && let Some(first) = options.first()
+        {
+            self.menu_selection = first.clone();
         }
     }
 
@@ -293,123 +563,393 @@ impl App {
         }
     }
 
-    fn token_file_path() -> PathBuf {
-        utils::project_root().join(".ghcr_token")
-    }
+    fn handle_registry_setup_events(&mut self) -> Result<Option<RegistryAction>> {
+        if event::poll(std::time::Duration::from_millis(100))?
+            && let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                self.running = false;
+                return Ok(None);
+            }
 
-    fn load_token_from_disk() -> Option<String> {
-        fs::read_to_string(Self::token_file_path())
-            .ok()
-            .map(|s| s.trim().to_string())
-            .filter(|s| !s.is_empty())
+            return Ok(match self.registry_form.handle_key(key) {
+                Some(FormAction::Submit) => Some(RegistryAction::Submit),
+                Some(FormAction::Cancel) => Some(RegistryAction::Skip),
+                None => None,
+            });
+        }
+
+        Ok(None)
     }
 
-    fn persist_token(&self, token: &str) -> Result<()> {
-        let path = Self::token_file_path();
-        fs::write(&path, token)?;
-        #[cfg(unix)]
+    fn handle_configure_events(&mut self) -> Result<Option<FormAction>> {
+        if event::poll(std::time::Duration::from_millis(100))?
+            && let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
         {
-            let perms = std::fs::Permissions::from_mode(0o600);
-            fs::set_permissions(&path, perms)?;
+            if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                self.running = false;
+                return Ok(None);
+            }
+
+            return Ok(self.config_form.handle_key(key));
+        }
+
+        Ok(None)
+    }
+
+    fn registry_form(config: &RegistryConfig) -> Form {
+        let rewrites = config
+            .rewrites
+            .iter()
+            .map(|rule| format!("{}={}", rule.from, rule.to))
+            .collect::<Vec<_>>()
+            .join(",");
+
+        Form::new(
+            vec![
+                FormField::new(registry::HOST_KEY, "Registry host")
+                    .value(config.host.clone())
+                    .placeholder(registry::GHCR_HOST),
+                FormField::new(registry::USERNAME_KEY, "Username")
+                    .value(config.username.clone().unwrap_or_default())
+                    .placeholder("<detected from token on ghcr.io>"),
+                FormField::new(TOKEN_FIELD, "Token or password")
+                    .placeholder("<paste token here>")
+                    .masked()
+                    .validator(form::required),
+                FormField::new(registry::REWRITE_KEY, "Image prefix rewrite")
+                    .value(rewrites)
+                    .placeholder("<none, e.g. ghcr.io/nexusquantum=harbor.local/nqrust>")
+                    .validator(|value| {
+                        registry::parse_rewrites(value)
+                            .map(|_| ())
+                            .map_err(|e| e.to_string())
+                    }),
+            ],
+            "Submit and login",
+        )
+    }
+
+    /// The registry settings entered on the registry page.
+    fn registry_config_from_form(&self) -> Result<RegistryConfig> {
+        let value = |key: &str| {
+            let value = self.registry_form.value(key).trim();
+            (!value.is_empty()).then(|| value.to_string())
+        };
+
+        Ok(RegistryConfig {
+            host: value(registry::HOST_KEY).unwrap_or_else(|| registry::GHCR_HOST.to_string()),
+            username: value(registry::USERNAME_KEY),
+            rewrites: registry::parse_rewrites(&value(registry::REWRITE_KEY).unwrap_or_default())?,
+        })
+    }
+
+    fn env_path() -> PathBuf {
+        utils::project_root().join(utils::ENV_FILE)
+    }
+
+    /// Validates the configuration form and writes it into `.env`. Leaves the
+    /// reason in the form's error message when validation fails.
+    fn save_config(&mut self) -> Result<()> {
+        if !self.config_form.validate() {
+            return Ok(());
+        }
+
+        let path = Self::env_path();
+        let mut env = EnvFile::load(&path)?;
+        configure::apply_config(&self.config_form, &mut env);
+        env.save(&path)
+    }
+
+    async fn start_installation(&mut self, terminal: &mut impl Surface) {
+        self.state = AppState::Installing;
+        self.logs
+            .push("🚀 Starting Identity installation...".to_string());
+
+        match self.run_docker_compose(terminal).await {
+            Ok(_) => {
+                self.state = AppState::Success;
+                self.progress = 100.0;
+            }
+            Err(e) => {
+                self.state = AppState::Error(format!("Installation failed: {}", e));
+            }
         }
+    }
+
+    /// Resolves the compose project and builds the teardown plan shown on the
+    /// uninstall page. Data and images are kept unless the user opts in.
+    async fn prepare_uninstall(&mut self) -> Result<()> {
+        self.load_project().await?;
+        self.uninstall_plan = UninstallPlan::from_project(&self.project);
+        self.uninstall_selection = UninstallOption::Back;
         Ok(())
     }
 
-    fn handle_registry_setup_events(&mut self) -> Result<Option<RegistryAction>> {
-        if event::poll(std::time::Duration::from_millis(100))? {
-            if let Event::Key(key) = event::read()? {
-                if key.kind == KeyEventKind::Press {
-                    if self.registry_form.editing
-                        && RegistryForm::is_input_field(self.registry_form.current_field)
-                    {
-                        match key.code {
-                            KeyCode::Enter | KeyCode::Esc => {
-                                self.registry_form.editing = false;
-                            }
-                            KeyCode::Backspace => {
-                                self.registry_form.get_current_value_mut().pop();
-                            }
-                            KeyCode::Char(c) => {
-                                if !key.modifiers.contains(KeyModifiers::CONTROL) {
-                                    self.registry_form.get_current_value_mut().push(c);
-                                }
-                            }
-                            _ => {}
-                        }
-                    } else {
-                        match key.code {
-                            KeyCode::Up => {
-                                if self.registry_form.current_field == 0 {
-                                    self.registry_form.current_field =
-                                        self.registry_form.total_items() - 1;
-                                } else {
-                                    self.registry_form.current_field -= 1;
-                                }
-                            }
-                            KeyCode::Down | KeyCode::Tab => {
-                                self.registry_form.current_field =
-                                    (self.registry_form.current_field + 1)
-                                        % self.registry_form.total_items();
-                            }
-                            KeyCode::Enter => {
-                                if RegistryForm::is_input_field(self.registry_form.current_field) {
-                                    self.registry_form.editing = true;
-                                } else {
-                                    return Ok(Some(RegistryAction::Submit));
-                                }
-                            }
-                            KeyCode::Char('s') => {
-                                if key.modifiers.contains(KeyModifiers::CONTROL) {
-                                    return Ok(Some(RegistryAction::Submit));
-                                }
-                            }
-                            KeyCode::Esc | KeyCode::Char('q') => {
-                                return Ok(Some(RegistryAction::Skip));
-                            }
-                            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                                self.running = false;
-                            }
-                            _ => {}
-                        }
+    /// Detects the compose command and refreshes `project` from
+    /// `compose config`. Returns the compose command for follow-up calls.
+    async fn load_project(&mut self) -> Result<Vec<String>> {
+        let compose_cmd = self.detect_compose_command().await?;
+        // The compose file requires the generated secrets to interpolate.
+        self.ensure_compose_bundle().await?;
+        self.project = ComposeProject::load(&compose_cmd, &utils::project_root()).await?;
+        Ok(compose_cmd)
+    }
+
+    /// Points the compose image variables at the mirror by writing rewritten
+    /// references into `.env`. Only install and apply paths call this;
+    /// read-only ones use [`Self::load_project_mirrored`].
+    async fn apply_image_rewrites(
+        &mut self,
+        config: &RegistryConfig,
+        compose_cmd: &[String],
+    ) -> Result<()> {
+        if config.rewrites.is_empty() {
+            return Ok(());
+        }
+
+        let root = utils::project_root();
+        let project = ComposeProject::load(compose_cmd, &root).await?;
+        let variables = ComposeProject::load_uninterpolated(compose_cmd, &root)
+            .await?
+            .image_variables();
+        let rewrites = self.image_rewrites(config, &project, &variables);
+        if rewrites.is_empty() {
+            return Ok(());
+        }
+
+        let path = Self::env_path();
+        let mut env = EnvFile::load(&path)?;
+        for (_, variable, reference) in &rewrites {
+            env.set(variable, reference);
+        }
+        env.save(&path)?;
+        Ok(())
+    }
+
+    /// Refreshes `project` like [`Self::load_project`], with the mirror
+    /// rewrites applied in memory only so `.env` is left untouched. Returns
+    /// the compose command and the variable each service's image is read
+    /// from.
+    async fn load_project_mirrored(
+        &mut self,
+        config: &RegistryConfig,
+    ) -> Result<(Vec<String>, BTreeMap<String, String>)> {
+        let compose_cmd = self.load_project().await?;
+        let variables = ComposeProject::load_uninterpolated(&compose_cmd, &utils::project_root())
+            .await
+            .map(|raw| raw.image_variables())
+            .unwrap_or_default();
+
+        let mut project = std::mem::take(&mut self.project);
+        for (service, _, reference) in self.image_rewrites(config, &project, &variables) {
+            if let Some(entry) = project.services.get_mut(&service) {
+                entry.image = Some(reference);
+            }
+        }
+        self.project = project;
+        Ok((compose_cmd, variables))
+    }
+
+    /// Mirror references `config` gives the services of `project`, as
+    /// `(service, variable, reference)`. Services whose image is not read
+    /// from a variable cannot be mirrored and are reported.
+    fn image_rewrites(
+        &mut self,
+        config: &RegistryConfig,
+        project: &ComposeProject,
+        variables: &BTreeMap<String, String>,
+    ) -> Vec<(String, String, String)> {
+        let mut rewrites = Vec::new();
+        for service in project.pull_services() {
+            let Some(image) = project.image(&service) else {
+                continue;
+            };
+            let Some(rewritten) = config.rewrite(image) else {
+                continue;
+            };
+            match variables.get(&service) {
+                Some(variable) => {
+                    self.add_log(&format!(
+                        "🪞 {}: {} → {} ({})",
+                        service, image, rewritten, variable
+                    ));
+                    rewrites.push((service, variable.clone(), rewritten));
+                }
+                None => self.add_log(&format!(
+                    "⚠️  {} uses a fixed image ({}); use a variable in docker-compose.yaml to mirror it",
+                    service, image
+                )),
+            }
+        }
+        rewrites
+    }
+
+    fn handle_uninstall_events(&mut self) -> Result<Option<UninstallOption>> {
+        if event::poll(std::time::Duration::from_millis(100))?
+            && let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            let index = UNINSTALL_OPTIONS
+                .iter()
+                .position(|option| *option == self.uninstall_selection)
+                .unwrap_or(0);
+
+            match key.code {
+                KeyCode::Up => {
+                    let index = index.checked_sub(1).unwrap_or(UNINSTALL_OPTIONS.len() - 1);
+                    self.uninstall_selection = UNINSTALL_OPTIONS[index];
+                }
+                KeyCode::Down | KeyCode::Tab => {
+                    self.uninstall_selection =
+                        UNINSTALL_OPTIONS[(index + 1) % UNINSTALL_OPTIONS.len()];
+                }
+                KeyCode::Char(' ') | KeyCode::Enter => match self.uninstall_selection {
+                    UninstallOption::Confirm | UninstallOption::Back => {
+                        return Ok(Some(self.uninstall_selection));
                     }
+                    option => self.uninstall_plan.toggle(option),
+                },
+                KeyCode::Esc => return Ok(Some(UninstallOption::Back)),
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    self.running = false;
                 }
+                _ => {}
             }
         }
 
         Ok(None)
     }
 
+    async fn run_uninstall(&mut self, terminal: &mut impl Surface) {
+        self.state = AppState::Uninstalling;
+        self.logs.clear();
+        self.progress = 0.0;
+        self.service_health.clear();
+        self.current_service.clear();
+        self.completed_services = 0;
+        self.total_services = self.uninstall_plan.deletions().len();
+        self.add_log("🗑  Removing the Identity stack...");
+
+        match self.run_compose_down(terminal).await {
+            Ok(()) => {
+                self.progress = 100.0;
+                self.add_log("✅ Identity stack removed");
+                self.state = AppState::Uninstalled;
+            }
+            Err(e) => {
+                self.state = AppState::Error(format!("Uninstall failed: {}", e));
+            }
+        }
+    }
+
+    async fn run_compose_down(&mut self, terminal: &mut impl Surface) -> Result<()> {
+        let compose_cmd = self.detect_compose_command().await?;
+        let args = self.uninstall_plan.down_args();
+
+        if !self
+            .run_compose_step(terminal, &compose_cmd, &args, &[])
+            .await?
+        {
+            return Err(eyre!("Docker Compose down failed"));
+        }
+
+        // podman-compose has no `down --rmi`, so the images go separately.
+        if self.uninstall_plan.remove_images && compose::is_podman_compose(&compose_cmd) {
+            let images = self.uninstall_plan.images.clone();
+            self.add_log_and_redraw(terminal, &format!("🗑  Removing {}", images.join(", ")));
+            let output = Runtime::current()
+                .command()
+                .arg("rmi")
+                .args(&images)
+                .output()
+                .await?;
+            if !output.status.success() {
+                return Err(eyre!(
+                    "Failed to remove images: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ));
+            }
+        }
+        Ok(())
+    }
+
     async fn try_registry_login(&mut self) -> Result<bool> {
         if !self.registry_form.validate() {
             self.registry_status = Some(self.registry_form.error_message.clone());
             return Ok(false);
         }
 
-        let token = self.registry_form.token.trim().to_string();
+        let token = self.registry_form.value(TOKEN_FIELD).trim().to_string();
 
         if token.is_empty() {
             self.registry_status = Some("Token is required".to_string());
             return Ok(false);
         }
 
-        self.registry_status = Some("Resolving GitHub username from token...".to_string());
-
-        let username = match self.fetch_github_username(&token).await {
-            Ok(name) => name,
-            Err(e) => {
-                self.registry_status = Some(format!("Failed to resolve username: {}", e));
+        let config = self.registry_config_from_form()?;
+        let username = if config.is_ghcr() {
+            self.registry_status = Some("Checking token permissions with GitHub...".to_string());
+
+            let client = Client::builder()
+                .timeout(std::time::Duration::from_secs(15))
+                .build()?;
+            let check = match token_check::check_token(&client, &token).await {
+                Ok(Some(check)) => check,
+                Ok(None) => {
+                    self.registry_status = Some(token_check::REJECTED_MESSAGE.to_string());
+                    return Ok(false);
+                }
+                Err(e) => {
+                    self.registry_status = Some(format!("Token validation failed: {}", e));
+                    return Ok(false);
+                }
+            };
+            let now = chrono::Utc::now();
+            let problem = check.problem(now);
+            let username = config
+                .username
+                .clone()
+                .unwrap_or_else(|| check.username.clone());
+            self.token_notice = check
+                .expiry_warning(now)
+                .map(|message| format!("⚠️  {}", message));
+            self.token_check = Some(check);
+            if let Some(problem) = problem {
+                self.registry_status = Some(problem);
                 return Ok(false);
             }
+            username
+        } else {
+            self.token_check = None;
+            self.token_notice = None;
+            match config.username.clone() {
+                Some(username) => username,
+                None => {
+                    self.registry_status = Some(format!(
+                        "A username is required to log in to {}",
+                        config.host
+                    ));
+                    return Ok(false);
+                }
+            }
         };
 
-        self.registry_status = Some("Logging in to ghcr.io...".to_string());
+        let host = config.host.as_str();
+        let runtime = Runtime::current();
+        self.registry_status = Some(format!("Logging in to {}...", host));
         self.add_log(&format!(
-            "🔐 Executing: docker login ghcr.io as {}",
+            "🔐 Executing: {} login {} as {}",
+            runtime.binary(),
+            host,
             username
         ));
 
-        let mut child = Command::new("docker")
-            .args(["login", "ghcr.io", "-u", &username, "--password-stdin"])
+        let mut child = runtime
+            .command()
+            .args(["login", host, "-u", &username, "--password-stdin"])
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
@@ -418,71 +958,93 @@ impl App {
         if let Some(mut stdin) = child.stdin.take() {
             stdin.write_all(format!("{}\n", token).as_bytes()).await?;
         } else {
-            self.registry_status = Some("Failed to communicate with docker login".to_string());
+            self.registry_status = Some(format!(
+                "Failed to communicate with {} login",
+                runtime.binary()
+            ));
             return Ok(false);
         }
 
         let output = child.wait_with_output().await?;
 
         if output.status.success() {
-            self.registry_status = Some("Authenticated with ghcr.io successfully".to_string());
+            // Only a working login gets to point later pulls at the registry.
+            let path = Self::env_path();
+            let mut env = EnvFile::load(&path)?;
+            config.save(&mut env);
+            env.save(&path)?;
+
             self.ghcr_token = Some(token.clone());
             self.registry_form.error_message.clear();
+            let expiry = self
+                .token_check
+                .as_ref()
+                .map(|check| format!(" (token {})", check.expiry(chrono::Utc::now())))
+                .unwrap_or_default();
             // Persist so users don't have to paste again
-            if let Err(e) = self.persist_token(&token) {
-                self.registry_status = Some(format!(
-                    "Authenticated, but failed to cache token locally: {}",
-                    e
-                ));
-            }
+            self.registry_status = Some(match secrets::store_token(&username, &token) {
+                Ok(store) => format!(
+                    "Authenticated with {}{}; token saved in {}",
+                    host, expiry, store
+                ),
+                Err(e) => format!("Authenticated, but failed to save the token: {}", e),
+            });
             Ok(true)
         } else {
             let stderr = String::from_utf8_lossy(&output.stderr);
             let stdout = String::from_utf8_lossy(&output.stdout);
-            
+
             let error_msg = if !stderr.trim().is_empty() {
                 stderr.trim().to_string()
             } else if !stdout.trim().is_empty() {
                 stdout.trim().to_string()
             } else {
-                "Docker login failed with no error message. Check: 1) Docker is running, 2) Docker credential helper is configured, 3) Try manual login: docker login ghcr.io".to_string()
+                format!(
+                    "{} login failed with no error message. Check: 1) {} is running, 2) its credential helper is configured, 3) Try manual login: {} login {}",
+                    runtime.name(),
+                    runtime.name(),
+                    runtime.binary(),
+                    host
+                )
             };
-            
-            self.registry_status = Some(format!("Docker login failed: {}", error_msg));
+
+            self.registry_status = Some(format!("{} login failed: {}", runtime.name(), error_msg));
             Ok(false)
         }
     }
 
-    async fn fetch_github_username(&self, token: &str) -> Result<String> {
-        #[derive(Deserialize)]
-        struct GitHubUser {
-            login: String,
-        }
-
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(15))
-            .build()?;
-
-        let response = client
-            .get("https://api.github.com/user")
-            .header("User-Agent", "nqrust-identity")
-            .header("Accept", "application/vnd.github+json")
-            .bearer_auth(token)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            return Err(eyre!(
-                "GitHub API returned {} when fetching user info: {}",
-                status,
-                body
-            ));
+    /// Re-validates the saved token at startup so an expiring or revoked
+    /// token is flagged before an install or update needs it. Network
+    /// failures are ignored; the token is checked again on login.
+    async fn check_saved_token(&mut self) {
+        let Some(token) = self.ghcr_token.clone() else {
+            return;
+        };
+        if !RegistryConfig::configured().is_ok_and(|config| config.is_ghcr()) {
+            return;
         }
-
-        let user: GitHubUser = response.json().await?;
-        Ok(user.login)
+        let Ok(client) = Client::builder()
+            .timeout(std::time::Duration::from_secs(5))
+            .build()
+        else {
+            return;
+        };
+        let check = match token_check::check_token(&client, &token).await {
+            Ok(Some(check)) => check,
+            Ok(None) => {
+                self.token_notice = Some(format!("⚠️  {}", token_check::REJECTED_MESSAGE));
+                return;
+            }
+            Err(_) => return,
+        };
+        let _ = secrets::migrate_legacy_token_for(&check.username, &token);
+
+        let now = chrono::Utc::now();
+        self.token_notice = check
+            .problem(now)
+            .or_else(|| check.expiry_warning(now))
+            .map(|message| format!("⚠️  {}", message));
+        self.token_check = Some(check);
     }
 
     async fn load_updates(&mut self) -> Result<()> {
@@ -503,7 +1065,7 @@ impl App {
             if let Some(token) = env_token.clone() {
                 self.ghcr_token = Some(token.clone());
                 Some(token)
-            } else if let Some(token) = App::load_token_from_disk() {
+            } else if let Some(token) = secrets::load_token() {
                 self.ghcr_token = Some(token.clone());
                 Some(token)
             } else {
@@ -511,15 +1073,20 @@ impl App {
             }
         };
 
-        self.update_infos = collect_update_infos(&client, token.as_deref()).await?;
+        let config = RegistryConfig::configured()?;
+        let (_, image_variables) = self.load_project_mirrored(&config).await?;
+        let credentials = config.credentials(token.as_deref());
+        self.update_infos =
+            collect_update_infos(&client, &credentials, &self.project, &image_variables).await?;
         self.ensure_update_selection();
 
         if self.update_infos.is_empty() {
-            self.update_message =
-                Some("No GHCR-backed services were found in docker-compose.yaml".to_string());
+            self.update_message = Some(
+                "No services with registry images were found in docker-compose.yaml".to_string(),
+            );
         } else {
             self.update_message = Some(
-                "Use ↑/↓ to pick a service, Enter or P to pull :latest, R to refresh, Esc to go back"
+                "Use ↑/↓ to pick a service, Enter or A to apply (with backup and rollback), P to pull only, T to pick a tag, R to refresh, Esc to go back"
                     .to_string(),
             );
         }
@@ -527,120 +1094,183 @@ impl App {
         Ok(())
     }
 
-    fn redraw(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
-        terminal.draw(|frame| self.render(frame))?;
-        Ok(())
+    fn redraw(&mut self, terminal: &mut impl Surface) -> Result<()> {
+        terminal.refresh(self)
     }
 
-    fn add_log_and_redraw(&mut self, terminal: &mut DefaultTerminal, message: &str) {
+    fn add_log_and_redraw(&mut self, terminal: &mut impl Surface, message: &str) {
         self.add_log(message);
         let _ = self.redraw(terminal);
     }
 
-    async fn pull_selected_update(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
-        if self.update_infos.is_empty() {
+    /// Pulls the selected service's image (or self-updates the installer)
+    /// without touching the running container.
+    async fn pull_selected_update(&mut self, terminal: &mut impl Surface) -> Result<()> {
+        let Some((index, info)) = self.selected_update() else {
             return Ok(());
+        };
+
+        if info.is_self {
+            return self.self_update(info, terminal).await;
         }
 
-        // Reset progress for pull/self-update flows
-        self.progress = 0.0;
+        self.logs.clear();
+        self.pull_service_image(terminal, &info).await?;
+        self.refresh_local_image(index).await;
+        self.update_message = Some(
+            "Image pulled. Press A to apply it, R to fetch remote metadata again.".to_string(),
+        );
+        Ok(())
+    }
 
-        let index = self.update_selection_index.min(self.update_infos.len() - 1);
-        let info = self.update_infos[index].clone();
+    /// Pulls, recreates and health-checks the selected service, rolling back
+    /// on failure. Reports the image before and after in the update message.
+    async fn apply_selected_update(&mut self, terminal: &mut impl Surface) -> Result<()> {
+        let Some((index, info)) = self.selected_update() else {
+            return Ok(());
+        };
 
         if info.is_self {
             return self.self_update(info, terminal).await;
         }
 
-        let reference = info.pull_reference();
-        let image = info.image.clone();
-        let tag = info.current_tag.clone();
+        self.logs.clear();
+        let report = self.update_service(terminal, &info, None).await?;
+        self.refresh_local_image(index).await;
+        self.update_message = Some(report.summary());
+        Ok(())
+    }
+
+    /// Pins the selected service to `tag` in `.env`, then updates it like
+    /// [`Self::apply_selected_update`]. The pin is reverted on rollback.
+    async fn pin_selected_update(&mut self, terminal: &mut impl Surface, tag: &str) -> Result<()> {
+        let Some((index, info)) = self.selected_update() else {
+            return Ok(());
+        };
+        let env_key = info
+            .env_key
+            .clone()
+            .ok_or_else(|| eyre!("{} cannot be pinned to a tag", info.display_name))?;
+        let pin = ImagePin {
+            env_key,
+            reference: info.reference_for(tag),
+        };
+
+        // A new major cannot start on the existing data directory.
+        let majors = (tag_major(&info.current_tag), tag_major(tag));
+        if info.service.as_deref() == Some(backup::DATABASE_SERVICE)
+            && let (Some(current), Some(target)) = majors
+            && current != target
+        {
+            self.update_message = Some(format!(
+                "PostgreSQL {} → {} needs a dump and restore: use U on the Backup and restore page",
+                current, target
+            ));
+            return Ok(());
+        }
 
         self.logs.clear();
-        self.add_log_and_redraw(
-            terminal,
-            &format!("⬇️  Executing: docker pull {}", reference),
-        );
+        let report = self.update_service(terminal, &info, Some(&pin)).await?;
+        if let Some(info) = self.update_infos.get_mut(index) {
+            info.current_tag = tag.to_string();
+            // The digest was resolved for the old tag; R fetches the new one.
+            info.remote_digest = None;
+        }
+        self.refresh_local_image(index).await;
+        self.update_message = Some(format!("{} (pinned to {})", report.summary(), tag));
+        Ok(())
+    }
 
-        let mut child = Command::new("docker")
-            .args(["pull", &reference])
-            .env("DOCKER_CLI_PROGRESS", "plain")
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()?;
+    /// Opens the tag picker for the selected service, or explains why it
+    /// cannot be opened.
+    fn open_tag_picker(&mut self) {
+        let Some(info) = self.update_infos.get(self.update_selection_index) else {
+            return;
+        };
 
-        let stdout = child
-            .stdout
-            .take()
-            .ok_or_else(|| eyre!("Failed to capture docker pull stdout"))?;
-        let stderr = child
-            .stderr
-            .take()
-            .ok_or_else(|| eyre!("Failed to capture docker pull stderr"))?;
+        if info.is_self {
+            self.update_message = Some(format!("{} cannot be pinned to a tag", info.display_name));
+            return;
+        }
+        if info.env_key.is_none() {
+            self.update_message = Some(format!(
+                "{} has a fixed image in docker-compose.yaml; use image: ${{VAR:-{}}} to pin tags",
+                info.display_name,
+                info.pull_reference()
+            ));
+            return;
+        }
 
-        let mut stdout_reader = BufReader::new(stdout).lines();
-        let mut stderr_reader = BufReader::new(stderr).lines();
+        let picker = TagPicker::new(info);
+        if picker.tags.is_empty() {
+            self.update_message = Some(format!(
+                "No tags are known for {}; press R to fetch them",
+                info.display_name
+            ));
+        } else {
+            self.tag_picker = Some(picker);
+        }
+    }
 
-        loop {
-            tokio::select! {
-                output = stdout_reader.next_line() => {
-                    match output {
-                        Ok(Some(line)) => {
-                            self.add_log_and_redraw(terminal, &format!("ℹ️  {}", line));
-                        }
-                        Ok(None) => break,
-                        Err(e) => {
-                            self.add_log_and_redraw(terminal, &format!("❌ Error reading stdout: {}", e));
-                            break;
-                        }
-                    }
-                }
-                output = stderr_reader.next_line() => {
-                    match output {
-                        Ok(Some(line)) => {
-                            self.add_log_and_redraw(terminal, &format!("⚠️  {}", line));
-                        }
-                        Ok(None) => break,
-                        Err(e) => {
-                            self.add_log_and_redraw(terminal, &format!("❌ Error reading stderr: {}", e));
-                            break;
-                        }
-                    }
-                }
+    fn handle_tag_picker_key(&mut self, code: KeyCode) -> Option<UpdateListAction> {
+        let picker = self.tag_picker.as_mut()?;
+        match code {
+            KeyCode::Up => {
+                picker.selected = picker
+                    .selected
+                    .checked_sub(1)
+                    .unwrap_or(picker.tags.len() - 1);
+            }
+            KeyCode::Down | KeyCode::Tab => {
+                picker.selected = (picker.selected + 1) % picker.tags.len();
             }
+            KeyCode::Enter => {
+                let tag = picker.selected_tag().map(str::to_string);
+                self.tag_picker = None;
+                return tag.map(UpdateListAction::Pin);
+            }
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('Q') => {
+                self.tag_picker = None;
+            }
+            _ => {}
         }
+        None
+    }
 
-        let status = child.wait().await?;
-
-        if !status.success() {
-            return Err(eyre!("docker pull exited with a non-zero status"));
+    fn selected_update(&mut self) -> Option<(usize, UpdateInfo)> {
+        if self.update_infos.is_empty() {
+            return None;
         }
 
-        self.add_log_and_redraw(terminal, "✅ Image pulled successfully");
+        // Reset progress for pull/self-update flows
+        self.progress = 0.0;
+
+        let index = self.update_selection_index.min(self.update_infos.len() - 1);
+        Some((index, self.update_infos[index].clone()))
+    }
+
+    async fn refresh_local_image(&mut self, index: usize) {
+        let Some(info) = self.update_infos.get(index) else {
+            return;
+        };
 
-        match get_local_image_created(&image, &tag).await {
-            Ok(created) => {
+        match inspect_local_image(&info.image, &info.current_tag).await {
+            Ok(image) => {
                 if let Some(info) = self.update_infos.get_mut(index) {
                     info.clear_local_error();
-                    info.apply_local_created(created);
+                    info.apply_local_image(image);
                 }
             }
             Err(e) => {
                 if let Some(info) = self.update_infos.get_mut(index) {
                     info.append_status(&format!("Failed to inspect local image: {}", e));
-                    info.apply_local_created(None);
+                    info.apply_local_image(None);
                 }
             }
         }
-
-        Ok(())
     }
 
-    async fn self_update(
-        &mut self,
-        info: UpdateInfo,
-        terminal: &mut DefaultTerminal,
-    ) -> Result<()> {
+    async fn self_update(&mut self, info: UpdateInfo, terminal: &mut impl Surface) -> Result<()> {
         let download_url = info
             .download_url
             .clone()
@@ -807,47 +1437,45 @@ impl App {
     fn handle_update_list_events(&mut self) -> Result<Option<UpdateListAction>> {
         self.ensure_update_selection();
 
-        if event::poll(std::time::Duration::from_millis(100))? {
-            if let Event::Key(key) = event::read()? {
-                if key.kind == KeyEventKind::Press {
-                    match key.code {
-                        KeyCode::Up => {
-                            if !self.update_infos.is_empty() {
-                                if self.update_selection_index == 0 {
-                                    self.update_selection_index = self.update_infos.len() - 1;
-                                } else {
-                                    self.update_selection_index -= 1;
-                                }
-                            }
-                        }
-                        KeyCode::Down | KeyCode::Tab => {
-                            if !self.update_infos.is_empty() {
-                                self.update_selection_index =
-                                    (self.update_selection_index + 1) % self.update_infos.len();
-                            }
-                        }
-                        KeyCode::Enter => {
-                            if !self.update_infos.is_empty() {
-                                return Ok(Some(UpdateListAction::Pull));
-                            }
-                        }
-                        KeyCode::Char('p') | KeyCode::Char('P') => {
-                            if !self.update_infos.is_empty() {
-                                return Ok(Some(UpdateListAction::Pull));
-                            }
-                        }
-                        KeyCode::Char('r') | KeyCode::Char('R') => {
-                            return Ok(Some(UpdateListAction::Refresh));
-                        }
-                        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('Q') => {
-                            return Ok(Some(UpdateListAction::Back));
-                        }
-                        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                            self.running = false;
-                        }
-                        _ => {}
+        if event::poll(std::time::Duration::from_millis(100))?
+            && let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            if self.tag_picker.is_some() && !key.modifiers.contains(KeyModifiers::CONTROL) {
+                return Ok(self.handle_tag_picker_key(key.code));
+            }
+
+            match key.code {
+                KeyCode::Up if !self.update_infos.is_empty() => {
+                    if self.update_selection_index == 0 {
+                        self.update_selection_index = self.update_infos.len() - 1;
+                    } else {
+                        self.update_selection_index -= 1;
                     }
                 }
+                KeyCode::Down | KeyCode::Tab if !self.update_infos.is_empty() => {
+                    self.update_selection_index =
+                        (self.update_selection_index + 1) % self.update_infos.len();
+                }
+                KeyCode::Enter | KeyCode::Char('a') | KeyCode::Char('A')
+                    if !self.update_infos.is_empty() =>
+                {
+                    return Ok(Some(UpdateListAction::Apply));
+                }
+                KeyCode::Char('p') | KeyCode::Char('P') if !self.update_infos.is_empty() => {
+                    return Ok(Some(UpdateListAction::Pull));
+                }
+                KeyCode::Char('t') | KeyCode::Char('T') => self.open_tag_picker(),
+                KeyCode::Char('r') | KeyCode::Char('R') => {
+                    return Ok(Some(UpdateListAction::Refresh));
+                }
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('Q') => {
+                    return Ok(Some(UpdateListAction::Back));
+                }
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    self.running = false;
+                }
+                _ => {}
             }
         }
 
@@ -857,84 +1485,106 @@ impl App {
     fn handle_confirmation_events(&mut self) -> Result<Option<MenuSelection>> {
         self.ensure_menu_selection();
 
-        if event::poll(std::time::Duration::from_millis(100))? {
-            if let Event::Key(key) = event::read()? {
-                if key.kind == KeyEventKind::Press {
-                    let options = self.menu_options();
-                    if options.is_empty() {
-                        return Ok(None);
-                    }
+        if event::poll(std::time::Duration::from_millis(100))?
+            && let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            let options = self.menu_options();
+            if options.is_empty() {
+                return Ok(None);
+            }
 
-                    let mut index = options
-                        .iter()
-                        .position(|option| option == &self.menu_selection)
-                        .unwrap_or(0);
-
-                    match key.code {
-                        KeyCode::Up => {
-                            if index == 0 {
-                                index = options.len() - 1;
-                            } else {
-                                index -= 1;
-                            }
-                            self.menu_selection = options[index].clone();
-                        }
-                        KeyCode::Down | KeyCode::Tab => {
-                            index = (index + 1) % options.len();
-                            self.menu_selection = options[index].clone();
-                        }
-                        KeyCode::Enter => {
-                            return Ok(Some(self.menu_selection.clone()));
-                        }
-                        KeyCode::Esc | KeyCode::Char('q') => {
-                            return Ok(Some(MenuSelection::Cancel));
-                        }
-                        KeyCode::Char('c') => {
-                            if key.modifiers.contains(KeyModifiers::CONTROL) {
-                                return Ok(Some(MenuSelection::Cancel));
-                            }
-                        }
-                        _ => {}
+            let mut index = options
+                .iter()
+                .position(|option| option == &self.menu_selection)
+                .unwrap_or(0);
+
+            match key.code {
+                KeyCode::Up => {
+                    if index == 0 {
+                        index = options.len() - 1;
+                    } else {
+                        index -= 1;
                     }
+                    self.menu_selection = options[index].clone();
+                }
+                KeyCode::Down | KeyCode::Tab => {
+                    index = (index + 1) % options.len();
+                    self.menu_selection = options[index].clone();
                 }
+                KeyCode::Enter => {
+                    return Ok(Some(self.menu_selection.clone()));
+                }
+                KeyCode::Esc | KeyCode::Char('q') => {
+                    return Ok(Some(MenuSelection::Cancel));
+                }
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    return Ok(Some(MenuSelection::Cancel));
+                }
+                _ => {}
             }
         }
         Ok(None)
     }
 
     async fn detect_compose_command(&self) -> Result<Vec<String>> {
-        // Prefer the integrated Docker CLI plugin first
-        let docker_compose = Command::new("docker")
-            .args(["compose", "version"])
-            .output()
-            .await;
-
-        if let Ok(output) = docker_compose {
-            if output.status.success() {
-                return Ok(vec!["docker".to_string(), "compose".to_string()]);
-            }
-        }
-
-        // Fallback to standalone docker-compose
-        let standalone = Command::new("docker-compose").arg("version").output().await;
-
-        if let Ok(output) = standalone {
-            if output.status.success() {
-                return Ok(vec!["docker-compose".to_string()]);
+        // Prefer the CLI subcommand (`docker compose`, `podman compose`), then
+        // the standalone binary. `podman compose` handing over to
+        // podman-compose is skipped for podman-compose itself, which is the
+        // next candidate.
+        let runtime = Runtime::current();
+        for candidate in runtime.compose_candidates() {
+            let output = Command::new(candidate[0])
+                .args(&candidate[1..])
+                .arg("version")
+                .output()
+                .await;
+            if let Ok(output) = output
+                && output.status.success()
+            {
+                let version = format!(
+                    "{}{}",
+                    String::from_utf8_lossy(&output.stdout),
+                    String::from_utf8_lossy(&output.stderr)
+                );
+                if candidate[0] != compose::PODMAN_COMPOSE
+                    && compose::reports_podman_compose(&version)
+                {
+                    continue;
+                }
+                return Ok(candidate.iter().map(|part| part.to_string()).collect());
             }
         }
 
+        let tried = runtime
+            .compose_candidates()
+            .map(|candidate| format!("`{}`", candidate.join(" ")));
         Err(eyre!(
-            "Could not find Docker Compose. Tried `docker compose` and `docker-compose`. Install Docker Compose v2 or the standalone docker-compose."
+            "Could not find a compose tool for {}. Tried {} and {}. Install Docker Compose v2 (for Podman, Docker Compose v2 or podman-compose).",
+            runtime.name(),
+            tried[0],
+            tried[1]
         ))
     }
 
+    /// Scaffolds the compose file and `.env`, keeping freshly generated admin
+    /// credentials for the success page.
+    async fn ensure_compose_bundle(&mut self) -> Result<()> {
+        if let Some(credentials) = utils::ensure_compose_bundle(&utils::project_root()).await? {
+            self.add_log(&format!(
+                "🔑 Generated admin and database passwords in {}",
+                Self::env_path().display()
+            ));
+            self.generated_credentials = Some(credentials);
+        }
+        Ok(())
+    }
 
-    async fn run_docker_compose(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+    async fn run_docker_compose(&mut self, terminal: &mut impl Surface) -> Result<()> {
         let compose_cmd = self.detect_compose_command().await?;
 
         let project_root = utils::project_root();
-        utils::ensure_compose_bundle(&project_root)?;
+        self.ensure_compose_bundle().await?;
         let compose_files = [
             "docker-compose.yml",
             "docker-compose.yaml",
@@ -942,9 +1592,7 @@ impl App {
             "compose.yaml",
         ];
 
-        let has_compose = compose_files
-            .iter()
-            .any(|name| project_root.join(name).exists());
+        let has_compose = compose_files.iter().any(|name| utils::find_file(name));
 
         if !has_compose {
             let mut msg = format!(
@@ -955,219 +1603,532 @@ impl App {
             return Err(color_eyre::eyre::eyre!(msg));
         }
 
-        self.add_log("🔨 Step 1/2: Building images...");
-        self.add_log(&format!("📦 Executing: {} build", compose_cmd.join(" ")));
-        let _ = self.redraw(terminal);
+        // Bundled images keep the references they were saved under.
+        if !self.offline {
+            let registry_config = RegistryConfig::configured()?;
+            self.apply_image_rewrites(&registry_config, &compose_cmd)
+                .await?;
+        }
+
+        let project = ComposeProject::load(&compose_cmd, &project_root)
+            .await
+            .map_err(|e| eyre!("Failed to read the compose project: {}", e))?;
+        self.total_services = project.services.len();
+        self.completed_services = 0;
+        self.add_log(&format!(
+            "📋 Compose project {} with services: {}",
+            project.name,
+            project.service_names().join(", ")
+        ));
 
-        let buildkit_available = self.buildkit_available().await.unwrap_or(false);
-        if buildkit_available {
-            self.add_log_and_redraw(terminal, "🛠 Using BuildKit for builds");
+        let pull_services = if self.offline {
+            Vec::new()
         } else {
-            self.add_log_and_redraw(
-                terminal,
-                "⚠️ BuildKit (docker buildx) not available. Please install docker-buildx-plugin and retry.",
-            );
-            return Err(color_eyre::eyre::eyre!(
-                "BuildKit is required but docker buildx is missing"
+            project.pull_services()
+        };
+        let build_services = project.build_services();
+        let total_steps = if build_services.is_empty() { 3 } else { 4 };
+        let mut step = 1;
+
+        if self.offline {
+            self.add_log(&format!(
+                "📦 Step {}/{}: Using images loaded from the bundle",
+                step, total_steps
+            ));
+        } else {
+            self.add_log(&format!(
+                "⬇️  Step {}/{}: Pulling images...",
+                step, total_steps
             ));
         }
+        self.project = project;
+        self.init_service_health();
+        let _ = self.redraw(terminal);
 
-        let mut build_child = {
-            let mut cmd = Command::new(&compose_cmd[0]);
-            if compose_cmd.len() > 1 {
-                cmd.arg(&compose_cmd[1]);
-            }
-            cmd.arg("build");
-            if buildkit_available {
-                cmd.env("DOCKER_BUILDKIT", "1");
+        for (index, service) in pull_services.iter().enumerate() {
+            let image = self.project.image(service).unwrap_or_default().to_string();
+            self.current_service = service.clone();
+            self.add_log(&format!(
+                "⬇️  Pulling image for {} ({}/{}): {}",
+                service,
+                index + 1,
+                pull_services.len(),
+                image
+            ));
+
+            self.pull_slot = Some((index, pull_services.len()));
+            self.pull_progress = PullProgress::default();
+            let pulled = self.pull_service(terminal, service).await;
+            self.pull_slot = None;
+            pulled?;
+
+            self.progress = ((index + 1) as f64 / pull_services.len() as f64) * 40.0;
+            self.add_log(&format!("✅ Pulled {}", image));
+            let _ = self.redraw(terminal);
+        }
+
+        self.progress = 40.0;
+
+        if !build_services.is_empty() {
+            step += 1;
+            self.add_log(&format!(
+                "🔨 Step {}/{}: Building {}...",
+                step,
+                total_steps,
+                build_services.join(", ")
+            ));
+
+            let buildkit_available = self.buildkit_available().await.unwrap_or(false);
+            let buildkit = if buildkit_available {
+                self.add_log("🛠 Using BuildKit for builds");
+                "1"
+            } else {
+                self.add_log(match Runtime::current() {
+                    Runtime::Docker => {
+                        "⚠️  BuildKit (docker buildx) not available; using the classic builder"
+                    }
+                    Runtime::Podman => "🛠 Building with Podman (Buildah)",
+                });
+                "0"
+            };
+
+            let mut args = vec!["build"];
+            args.extend(build_services.iter().map(String::as_str));
+            if !self
+                .run_compose_step(
+                    terminal,
+                    &compose_cmd,
+                    &args,
+                    &[("DOCKER_BUILDKIT", buildkit)],
+                )
+                .await?
+            {
+                return Err(eyre!("Docker Compose build failed"));
             }
-            cmd.env("DOCKER_CLI_PROGRESS", "plain")
-                .current_dir(&project_root)
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
-                .spawn()?
+
+            self.add_log("✅ Build completed successfully!");
+        }
+
+        self.progress = 50.0;
+        step += 1;
+        self.add_log(&format!(
+            "🚀 Step {}/{}: Starting services...",
+            step, total_steps
+        ));
+        let _ = self.redraw(terminal);
+
+        let up_args: &[&str] = if self.offline {
+            &["up", "-d", "--pull", "never"]
+        } else {
+            &["up", "-d"]
         };
+        if !self
+            .run_compose_step(terminal, &compose_cmd, up_args, &[])
+            .await?
+        {
+            return Err(eyre!("Docker Compose up failed"));
+        }
 
-        let build_stdout = build_child.stdout.take().expect("Failed to capture stdout");
-        let build_stderr = build_child.stderr.take().expect("Failed to capture stderr");
+        self.add_log("✅ All services started successfully!");
+        self.progress = 80.0;
+        let _ = self.redraw(terminal);
 
-        let mut build_stdout_reader = BufReader::new(build_stdout).lines();
-        let mut build_stderr_reader = BufReader::new(build_stderr).lines();
+        self.wait_for_health(terminal).await
+    }
 
-        loop {
+    /// Runs `compose <args>` in the project root, feeding every output line
+    /// through `process_log_line`. Returns whether the command succeeded.
+    async fn run_compose_step(
+        &mut self,
+        terminal: &mut impl Surface,
+        compose_cmd: &[String],
+        args: &[&str],
+        envs: &[(&str, &str)],
+    ) -> Result<bool> {
+        let args = compose::args(compose_cmd, args);
+        self.add_log(&format!(
+            "📦 Executing: {} {}",
+            compose_cmd.join(" "),
+            args.join(" ")
+        ));
+        let _ = self.redraw(terminal);
+
+        let mut child = compose::command(compose_cmd, &utils::project_root())
+            .args(&args)
+            .envs(envs.iter().copied())
+            .env("DOCKER_CLI_PROGRESS", "plain")
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| eyre!("Failed to capture stdout"))?;
+        let stderr = child
+            .stderr
+            .take()
+            .ok_or_else(|| eyre!("Failed to capture stderr"))?;
+
+        let mut stdout_reader = BufReader::new(stdout).lines();
+        let mut stderr_reader = BufReader::new(stderr).lines();
+        let mut stdout_done = false;
+        let mut stderr_done = false;
+
+        while !(stdout_done && stderr_done) {
             tokio::select! {
-                result = build_stdout_reader.next_line() => {
+                result = stdout_reader.next_line(), if !stdout_done => {
                     match result {
                         Ok(Some(line)) => {
                             self.process_log_line(&line);
                             let _ = self.redraw(terminal);
                         }
-                        Ok(None) => break,
+                        Ok(None) => stdout_done = true,
                         Err(e) => {
                             self.add_log(&format!("❌ Error reading stdout: {}", e));
-                            let _ = self.redraw(terminal);
-                            break;
+                            stdout_done = true;
                         }
                     }
                 }
-                result = build_stderr_reader.next_line() => {
+                result = stderr_reader.next_line(), if !stderr_done => {
                     match result {
                         Ok(Some(line)) => {
                             self.process_log_line(&line);
                             let _ = self.redraw(terminal);
                         }
-                        Ok(None) => break,
+                        Ok(None) => stderr_done = true,
                         Err(e) => {
                             self.add_log(&format!("❌ Error reading stderr: {}", e));
-                            let _ = self.redraw(terminal);
-                            break;
+                            stderr_done = true;
                         }
                     }
                 }
             }
         }
 
-        let build_status = build_child.wait().await?;
+        Ok(child.wait().await?.success())
+    }
 
-        if !build_status.success() {
-            return Err(color_eyre::eyre::eyre!("Docker Compose build failed"));
-        }
+    /// Pulls the image of `service` through [`Self::pull_image`].
+    async fn pull_service(&mut self, terminal: &mut impl Surface, service: &str) -> Result<()> {
+        let image = self
+            .project
+            .image(service)
+            .ok_or_else(|| eyre!("{} has no image to pull", service))?
+            .to_string();
+        self.pull_image(terminal, service, &image).await
+    }
 
-        self.add_log("✅ Build completed successfully!");
-        self.progress = 50.0;
+    /// Pulls `image` for `service` through the Docker Engine API, feeding
+    /// its layer progress into the same state as `compose pull` output.
+    /// Credentials come from the installer's registry login, falling back to
+    /// what `docker login` stored.
+    async fn pull_image(
+        &mut self,
+        terminal: &mut impl Surface,
+        service: &str,
+        image: &str,
+    ) -> Result<()> {
+        let host = Registry::for_image(image).host().to_string();
+        let config = RegistryConfig::configured().unwrap_or_default();
+        let auth = match config
+            .credentials(self.ghcr_token.as_deref())
+            .basic_auth_for(&host)
+        {
+            Some((username, password)) => Some(RegistryAuth {
+                username: username.to_string(),
+                password: password.to_string(),
+                server: host.clone(),
+            }),
+            None => RegistryAuth::stored(&host),
+        };
+
+        self.apply_progress_event(ProgressEvent::ServicePull {
+            service: service.to_string(),
+            done: false,
+        });
         let _ = self.redraw(terminal);
+        DockerClient::from_env()?
+            .pull(image, auth.as_ref(), |event| {
+                if let Some(event) = progress::pull_event(event) {
+                    self.apply_progress_event(event);
+                    let _ = self.redraw(terminal);
+                }
+            })
+            .await
+            .map_err(|e| eyre!("Failed to pull {} for {}: {}", image, service, e))?;
+        self.apply_progress_event(ProgressEvent::ServicePull {
+            service: service.to_string(),
+            done: true,
+        });
+        Ok(())
+    }
 
-        self.add_log("🚀 Step 2/2: Starting services...");
-        self.add_log(&format!("📦 Executing: {} up -d", compose_cmd.join(" ")));
+    /// Polls every service until its container healthcheck (and readiness URL,
+    /// when it has one) passes. Fails fast when a container exits or turns
+    /// unhealthy, and gives up after `HEALTH_TIMEOUT`; in both cases the tail
+    /// of the offending containers' logs is appended to the installation log.
+    async fn wait_for_health(&mut self, terminal: &mut impl Surface) -> Result<()> {
+        self.add_log("🩺 Waiting for services to become healthy...");
         let _ = self.redraw(terminal);
 
-        let mut up_child = {
-            let mut cmd = Command::new(&compose_cmd[0]);
-            if compose_cmd.len() > 1 {
-                cmd.arg(&compose_cmd[1]);
-            }
-            cmd.args(["up", "-d"])
-                .env("DOCKER_CLI_PROGRESS", "plain")
-                .current_dir(&project_root)
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
-                .spawn()?
-        };
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_secs(3))
+            .build()?;
+        let started = std::time::Instant::now();
 
-        let up_stdout = up_child.stdout.take().expect("Failed to capture stdout");
-        let up_stderr = up_child.stderr.take().expect("Failed to capture stderr");
+        loop {
+            for index in 0..self.service_health.len() {
+                let entry = self.service_health[index].clone();
 
-        let mut up_stdout_reader = BufReader::new(up_stdout).lines();
-        let mut up_stderr_reader = BufReader::new(up_stderr).lines();
+                let (mut state, mut detail) =
+                    match health::inspect_container_health(&entry.container).await {
+                        Ok(result) => result,
+                        Err(e) => (HealthState::Waiting, e.to_string()),
+                    };
 
-        loop {
-            tokio::select! {
-                result = up_stdout_reader.next_line() => {
-                    match result {
-                        Ok(Some(line)) => {
-                            self.process_log_line(&line);
-                            let _ = self.redraw(terminal);
-                        }
-                        Ok(None) => break,
-                        Err(e) => {
-                            self.add_log(&format!("❌ Error reading stdout: {}", e));
-                            let _ = self.redraw(terminal);
-                            break;
-                        }
-                    }
+                if state == HealthState::Starting {
+                    health::run_healthcheck(&entry.container).await;
                 }
-                result = up_stderr_reader.next_line() => {
-                    match result {
-                        Ok(Some(line)) => {
-                            self.process_log_line(&line);
-                            let _ = self.redraw(terminal);
+
+                if state == HealthState::Healthy
+                    && let Some(url) = &entry.ready_url
+                    && !health::http_ready(&client, url).await
+                {
+                    state = HealthState::Starting;
+                    detail = format!("waiting for {}", url);
+                }
+
+                if state != entry.state {
+                    self.current_service = entry.service.clone();
+                    match state {
+                        HealthState::Healthy => {
+                            self.add_log(&format!("✅ {} is healthy", entry.service))
                         }
-                        Ok(None) => break,
-                        Err(e) => {
-                            self.add_log(&format!("❌ Error reading stderr: {}", e));
-                            let _ = self.redraw(terminal);
-                            break;
+                        HealthState::Unhealthy | HealthState::Exited => {
+                            self.add_log(&format!("❌ {}: {}", entry.service, detail))
                         }
+                        _ => self.add_log(&format!(
+                            "⏳ {} is {} {}",
+                            entry.service,
+                            state.label(),
+                            detail
+                        )),
                     }
                 }
+
+                let current = &mut self.service_health[index];
+                current.state = state;
+                current.detail = detail;
+            }
+
+            let total = self.service_health.len().max(1);
+            let healthy = self
+                .service_health
+                .iter()
+                .filter(|entry| entry.state == HealthState::Healthy)
+                .count();
+            self.progress = 80.0 + (healthy as f64 / total as f64) * 20.0;
+            let _ = self.redraw(terminal);
+
+            if healthy == self.service_health.len() {
+                self.add_log("✅ All services are healthy");
+                return Ok(());
             }
-        }
 
-        let up_status = up_child.wait().await?;
+            let failed: Vec<ServiceHealth> = self
+                .service_health
+                .iter()
+                .filter(|entry| entry.state.is_failed())
+                .cloned()
+                .collect();
+            if !failed.is_empty() {
+                self.attach_container_logs(&failed).await;
+                let names: Vec<&str> = failed.iter().map(|entry| entry.service.as_str()).collect();
+                return Err(eyre!("{} failed to start", names.join(", ")));
+            }
 
-        if up_status.success() {
-            self.add_log("✅ All services started successfully!");
-            self.progress = 100.0;
-            Ok(())
-        } else {
-            Err(color_eyre::eyre::eyre!("Docker Compose up failed"))
+            if started.elapsed() >= HEALTH_TIMEOUT {
+                let pending: Vec<ServiceHealth> = self
+                    .service_health
+                    .iter()
+                    .filter(|entry| entry.state != HealthState::Healthy)
+                    .cloned()
+                    .collect();
+                self.attach_container_logs(&pending).await;
+                let names: Vec<&str> = pending.iter().map(|entry| entry.service.as_str()).collect();
+                return Err(eyre!(
+                    "Timed out after {}s waiting for {} to become healthy",
+                    HEALTH_TIMEOUT.as_secs(),
+                    names.join(", ")
+                ));
+            }
+
+            tokio::time::sleep(HEALTH_POLL_INTERVAL).await;
+        }
+    }
+
+    async fn attach_container_logs(&mut self, services: &[ServiceHealth]) {
+        for entry in services {
+            self.add_log(&format!("📜 Last logs from {}:", entry.container));
+            match health::container_logs(&entry.container, FAILURE_LOG_LINES).await {
+                Ok(lines) => {
+                    for line in lines {
+                        self.add_log(&format!("   {}", line));
+                    }
+                }
+                Err(e) => self.add_log(&format!("❌ Could not read logs: {}", e)),
+            }
         }
     }
 
     fn process_log_line(&mut self, line: &str) {
-        let lower = line.to_lowercase();
+        if let Some(event) = progress::parse_line(line) {
+            self.apply_progress_event(event);
+        }
+    }
 
-        // Update progress during Docker build steps when available (e.g., "Step 1/4 : FROM busybox").
-        if let Some((step, total)) = Self::parse_build_step(line) {
-            if total > 0 {
-                let pct = 5.0 + (step as f64 / total as f64) * 45.0; // 5-50% during build phase
-                self.progress = self.progress.max(pct.min(50.0));
-            }
+    /// Folds one compose progress event into the per-service state, the
+    /// overall progress bar and the installation log.
+    fn apply_progress_event(&mut self, event: ProgressEvent) {
+        if self.state == AppState::Uninstalling && event.is_removal() {
+            self.completed_services = (self.completed_services + 1).min(self.total_services);
+            self.progress = (self.completed_services as f64 / self.total_services.max(1) as f64
+                * 100.0)
+                .min(95.0);
         }
 
-        if lower.contains("pulling") {
-            if let Some(service) = self.extract_service_name(line) {
+        match event {
+            ProgressEvent::ServicePull { service, done } => {
                 self.current_service = service.clone();
-                self.add_log(&format!("⬇️  Pulling image for {}...", service));
+                let detail = if done { "image ready" } else { "pulling image" };
+                self.set_service_detail(&service, detail);
             }
-        } else if lower.contains("pulled") {
-            self.add_log("✓ Image pulled");
-        } else if lower.contains("creating") {
-            if let Some(service) = self.extract_service_name(line) {
-                self.current_service = service.clone();
-                self.add_log(&format!("🔨 Creating container {}...", service));
+            ProgressEvent::Layer {
+                id,
+                stage,
+                current,
+                total,
+            } => {
+                self.pull_progress.update(&id, stage, current, total);
+                if let Some((index, count)) = self.pull_slot {
+                    let fraction = self.pull_progress.fraction();
+                    let pct = (index as f64 + fraction) / count.max(1) as f64 * 40.0;
+                    self.progress = self.progress.max(pct.min(40.0));
+                    let service = self.current_service.clone();
+                    self.set_service_detail(&service, &format!("pulling {:.0}%", fraction * 100.0));
+                }
             }
-        } else if lower.contains("created") {
-            self.add_log("✓ Container created");
-        } else if lower.contains("starting") {
-            if let Some(service) = self.extract_service_name(line) {
+            ProgressEvent::Container { name, status } => {
+                let service = self
+                    .project
+                    .service_for_line(&name)
+                    .unwrap_or(&name)
+                    .to_string();
                 self.current_service = service.clone();
+                self.apply_container_status(&service, &name, status);
+            }
+            ProgressEvent::Resource { kind, name, status } => {
+                self.add_log(&format!("ℹ️  {} {} {}", kind, name, status.to_lowercase()));
+            }
+            ProgressEvent::BuildStep { step, total } => {
+                if total > 0 {
+                    let pct = 40.0 + (step as f64 / total as f64) * 10.0; // 40-50% during build phase
+                    self.progress = self.progress.max(pct.min(50.0));
+                }
+            }
+            ProgressEvent::Error(message) => self.add_log(&format!("❌ {}", message)),
+            ProgressEvent::Message(message) => self.add_log(&format!("ℹ️  {}", message)),
+        }
+    }
+
+    fn apply_container_status(&mut self, service: &str, container: &str, status: ContainerStatus) {
+        match status {
+            ContainerStatus::Creating => {
+                self.add_log(&format!("🔨 Creating container {}...", container));
+                self.set_service_detail(service, "creating container");
+            }
+            ContainerStatus::Created => self.set_service_detail(service, "container created"),
+            ContainerStatus::Starting => {
                 self.add_log(&format!("▶️  Starting service {}...", service));
+                self.set_service_detail(service, "starting container");
             }
-        } else if lower.contains("started") {
-            self.completed_services += 1;
-            self.progress =
-                50.0 + (self.completed_services as f64 / self.total_services as f64) * 50.0;
-            self.add_log(&format!(
-                "✅ Service started ({}/{})",
-                self.completed_services, self.total_services
-            ));
-        } else if lower.contains("running") {
-            self.add_log("🟢 Service is running");
-        } else if lower.contains("error") || lower.contains("failed") {
-            self.add_log(&format!("❌ {}", line));
-        } else if !line.trim().is_empty() {
-            self.add_log(&format!("ℹ️  {}", line));
+            ContainerStatus::Started | ContainerStatus::Running => {
+                let first_start = self
+                    .service_health
+                    .iter_mut()
+                    .find(|entry| entry.service == service && entry.state == HealthState::Waiting)
+                    .map(|entry| {
+                        entry.state = HealthState::Starting;
+                        entry.detail = "container started".to_string();
+                    })
+                    .is_some();
+
+                if first_start {
+                    self.completed_services =
+                        (self.completed_services + 1).min(self.total_services);
+                    self.progress = self.progress.max(
+                        50.0 + (self.completed_services as f64 / self.total_services.max(1) as f64)
+                            * 30.0,
+                    );
+                    self.add_log(&format!(
+                        "✅ Service {} started ({}/{})",
+                        service, self.completed_services, self.total_services
+                    ));
+                }
+            }
+            ContainerStatus::Waiting => {
+                self.set_service_detail(service, "waiting for dependencies")
+            }
+            ContainerStatus::Healthy => self.set_service_detail(service, "healthy"),
+            ContainerStatus::Error(detail) => {
+                self.add_log(&format!("❌ Container {}: {}", container, detail));
+                self.set_service_detail(service, "error");
+            }
+            ContainerStatus::Other(text) => {
+                self.add_log(&format!("ℹ️  Container {} {}", container, text))
+            }
+            ContainerStatus::Stopping => {
+                self.add_log(&format!("⏹  Stopping container {}...", container))
+            }
+            ContainerStatus::Removed => {
+                self.add_log(&format!("🗑  Removed container {}", container))
+            }
+            ContainerStatus::Stopped | ContainerStatus::Removing => {}
         }
     }
 
-    fn parse_build_step(line: &str) -> Option<(u32, u32)> {
-        // Expected format prefix: "Step X/Y" or "Step X/Y :"
-        let trimmed = line.trim();
-        if !trimmed.starts_with("Step ") {
-            return None;
+    fn set_service_detail(&mut self, service: &str, detail: &str) {
+        if let Some(entry) = self
+            .service_health
+            .iter_mut()
+            .find(|entry| entry.service == service)
+        {
+            entry.detail = detail.to_string();
         }
+    }
 
-        let after = trimmed.strip_prefix("Step ")?;
-        let mut parts = after.split_whitespace();
-        let frac = parts.next()?; // e.g., "1/4"
-        let mut nums = frac.split('/');
-        let step: u32 = nums.next()?.parse().ok()?;
-        let total: u32 = nums.next()?.parse().ok()?;
-        Some((step, total))
+    /// Creates a `Waiting` entry for every compose service. Keycloak also has
+    /// to answer its readiness endpoint before it counts as healthy.
+    fn init_service_health(&mut self) {
+        let env = EnvFile::load(&Self::env_path()).unwrap_or_default();
+        let ready_url = format!("{}/health/ready", configure::local_url(&env));
+        self.service_health = self
+            .project
+            .service_names()
+            .iter()
+            .map(|service| {
+                let url = (service == KEYCLOAK_SERVICE).then(|| ready_url.clone());
+                ServiceHealth::new(service, &self.project.container_name(service), url)
+            })
+            .collect();
     }
 
+    /// Podman always builds with Buildah, so BuildKit only matters for Docker.
     async fn buildkit_available(&self) -> Result<bool> {
+        if Runtime::current() == Runtime::Podman {
+            return Ok(false);
+        }
         let status = Command::new("docker")
             .args(["buildx", "version"])
             .stdout(Stdio::null())
@@ -1178,23 +2139,18 @@ impl App {
         Ok(status)
     }
 
-    fn extract_service_name(&self, line: &str) -> Option<String> {
-        let services = [
-            "analytics-service",
-            "qdrant",
-            "northwind-db",
-            "analytics-ui",
-        ];
-
-        for service in services {
-            if line.to_lowercase().contains(service) {
-                return Some(service.to_string());
+    fn add_log(&mut self, message: &str) {
+        match self.echo {
+            Some(OutputFormat::Plain) => println!("{}", message),
+            Some(OutputFormat::Json) => {
+                println!(
+                    "{}",
+                    serde_json::json!({ "event": "log", "message": message })
+                )
             }
+            None => {}
         }
-        None
-    }
 
-    fn add_log(&mut self, message: &str) {
         self.logs.push(message.to_string());
 
         if self.logs.len() > 100 {
@@ -1208,6 +2164,7 @@ impl App {
                 let view = RegistrySetupView {
                     form: &self.registry_form,
                     status: self.registry_status.as_deref(),
+                    token: self.token_check.as_ref(),
                 };
                 ui::render_registry_setup(frame, &view);
             }
@@ -1216,9 +2173,20 @@ impl App {
                 let view = ConfirmationView {
                     menu_selection: &self.menu_selection,
                     menu_options: &menu_options,
+                    notice: self.token_notice.as_deref(),
+                    deployed: self.deployed,
                 };
                 ui::render_confirmation(frame, &view);
             }
+            AppState::Configure => {
+                let env_path = Self::env_path();
+                let env_path = env_path.display().to_string();
+                let view = ConfigureView {
+                    form: &self.config_form,
+                    env_path: &env_path,
+                };
+                ui::render_configure(frame, &view);
+            }
             AppState::UpdateList => {
                 let view = UpdateListView {
                     updates: &self.update_infos,
@@ -1227,6 +2195,7 @@ impl App {
                     logs: &self.logs,
                     pulling: false,
                     progress: None,
+                    tag_picker: self.tag_picker.as_ref(),
                 };
                 ui::render_update_list(frame, &view);
             }
@@ -1238,21 +2207,138 @@ impl App {
                     logs: &self.logs,
                     pulling: true,
                     progress: Some(self.progress),
+                    tag_picker: None,
                 };
                 ui::render_update_list(frame, &view);
             }
             AppState::Installing => {
                 let view = InstallingView {
+                    title: "🔄 Installing Identity... Please wait",
+                    help: "Press Ctrl+C to cancel",
+                    progress: self.progress,
+                    current_service: &self.current_service,
+                    completed_services: self.completed_services,
+                    total_services: self.total_services,
+                    services: &self.service_health,
+                    logs: &self.logs,
+                };
+                ui::render_installing(frame, &view);
+            }
+            AppState::Dashboard => {
+                let view = DashboardView {
+                    services: &self.services,
+                    selected_index: self.dashboard_selection,
+                    message: self.dashboard_message.as_deref(),
+                };
+                ui::render_dashboard(frame, &view);
+            }
+            AppState::LogViewer => {
+                if let Some(viewer) = &self.log_viewer {
+                    ui::render_log_viewer(frame, &LogViewerView { viewer });
+                }
+            }
+            AppState::ManagingService => {
+                let title = format!(
+                    "⚙️  {} {}... Please wait",
+                    self.service_action.map_or("Updating", ServiceAction::verb),
+                    self.current_service
+                );
+                let view = InstallingView {
+                    title: &title,
+                    help: "Press Ctrl+C to cancel",
+                    progress: self.progress,
+                    current_service: &self.current_service,
+                    completed_services: self.completed_services,
+                    total_services: self.total_services,
+                    services: &self.service_health,
+                    logs: &self.logs,
+                };
+                ui::render_installing(frame, &view);
+            }
+            AppState::Uninstall => {
+                let view = UninstallView {
+                    plan: &self.uninstall_plan,
+                    selected: self.uninstall_selection,
+                };
+                ui::render_uninstall(frame, &view);
+            }
+            AppState::Backups => {
+                let backup_dir = backup::backup_dir().display().to_string();
+                let view = BackupsView {
+                    backups: &self.backups,
+                    selected_index: self.backup_selection,
+                    message: self.backup_message.as_deref(),
+                    backup_dir: &backup_dir,
+                };
+                ui::render_backups(frame, &view);
+            }
+            AppState::Preflight => {
+                let view = PreflightView {
+                    report: self.preflight.as_ref(),
+                };
+                ui::render_preflight(frame, &view);
+            }
+            AppState::PgUpgrade => {
+                if let Some(plan) = &self.pg_upgrade {
+                    let view = PgUpgradeView {
+                        plan,
+                        message: self.pg_upgrade_message.as_deref(),
+                    };
+                    ui::render_pg_upgrade(frame, &view);
+                }
+            }
+            AppState::BackingUp | AppState::Restoring | AppState::PgUpgrading => {
+                let title = match self.state {
+                    AppState::BackingUp => "💾 Backing up the identity database... Please wait",
+                    AppState::Restoring => "♻️  Restoring the identity database... Please wait",
+                    _ => "🐘 Upgrading PostgreSQL... Please wait",
+                };
+                let view = InstallingView {
+                    title,
+                    help: "Press Ctrl+C to cancel",
+                    progress: self.progress,
+                    current_service: &self.current_service,
+                    completed_services: self.completed_services,
+                    total_services: self.total_services,
+                    services: &self.service_health,
+                    logs: &self.logs,
+                };
+                ui::render_installing(frame, &view);
+            }
+            AppState::Uninstalling | AppState::Uninstalled => {
+                let (title, help) = if self.state == AppState::Uninstalling {
+                    (
+                        "🗑  Uninstalling Identity... Please wait",
+                        "Press Ctrl+C to cancel",
+                    )
+                } else {
+                    (
+                        "✅ Identity stack removed",
+                        "Press Enter to return to the menu",
+                    )
+                };
+                let view = InstallingView {
+                    title,
+                    help,
                     progress: self.progress,
                     current_service: &self.current_service,
                     completed_services: self.completed_services,
                     total_services: self.total_services,
+                    services: &self.service_health,
                     logs: &self.logs,
                 };
                 ui::render_installing(frame, &view);
             }
             AppState::Success => {
-                let view = SuccessView { logs: &self.logs };
+                let env_path = Self::env_path();
+                let console_url =
+                    configure::console_url(&EnvFile::load(&env_path).unwrap_or_default());
+                let view = SuccessView {
+                    logs: &self.logs,
+                    console_url: &console_url,
+                    credentials: self.generated_credentials.as_ref(),
+                    env_path: &env_path,
+                };
                 ui::render_success(frame, &view);
             }
             AppState::Error(err) => {
@@ -0,0 +1,683 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use chrono::Local;
+use color_eyre::{Result, eyre::eyre};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+
+use super::backup::{
+    DATABASE_SERVICE, DatabaseTarget, backup_file_name, create_backup_dir, dump_database,
+    restore_database, verify_backup,
+};
+use super::compose::{ComposeProject, image_repository, image_tag};
+use super::rollout::write_env_pin;
+use super::surface::Surface;
+use super::{App, AppState, KEYCLOAK_SERVICE};
+use crate::docker::DockerClient;
+use crate::runtime::Runtime;
+use crate::utils;
+
+/// How many majors past the data directory's version the assistant offers.
+const MAX_MAJOR_STEP: u32 = 3;
+const READY_ATTEMPTS: usize = 60;
+const READY_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Exact row count of every user table in one round trip, as `table|count`.
+const ROW_COUNT_QUERY: &str = "SELECT table_schema || '.' || table_name, \
+    (xpath('/row/c/text()', query_to_xml(format('SELECT count(*) AS c FROM %I.%I', \
+    table_schema, table_name), false, true, '')))[1]::text \
+    FROM information_schema.tables \
+    WHERE table_type = 'BASE TABLE' \
+    AND table_schema NOT IN ('pg_catalog', 'information_schema') \
+    ORDER BY 1";
+
+/// A PostgreSQL major upgrade: the cluster on `old_volume` is dumped with the
+/// image matching its `PG_VERSION` and restored into a fresh volume created
+/// by the target image. The old volume is only removed when asked to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgUpgradePlan {
+    /// Major version recorded in the data directory's `PG_VERSION`.
+    pub data_version: u32,
+    /// Image the compose file currently resolves for the database service.
+    pub current_image: String,
+    pub target_major: u32,
+    pub old_volume: String,
+    /// Variables the compose file reads the image and volume name from.
+    pub image_key: Option<String>,
+    pub volume_key: Option<String>,
+    /// Where the volume is mounted inside the database container.
+    pub mount_path: String,
+    pub completed: bool,
+    pub delete_pending: bool,
+}
+
+impl PgUpgradePlan {
+    /// Image of the data directory's own major, used to dump it.
+    pub fn source_image(&self) -> String {
+        image_for_major(&self.current_image, self.data_version)
+    }
+
+    pub fn target_image(&self) -> String {
+        image_for_major(&self.current_image, self.target_major)
+    }
+
+    pub fn new_volume(&self) -> String {
+        upgrade_volume_name(&self.old_volume, self.target_major)
+    }
+
+    /// Steps the target major within the offered range.
+    pub fn change_target(&mut self, up: bool) {
+        let min = self.data_version + 1;
+        let max = self.data_version + MAX_MAJOR_STEP;
+        self.target_major = if up {
+            (self.target_major + 1).min(max)
+        } else {
+            self.target_major.saturating_sub(1).max(min)
+        };
+    }
+
+    /// Why the upgrade cannot switch the stack over, if it cannot.
+    pub fn blocker(&self) -> Option<String> {
+        match (&self.image_key, &self.volume_key) {
+            (Some(_), Some(_)) => None,
+            (None, _) => Some(format!(
+                "The {} image in docker-compose.yaml is not read from a variable, e.g. image: ${{POSTGRES_IMAGE:-{}}}",
+                DATABASE_SERVICE, self.current_image
+            )),
+            (_, None) => Some(format!(
+                "The database volume's name is not read from a variable, e.g. name: ${{POSTGRES_VOLUME:-{}}}",
+                self.old_volume
+            )),
+        }
+    }
+}
+
+/// Leading major version of a tag: `16-alpine` and `16.4` give 16.
+pub fn tag_major(tag: &str) -> Option<u32> {
+    let digits: String = tag.chars().take_while(char::is_ascii_digit).collect();
+    digits.parse().ok()
+}
+
+/// Swaps the version in `reference`'s tag for `major`, keeping the variant:
+/// `postgres:16.4-alpine` becomes `postgres:17-alpine`.
+pub fn image_for_major(reference: &str, major: u32) -> String {
+    let repository = image_repository(reference);
+    let tag = image_tag(reference).unwrap_or_default();
+    let variant = tag.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.');
+    let variant = if variant == tag && !variant.starts_with('-') {
+        ""
+    } else {
+        variant
+    };
+    format!("{}:{}{}", repository, major, variant)
+}
+
+/// `identity_identity_pgdata` becomes `identity_identity_pgdata_pg17`; an
+/// earlier `_pgNN` suffix is replaced rather than stacked.
+pub fn upgrade_volume_name(old: &str, major: u32) -> String {
+    let base = match old.rsplit_once("_pg") {
+        Some((base, version))
+            if !version.is_empty() && version.chars().all(|c| c.is_ascii_digit()) =>
+        {
+            base
+        }
+        _ => old,
+    };
+    format!("{}_pg{}", base, major)
+}
+
+fn parse_row_counts(output: &str) -> BTreeMap<String, u64> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (table, count) = line.rsplit_once('|')?;
+            Some((table.to_string(), count.trim().parse().ok()?))
+        })
+        .collect()
+}
+
+/// Tables whose row count changed between the old and the new cluster.
+fn row_count_differences(
+    before: &BTreeMap<String, u64>,
+    after: &BTreeMap<String, u64>,
+) -> Vec<String> {
+    let mut differences = Vec::new();
+    for (table, count) in before {
+        match after.get(table) {
+            Some(restored) if restored == count => {}
+            Some(restored) => differences.push(format!("{}: {} → {}", table, count, restored)),
+            None => differences.push(format!("{}: missing after restore", table)),
+        }
+    }
+    differences
+}
+
+async fn docker(args: &[&str]) -> Result<String> {
+    let runtime = Runtime::current();
+    let output = runtime.command().args(args).output().await?;
+    if !output.status.success() {
+        return Err(eyre!(
+            "{} {} failed: {}",
+            runtime.binary(),
+            args.first().copied().unwrap_or_default(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Reads `PG_VERSION` from the volume without starting PostgreSQL.
+async fn read_data_version(image: &str, volume: &str, mount_path: &str) -> Result<u32> {
+    let mount = format!("{}:{}:ro", volume, mount_path);
+    let file = format!("{}/PG_VERSION", mount_path.trim_end_matches('/'));
+    let version = docker(&[
+        "run",
+        "--rm",
+        "-v",
+        &mount,
+        "--entrypoint",
+        "cat",
+        image,
+        &file,
+    ])
+    .await
+    .map_err(|e| eyre!("Cannot read PG_VERSION from {}: {}", volume, e))?;
+    version
+        .parse()
+        .map_err(|_| eyre!("Unexpected PG_VERSION in {}: {}", volume, version))
+}
+
+async fn row_counts(target: &DatabaseTarget) -> Result<BTreeMap<String, u64>> {
+    Ok(parse_row_counts(&target.psql(ROW_COUNT_QUERY).await?))
+}
+
+/// Waits until PostgreSQL accepts TCP connections. Unlike the socket check of
+/// the compose healthcheck, this is not fooled by the temporary server the
+/// image runs while initialising a new data directory.
+async fn wait_for_tcp_ready(target: &DatabaseTarget) -> Result<()> {
+    let docker = DockerClient::from_env()?;
+    for _ in 0..READY_ATTEMPTS {
+        let ready = docker
+            .exec(
+                &target.container,
+                &[
+                    "pg_isready",
+                    "-h",
+                    "127.0.0.1",
+                    "-U",
+                    &target.user,
+                    "-d",
+                    &target.database,
+                ],
+            )
+            .await;
+        if ready.is_ok_and(|output| output.exit_code == 0) {
+            return Ok(());
+        }
+        tokio::time::sleep(READY_INTERVAL).await;
+    }
+    Err(eyre!(
+        "{} did not accept connections in time",
+        target.container
+    ))
+}
+
+pub(super) enum PgUpgradeAction {
+    Start,
+    DeleteOldVolume,
+    Back,
+}
+
+impl App {
+    /// Inspects the database volume and proposes an upgrade to the next major.
+    pub(super) async fn prepare_pg_upgrade(&mut self) -> Result<()> {
+        let compose_cmd = self.load_project().await?;
+        let raw = ComposeProject::load_uninterpolated(&compose_cmd, &utils::project_root()).await?;
+
+        let (volume_key, old_volume, mount_path) =
+            self.project.named_mount(DATABASE_SERVICE).ok_or_else(|| {
+                eyre!(
+                    "{} does not store its data in a named volume",
+                    DATABASE_SERVICE
+                )
+            })?;
+        let current_image = self
+            .project
+            .image(DATABASE_SERVICE)
+            .ok_or_else(|| eyre!("{} has no image", DATABASE_SERVICE))?
+            .to_string();
+        let data_version = read_data_version(&current_image, &old_volume, &mount_path).await?;
+
+        let plan = PgUpgradePlan {
+            data_version,
+            current_image,
+            target_major: data_version + 1,
+            old_volume,
+            image_key: raw.image_variables().remove(DATABASE_SERVICE),
+            volume_key: raw.volume_variable(&volume_key).map(str::to_string),
+            mount_path,
+            completed: false,
+            delete_pending: false,
+        };
+
+        let configured = image_tag(&plan.current_image).and_then(tag_major);
+        self.pg_upgrade_message = match (plan.blocker(), configured) {
+            (Some(blocker), _) => Some(blocker),
+            (None, Some(major)) if major != data_version => Some(format!(
+                "{} is configured, but the data directory was created by PostgreSQL {}. Upgrade it to match.",
+                plan.current_image, data_version
+            )),
+            _ => None,
+        };
+        self.pg_upgrade = Some(plan);
+        Ok(())
+    }
+
+    pub(super) fn handle_pg_upgrade_events(&mut self) -> Result<Option<PgUpgradeAction>> {
+        if event::poll(std::time::Duration::from_millis(100))?
+            && let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            let Some(plan) = self.pg_upgrade.as_mut() else {
+                return Ok(Some(PgUpgradeAction::Back));
+            };
+
+            if plan.delete_pending {
+                plan.delete_pending = false;
+                return Ok(match key.code {
+                    KeyCode::Char('y') | KeyCode::Char('Y') => {
+                        Some(PgUpgradeAction::DeleteOldVolume)
+                    }
+                    _ => {
+                        self.pg_upgrade_message = Some(format!("Kept {}", plan.old_volume));
+                        None
+                    }
+                });
+            }
+
+            match key.code {
+                KeyCode::Left | KeyCode::Down if !plan.completed => plan.change_target(false),
+                KeyCode::Right | KeyCode::Up if !plan.completed => plan.change_target(true),
+                KeyCode::Enter if !plan.completed => {
+                    if let Some(blocker) = plan.blocker() {
+                        self.pg_upgrade_message = Some(blocker);
+                    } else {
+                        return Ok(Some(PgUpgradeAction::Start));
+                    }
+                }
+                KeyCode::Char('d') | KeyCode::Char('D') if plan.completed => {
+                    plan.delete_pending = true;
+                    self.pg_upgrade_message = Some(format!(
+                        "Delete {} for good? Only the new volume will hold the database. Press Y to confirm.",
+                        plan.old_volume
+                    ));
+                }
+                KeyCode::Esc => return Ok(Some(PgUpgradeAction::Back)),
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    self.running = false;
+                }
+                _ => {}
+            }
+        }
+
+        Ok(None)
+    }
+
+    pub(super) async fn run_pg_upgrade(&mut self, terminal: &mut impl Surface) {
+        let Some(plan) = self.pg_upgrade.clone() else {
+            return;
+        };
+
+        self.state = AppState::PgUpgrading;
+        self.logs.clear();
+        self.progress = 0.0;
+        self.service_health.clear();
+
+        match self.upgrade_postgres(terminal, &plan).await {
+            Ok(backup) => {
+                self.pg_upgrade_message = Some(format!(
+                    "Upgraded to PostgreSQL {} on {}. {} is kept (and {} as a dump): press D to delete the old volume once you are satisfied.",
+                    plan.target_major,
+                    plan.new_volume(),
+                    plan.old_volume,
+                    backup.display()
+                ));
+                if let Some(plan) = self.pg_upgrade.as_mut() {
+                    plan.completed = true;
+                }
+                self.state = AppState::PgUpgrade;
+            }
+            Err(e) => {
+                self.state = AppState::Error(format!("PostgreSQL upgrade failed: {}", e));
+            }
+        }
+    }
+
+    pub(super) async fn delete_old_pg_volume(&mut self) {
+        let Some(plan) = self.pg_upgrade.clone() else {
+            return;
+        };
+        self.pg_upgrade_message = Some(match docker(&["volume", "rm", &plan.old_volume]).await {
+            Ok(_) => format!("Deleted {}", plan.old_volume),
+            Err(e) => format!("Failed to delete {}: {}", plan.old_volume, e),
+        });
+    }
+
+    /// Runs the upgrade and returns the dump it was restored from. Any failure
+    /// after the switch puts the old image and volume back in `.env`.
+    async fn upgrade_postgres(
+        &mut self,
+        terminal: &mut impl Surface,
+        plan: &PgUpgradePlan,
+    ) -> Result<PathBuf> {
+        let (Some(image_key), Some(volume_key)) = (&plan.image_key, &plan.volume_key) else {
+            return Err(eyre!(plan.blocker().unwrap_or_default()));
+        };
+        let compose_cmd = self.load_project().await?;
+        let target = DatabaseTarget::from_project(&self.project);
+        let target_image = plan.target_image();
+        let new_volume = plan.new_volume();
+
+        self.current_service = DATABASE_SERVICE.to_string();
+        self.progress = 5.0;
+        self.add_log_and_redraw(terminal, &format!("⬇️  Pulling {}...", target_image));
+        self.pull_image(terminal, DATABASE_SERVICE, &target_image)
+            .await?;
+
+        self.progress = 15.0;
+        self.add_log_and_redraw(terminal, "⏹  Stopping the stack...");
+        if !self
+            .run_compose_step(
+                terminal,
+                &compose_cmd,
+                &["stop", KEYCLOAK_SERVICE, DATABASE_SERVICE],
+                &[],
+            )
+            .await?
+        {
+            return Err(eyre!("Failed to stop the stack"));
+        }
+
+        let dumped = self.dump_old_cluster(terminal, plan, &target).await;
+        let (backup, before) = match dumped {
+            Ok(dumped) => dumped,
+            Err(e) => {
+                self.start_database_stack(terminal, &compose_cmd).await;
+                return Err(e);
+            }
+        };
+
+        self.progress = 50.0;
+        let previous_image = write_env_pin(image_key, Some(&target_image))?;
+        let previous_volume = write_env_pin(volume_key, Some(&new_volume))?;
+        self.add_log_and_redraw(
+            terminal,
+            &format!(
+                "📌 Pinned {}={} and {}={} in .env",
+                image_key, target_image, volume_key, new_volume
+            ),
+        );
+
+        let upgraded = async {
+            self.restore_new_cluster(terminal, &target, &backup, &before)
+                .await?;
+            self.progress = 80.0;
+            self.init_service_health();
+            self.wait_for_health(terminal).await
+        }
+        .await;
+        if let Err(e) = upgraded {
+            self.add_log_and_redraw(
+                terminal,
+                &format!(
+                    "↩️  Upgrade failed ({}); switching back to {}",
+                    e, plan.old_volume
+                ),
+            );
+            let failures = self
+                .revert_pg_switch(
+                    terminal,
+                    [
+                        (image_key.as_str(), previous_image.as_deref()),
+                        (volume_key.as_str(), previous_volume.as_deref()),
+                    ],
+                )
+                .await;
+            if !failures.is_empty() {
+                return Err(eyre!(
+                    "{}; switching back to {} also failed: {}. Set {}={} and {}={} in .env and restart the stack",
+                    e,
+                    plan.old_volume,
+                    failures.join("; "),
+                    image_key,
+                    plan.current_image,
+                    volume_key,
+                    plan.old_volume
+                ));
+            }
+            return Err(eyre!(
+                "{}; back on {} with {}. {} was left for inspection",
+                e,
+                plan.old_volume,
+                plan.current_image,
+                new_volume
+            ));
+        }
+
+        self.add_log_and_redraw(
+            terminal,
+            &format!(
+                "✅ PostgreSQL {} is serving the identity database",
+                plan.target_major
+            ),
+        );
+        Ok(backup)
+    }
+
+    /// Puts the previous `.env` pins back and restarts the database on them.
+    /// Every step is attempted; the ones that failed are logged and returned.
+    async fn revert_pg_switch(
+        &mut self,
+        terminal: &mut impl Surface,
+        pins: [(&str, Option<&str>); 2],
+    ) -> Vec<String> {
+        let mut failures = Vec::new();
+        for (key, previous) in pins {
+            if let Err(e) = write_env_pin(key, previous) {
+                failures.push(format!("restoring {}: {}", key, e));
+            }
+        }
+        match self.load_project().await {
+            Ok(compose_cmd) => {
+                if let Err(e) = self
+                    .recreate_service(terminal, &compose_cmd, DATABASE_SERVICE)
+                    .await
+                {
+                    failures.push(format!("recreating {}: {}", DATABASE_SERVICE, e));
+                }
+                self.start_database_stack(terminal, &compose_cmd).await;
+            }
+            Err(e) => failures.push(format!("loading the compose project: {}", e)),
+        }
+        for failure in &failures {
+            self.add_log_and_redraw(terminal, &format!("❌ Rollback: {}", failure));
+        }
+        failures
+    }
+
+    /// Starts the old cluster in a throwaway container of its own major,
+    /// counts rows and dumps it. The container is removed either way.
+    async fn dump_old_cluster(
+        &mut self,
+        terminal: &mut impl Surface,
+        plan: &PgUpgradePlan,
+        target: &DatabaseTarget,
+    ) -> Result<(PathBuf, BTreeMap<String, u64>)> {
+        let source = DatabaseTarget {
+            container: format!("{}-upgrade-source", target.container),
+            ..target.clone()
+        };
+        let source_image = plan.source_image();
+        let mount = format!("{}:{}", plan.old_volume, plan.mount_path);
+
+        self.progress = 20.0;
+        self.add_log_and_redraw(
+            terminal,
+            &format!(
+                "🐘 Starting {} on {} to dump it...",
+                source_image, plan.old_volume
+            ),
+        );
+        let _ = docker(&["rm", "-f", &source.container]).await;
+        docker(&[
+            "run",
+            "-d",
+            "--name",
+            &source.container,
+            "-v",
+            &mount,
+            &source_image,
+        ])
+        .await?;
+
+        let dumped = async {
+            wait_for_tcp_ready(&source).await?;
+            let counts = row_counts(&source).await?;
+            self.add_log_and_redraw(
+                terminal,
+                &format!(
+                    "📊 Counted {} rows in {} tables",
+                    counts.values().sum::<u64>(),
+                    counts.len()
+                ),
+            );
+
+            let dir = create_backup_dir()?;
+            let path = dir.join(backup_file_name(Local::now().naive_local()));
+            self.progress = 30.0;
+            self.add_log_and_redraw(terminal, &format!("💾 Dumping to {}...", path.display()));
+            dump_database(&source, &path).await?;
+            let entries = verify_backup(&source, &path).await?;
+            self.add_log_and_redraw(terminal, &format!("✅ Dump verified ({} objects)", entries));
+            Ok((path, counts))
+        }
+        .await;
+
+        let _ = docker(&["rm", "-f", &source.container]).await;
+        dumped
+    }
+
+    /// Recreates the database service on the new image and volume, restores
+    /// the dump and checks every table has the same number of rows.
+    async fn restore_new_cluster(
+        &mut self,
+        terminal: &mut impl Surface,
+        target: &DatabaseTarget,
+        backup: &std::path::Path,
+        before: &BTreeMap<String, u64>,
+    ) -> Result<()> {
+        let compose_cmd = self.load_project().await?;
+        self.progress = 55.0;
+        self.recreate_service(terminal, &compose_cmd, DATABASE_SERVICE)
+            .await?;
+        wait_for_tcp_ready(target).await?;
+
+        self.progress = 65.0;
+        self.current_service = DATABASE_SERVICE.to_string();
+        self.add_log_and_redraw(terminal, "♻️  Restoring the dump into the new cluster...");
+        restore_database(target, backup).await?;
+
+        self.progress = 75.0;
+        let after = row_counts(target).await?;
+        let differences = row_count_differences(before, &after);
+        if !differences.is_empty() {
+            return Err(eyre!(
+                "row counts differ after restore: {}",
+                differences.join(", ")
+            ));
+        }
+        self.add_log_and_redraw(
+            terminal,
+            &format!("✅ Row counts match in all {} tables", before.len()),
+        );
+
+        self.add_log_and_redraw(terminal, &format!("▶️  Starting {}...", KEYCLOAK_SERVICE));
+        if !self
+            .run_compose_step(terminal, &compose_cmd, &["start", KEYCLOAK_SERVICE], &[])
+            .await?
+        {
+            return Err(eyre!("Failed to start {}", KEYCLOAK_SERVICE));
+        }
+        Ok(())
+    }
+
+    /// Best-effort restart after an aborted upgrade; failures are only logged.
+    async fn start_database_stack(&mut self, terminal: &mut impl Surface, compose_cmd: &[String]) {
+        let started = self
+            .run_compose_step(
+                terminal,
+                compose_cmd,
+                &["start", DATABASE_SERVICE, KEYCLOAK_SERVICE],
+                &[],
+            )
+            .await;
+        if !matches!(started, Ok(true)) {
+            self.add_log_and_redraw(
+                terminal,
+                "❌ Failed to restart the stack; start it manually",
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upgrade_names() {
+        assert_eq!(
+            image_for_major("postgres:16-alpine", 17),
+            "postgres:17-alpine"
+        );
+        assert_eq!(
+            image_for_major("postgres:16.4-bookworm", 17),
+            "postgres:17-bookworm"
+        );
+        assert_eq!(image_for_major("postgres:16", 18), "postgres:18");
+        assert_eq!(image_for_major("postgres", 17), "postgres:17");
+        assert_eq!(
+            image_for_major("registry.local:5000/postgres:16-alpine", 17),
+            "registry.local:5000/postgres:17-alpine"
+        );
+        assert_eq!(tag_major("16-alpine"), Some(16));
+        assert_eq!(tag_major("latest"), None);
+
+        assert_eq!(
+            upgrade_volume_name("identity_identity_pgdata", 17),
+            "identity_identity_pgdata_pg17"
+        );
+        assert_eq!(
+            upgrade_volume_name("identity_identity_pgdata_pg17", 18),
+            "identity_identity_pgdata_pg18"
+        );
+    }
+
+    #[test]
+    fn test_row_count_differences() {
+        let before =
+            parse_row_counts("public.realm|2\npublic.user_entity|14\npublic.event_entity|0\n");
+        let mut after = before.clone();
+        assert!(row_count_differences(&before, &after).is_empty());
+
+        after.insert("public.user_entity".to_string(), 13);
+        after.remove("public.event_entity");
+        assert_eq!(
+            row_count_differences(&before, &after),
+            vec![
+                "public.event_entity: missing after restore",
+                "public.user_entity: 14 → 13"
+            ]
+        );
+    }
+}
@@ -0,0 +1,743 @@
+use std::net::TcpListener;
+use std::path::Path;
+
+use color_eyre::Result;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use tokio::process::Command;
+
+use super::compose::{self, ComposeProject};
+use super::configure::{DEFAULT_HTTP_PORT, DEFAULT_PG_PORT, HTTP_PORT_KEY, PG_PORT_KEY};
+use super::{App, AppState};
+use crate::docker::DockerClient;
+use crate::env_file::EnvFile;
+use crate::runtime::Runtime;
+use crate::utils;
+
+/// Oldest Docker Engine the stack is tested with.
+const MIN_DOCKER_MAJOR: u64 = 20;
+/// Podman 4 brought `podman compose` and a Docker-compatible health API.
+const MIN_PODMAN_MAJOR: u64 = 4;
+/// Compose learned `pull_policy` in 1.28; v1 is end of life either way.
+const PULL_POLICY_COMPOSE: (u64, u64) = (1, 28);
+/// podman-compose 1.0 is the release RHEL's EPEL ships (1.0.6).
+const MIN_PODMAN_COMPOSE: (u64, u64) = (1, 0);
+const GIB: u64 = 1024 * 1024 * 1024;
+const DISK_FAIL_BYTES: u64 = 2 * GIB;
+const DISK_WARN_BYTES: u64 = 5 * GIB;
+const MEMORY_WARN_BYTES: u64 = 2 * GIB;
+/// Used when the compose file cannot be read yet (first install).
+const DEFAULT_PROJECT: &str = "identity";
+const DEFAULT_CONTAINERS: [&str; 2] = ["identity", "identity-db"];
+const UNPRIVILEGED_PORT_START: &str = "/proc/sys/net/ipv4/ip_unprivileged_port_start";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// One row of the preflight page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreflightCheck {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+    /// What to do about a warning or failure.
+    pub hint: Option<String>,
+}
+
+impl PreflightCheck {
+    fn pass(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Pass,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+
+    fn warn(name: &'static str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Warn,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Fail,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PreflightReport {
+    pub checks: Vec<PreflightCheck>,
+}
+
+impl PreflightReport {
+    pub fn count(&self, status: CheckStatus) -> usize {
+        self.checks
+            .iter()
+            .filter(|check| check.status == status)
+            .count()
+    }
+
+    /// Installation may go ahead when nothing failed; warnings are allowed.
+    pub fn passed(&self) -> bool {
+        self.count(CheckStatus::Fail) == 0
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &PreflightCheck> {
+        self.checks
+            .iter()
+            .filter(|check| check.status == CheckStatus::Fail)
+    }
+}
+
+pub enum PreflightAction {
+    Continue,
+    Rerun,
+    Back,
+}
+
+/// `major.minor` of a version string such as `v2.29.7` or `24.0.7-ce`.
+fn parse_version(text: &str) -> Option<(u64, u64)> {
+    let text = text.trim().trim_start_matches('v');
+    let mut parts = text.split(|c: char| !c.is_ascii_digit());
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts
+        .next()
+        .and_then(|minor| minor.parse().ok())
+        .unwrap_or(0);
+    Some((major, minor))
+}
+
+/// `info` and `version` templates that print the storage root and the
+/// engine version, which the two CLIs name differently.
+fn info_formats(runtime: Runtime) -> (&'static str, &'static str) {
+    match runtime {
+        Runtime::Docker => ("{{.DockerRootDir}}", "{{.Server.Version}}"),
+        Runtime::Podman => ("{{.Store.GraphRoot}}", "{{.Client.Version}}"),
+    }
+}
+
+fn daemon_check_name(runtime: Runtime) -> &'static str {
+    match runtime {
+        Runtime::Docker => "Docker daemon",
+        Runtime::Podman => "Podman",
+    }
+}
+
+/// Explains why `docker info` (or `podman info`) failed.
+fn daemon_failure(runtime: Runtime, stderr: &str) -> PreflightCheck {
+    let name = daemon_check_name(runtime);
+    let stderr = stderr.trim();
+    match runtime {
+        Runtime::Docker if stderr.contains("permission denied") => PreflightCheck::fail(
+            name,
+            "Permission denied on the Docker socket",
+            "Add your user to the docker group (sudo usermod -aG docker $USER) and log in again",
+        ),
+        Runtime::Podman if stderr.contains("newuidmap") || stderr.contains("subuid") => {
+            PreflightCheck::fail(
+                name,
+                "Rootless Podman has no subordinate UID/GID range for this user",
+                "Add ranges to /etc/subuid and /etc/subgid (sudo usermod --add-subuids 100000-165535 --add-subgids 100000-165535 $USER), then run `podman system migrate`",
+            )
+        }
+        _ => {
+            let detail = stderr.lines().last().unwrap_or("info failed");
+            PreflightCheck::fail(
+                name,
+                detail.to_string(),
+                format!(
+                    "{} and check `{} info`",
+                    runtime.start_hint(),
+                    runtime.binary()
+                ),
+            )
+        }
+    }
+}
+
+fn version_check(runtime: Runtime, version: &str) -> PreflightCheck {
+    let (name, product, min_major, upgrade) = match runtime {
+        Runtime::Docker => (
+            "Docker version",
+            "Docker Engine",
+            MIN_DOCKER_MAJOR,
+            "Upgrade Docker Engine from docs.docker.com/engine/install",
+        ),
+        Runtime::Podman => (
+            "Podman version",
+            "Podman",
+            MIN_PODMAN_MAJOR,
+            "Upgrade Podman from your distribution (e.g. sudo dnf upgrade podman)",
+        ),
+    };
+    match parse_version(version) {
+        Some((major, _)) if major >= min_major => {
+            PreflightCheck::pass(name, format!("{} {}", product, version))
+        }
+        Some(_) => PreflightCheck::warn(
+            name,
+            format!("{} {} is older than {}", product, version, min_major),
+            upgrade,
+        ),
+        None => PreflightCheck::warn(
+            name,
+            format!("Unrecognised version '{}'", version),
+            format!("Check `{} version`", runtime.binary()),
+        ),
+    }
+}
+
+fn compose_install_hint(runtime: Runtime) -> &'static str {
+    match runtime {
+        Runtime::Docker => "Install the Compose v2 plugin (docker-compose-plugin)",
+        Runtime::Podman => {
+            "Install podman-compose (sudo dnf install podman-compose) or Docker Compose v2 for `podman compose`"
+        }
+    }
+}
+
+/// The Compose version row and the `pull_policy` row it implies.
+fn compose_version_checks(runtime: Runtime, command: &str, version: &str) -> [PreflightCheck; 2] {
+    if command.contains(compose::PODMAN_COMPOSE) {
+        return podman_compose_checks(runtime, command, version);
+    }
+    let parsed = parse_version(version);
+    let version_check = match parsed {
+        Some((major, _)) if major >= 2 => {
+            PreflightCheck::pass("Compose version", format!("{} {}", command, version))
+        }
+        _ => PreflightCheck::warn(
+            "Compose version",
+            format!("{} {}", command, version),
+            compose_install_hint(runtime),
+        ),
+    };
+    let pull_policy = match parsed {
+        Some(version) if version >= PULL_POLICY_COMPOSE => PreflightCheck::pass(
+            "pull_policy support",
+            "identity is re-pulled on every install",
+        ),
+        _ => PreflightCheck::fail(
+            "pull_policy support",
+            format!("Compose {} does not understand pull_policy", version),
+            format!(
+                "{}; older releases reject docker-compose.yaml",
+                compose_install_hint(runtime)
+            ),
+        ),
+    };
+    [version_check, pull_policy]
+}
+
+/// podman-compose numbers its releases on its own, and accepts `pull_policy`
+/// in every release. 1.0.x prints its banner before the `--short` version.
+fn podman_compose_checks(runtime: Runtime, command: &str, version: &str) -> [PreflightCheck; 2] {
+    let version = version.lines().last().unwrap_or_default().trim();
+    let version_check = match parse_version(version) {
+        Some(parsed) if parsed >= MIN_PODMAN_COMPOSE => {
+            PreflightCheck::pass("Compose version", format!("{} {}", command, version))
+        }
+        _ => PreflightCheck::warn(
+            "Compose version",
+            format!("{} {}", command, version),
+            compose_install_hint(runtime),
+        ),
+    };
+    [
+        version_check,
+        PreflightCheck::pass(
+            "pull_policy support",
+            "identity is re-pulled on every install",
+        ),
+    ]
+}
+
+/// Available bytes from `df -Pk` output.
+fn parse_df_available(output: &str) -> Option<u64> {
+    let line = output.lines().nth(1)?;
+    let kilobytes: u64 = line.split_whitespace().nth(3)?.parse().ok()?;
+    Some(kilobytes * 1024)
+}
+
+fn disk_check(path: &str, available: u64) -> PreflightCheck {
+    let detail = format!("{} free on {}", format_gib(available), path);
+    if available < DISK_FAIL_BYTES {
+        PreflightCheck::fail(
+            "Disk space",
+            detail,
+            "Free at least 2 GiB for images and the database (docker system prune)",
+        )
+    } else if available < DISK_WARN_BYTES {
+        PreflightCheck::warn(
+            "Disk space",
+            detail,
+            "5 GiB or more is recommended for images, backups and upgrades",
+        )
+    } else {
+        PreflightCheck::pass("Disk space", detail)
+    }
+}
+
+/// `MemAvailable` from `/proc/meminfo`, in bytes.
+fn parse_mem_available(meminfo: &str) -> Option<u64> {
+    meminfo.lines().find_map(|line| {
+        let kilobytes = line
+            .strip_prefix("MemAvailable:")?
+            .trim()
+            .strip_suffix("kB")?;
+        Some(kilobytes.trim().parse::<u64>().ok()? * 1024)
+    })
+}
+
+fn memory_check(available: u64) -> PreflightCheck {
+    let detail = format!("{} available", format_gib(available));
+    if available < MEMORY_WARN_BYTES {
+        PreflightCheck::warn(
+            "Memory",
+            detail,
+            "Keycloak and PostgreSQL want about 2 GiB; stop other workloads or add memory",
+        )
+    } else {
+        PreflightCheck::pass("Memory", detail)
+    }
+}
+
+fn format_gib(bytes: u64) -> String {
+    format!("{:.1} GiB", bytes as f64 / GIB as f64)
+}
+
+/// `name\tstate\tcompose project` rows from `docker ps -a`.
+fn parse_containers(output: &str) -> Vec<(String, String, String)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            Some((
+                fields.next()?.to_string(),
+                fields.next().unwrap_or_default().to_string(),
+                fields.next().unwrap_or_default().to_string(),
+            ))
+        })
+        .collect()
+}
+
+fn existing_container_checks(
+    project: &str,
+    names: &[String],
+    containers: &[(String, String, String)],
+) -> Vec<PreflightCheck> {
+    let ours: Vec<_> = containers
+        .iter()
+        .filter(|(name, _, _)| names.contains(name))
+        .collect();
+    if ours.is_empty() {
+        return vec![PreflightCheck::pass(
+            "Existing containers",
+            format!("No {} containers yet", names.join("/")),
+        )];
+    }
+
+    ours.into_iter()
+        .map(|(name, state, owner)| {
+            if owner == project {
+                PreflightCheck::warn(
+                    "Existing containers",
+                    format!("{} is {} from an earlier install", name, state),
+                    "It will be recreated in place; take a backup first if it holds data",
+                )
+            } else {
+                PreflightCheck::fail(
+                    "Existing containers",
+                    format!(
+                        "{} exists but does not belong to this compose project",
+                        name
+                    ),
+                    format!("Remove or rename it first: docker rm -f {}", name),
+                )
+            }
+        })
+        .collect()
+}
+
+/// Rootless engines cannot publish ports below
+/// `net.ipv4.ip_unprivileged_port_start` (1024 unless lowered).
+fn rootless_port_check(ports: &[(&str, u16)], unprivileged_start: u16) -> PreflightCheck {
+    let name = "Rootless ports";
+    let low: Vec<String> = ports
+        .iter()
+        .filter(|(_, port)| *port < unprivileged_start)
+        .map(|(key, port)| format!("{}={}", key, port))
+        .collect();
+    if low.is_empty() {
+        return PreflightCheck::pass(
+            name,
+            format!("All ports are at or above {}", unprivileged_start),
+        );
+    }
+    PreflightCheck::fail(
+        name,
+        format!(
+            "{} below {} cannot be published rootless",
+            low.join(", "),
+            unprivileged_start
+        ),
+        format!(
+            "Use ports from {} up in .env, or lower it (sudo sysctl net.ipv4.ip_unprivileged_port_start=80)",
+            unprivileged_start
+        ),
+    )
+}
+
+async fn runtime_output(args: &[&str]) -> Result<std::process::Output> {
+    Ok(Runtime::current().command().args(args).output().await?)
+}
+
+fn stdout(output: &std::process::Output) -> String {
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+impl App {
+    /// Runs every check. Docker checks are skipped when the daemon cannot be
+    /// reached, since they would all fail for the same reason.
+    pub(super) async fn run_preflight_checks(&mut self) -> PreflightReport {
+        let mut checks = Vec::new();
+        let runtime = Runtime::current();
+        let (root_format, version_format) = info_formats(runtime);
+
+        let info = match runtime_output(&["info", "--format", root_format]).await {
+            Ok(output) if output.status.success() => {
+                let mode = if runtime.is_rootless() {
+                    "Reachable (rootless)"
+                } else {
+                    "Reachable"
+                };
+                checks.push(PreflightCheck::pass(daemon_check_name(runtime), mode));
+                Some(stdout(&output))
+            }
+            Ok(output) => {
+                checks.push(daemon_failure(
+                    runtime,
+                    &String::from_utf8_lossy(&output.stderr),
+                ));
+                None
+            }
+            Err(e) => {
+                checks.push(PreflightCheck::fail(
+                    daemon_check_name(runtime),
+                    format!("Cannot run {}: {}", runtime.binary(), e),
+                    match runtime {
+                        Runtime::Docker => {
+                            "Install Docker Engine from docs.docker.com/engine/install"
+                        }
+                        Runtime::Podman => "Install Podman (sudo dnf install podman)",
+                    },
+                ));
+                None
+            }
+        };
+
+        let root = utils::project_root();
+        let env = EnvFile::load(&Self::env_path()).unwrap_or_default();
+        let mut project = None;
+
+        if let Some(docker_root) = &info {
+            if let Ok(output) = runtime_output(&["version", "--format", version_format]).await {
+                checks.push(version_check(runtime, &stdout(&output)));
+            }
+
+            // Pulls, health and logs go through the API socket, which Podman
+            // only serves while podman.socket is running.
+            let api = match DockerClient::from_env() {
+                Ok(client) => client.ping().await,
+                Err(e) => Err(e),
+            };
+            checks.push(match api {
+                Ok(()) => PreflightCheck::pass("Container API", "Answering"),
+                Err(e) => {
+                    PreflightCheck::fail("Container API", e.to_string(), runtime.start_hint())
+                }
+            });
+
+            match self.detect_compose_command().await {
+                Ok(compose_cmd) => {
+                    let version = compose::command(&compose_cmd, &root)
+                        .args(["version", "--short"])
+                        .output()
+                        .await
+                        .map(|output| stdout(&output))
+                        .unwrap_or_default();
+                    checks.extend(compose_version_checks(
+                        runtime,
+                        &compose_cmd.join(" "),
+                        &version,
+                    ));
+                    project = ComposeProject::load_uninterpolated(&compose_cmd, &root)
+                        .await
+                        .ok();
+                }
+                Err(e) => checks.push(PreflightCheck::fail(
+                    "Compose version",
+                    e.to_string(),
+                    compose_install_hint(runtime),
+                )),
+            }
+
+            let disk_path = if Path::new(docker_root).exists() {
+                docker_root.as_str()
+            } else {
+                root.to_str().unwrap_or("/")
+            };
+            let df = Command::new("df").args(["-Pk", disk_path]).output().await;
+            match df
+                .ok()
+                .and_then(|output| parse_df_available(&stdout(&output)))
+            {
+                Some(available) => checks.push(disk_check(disk_path, available)),
+                None => checks.push(PreflightCheck::warn(
+                    "Disk space",
+                    format!("Could not read free space on {}", disk_path),
+                    "Make sure at least 5 GiB is free",
+                )),
+            }
+        }
+
+        match std::fs::read_to_string("/proc/meminfo")
+            .ok()
+            .as_deref()
+            .and_then(parse_mem_available)
+        {
+            Some(available) => checks.push(memory_check(available)),
+            None => checks.push(PreflightCheck::warn(
+                "Memory",
+                "Could not read available memory",
+                "Make sure about 2 GiB is free for Keycloak and PostgreSQL",
+            )),
+        }
+
+        let (project_name, names): (String, Vec<String>) = match &project {
+            Some(project) => (
+                project.name.clone(),
+                project
+                    .services
+                    .keys()
+                    .map(|service| project.container_name(service))
+                    .collect(),
+            ),
+            None => (
+                DEFAULT_PROJECT.to_string(),
+                DEFAULT_CONTAINERS
+                    .iter()
+                    .map(|name| name.to_string())
+                    .collect(),
+            ),
+        };
+
+        let containers = if info.is_some() {
+            let format = "{{.Names}}\t{{.State}}\t{{.Label \"com.docker.compose.project\"}}";
+            match runtime_output(&["ps", "--all", "--format", format]).await {
+                Ok(output) if output.status.success() => parse_containers(&stdout(&output)),
+                _ => Vec::new(),
+            }
+        } else {
+            Vec::new()
+        };
+
+        let mut ports = Vec::new();
+        for (key, default) in [
+            (HTTP_PORT_KEY, DEFAULT_HTTP_PORT),
+            (PG_PORT_KEY, DEFAULT_PG_PORT),
+        ] {
+            let port: u16 = env.get(key).unwrap_or(default).parse().unwrap_or_default();
+            checks.push(Self::port_check(key, port, &names).await);
+            ports.push((key, port));
+        }
+        if runtime.is_rootless() {
+            let unprivileged_start = std::fs::read_to_string(UNPRIVILEGED_PORT_START)
+                .ok()
+                .and_then(|value| value.trim().parse().ok())
+                .unwrap_or(1024);
+            checks.push(rootless_port_check(&ports, unprivileged_start));
+        }
+
+        if info.is_some() {
+            checks.extend(existing_container_checks(
+                &project_name,
+                &names,
+                &containers,
+            ));
+        }
+
+        PreflightReport { checks }
+    }
+
+    /// A port is fine when it is free or published by one of the stack's own
+    /// containers, which the install recreates.
+    async fn port_check(key: &str, port: u16, names: &[String]) -> PreflightCheck {
+        let name = if key == HTTP_PORT_KEY {
+            "Keycloak port"
+        } else {
+            "PostgreSQL port"
+        };
+        if TcpListener::bind(("0.0.0.0", port)).is_ok() {
+            return PreflightCheck::pass(name, format!("{} is free", port));
+        }
+
+        let filter = format!("publish={}", port);
+        let owners = runtime_output(&["ps", "--filter", &filter, "--format", "{{.Names}}"])
+            .await
+            .map(|output| stdout(&output))
+            .unwrap_or_default();
+        let owners: Vec<&str> = owners.lines().collect();
+        if !owners.is_empty() && owners.iter().all(|owner| names.iter().any(|n| n == owner)) {
+            return PreflightCheck::pass(
+                name,
+                format!(
+                    "{} is published by {} (existing install)",
+                    port,
+                    owners.join(", ")
+                ),
+            );
+        }
+
+        let holder = if owners.is_empty() {
+            "another process".to_string()
+        } else {
+            owners.join(", ")
+        };
+        PreflightCheck::fail(
+            name,
+            format!("{} is already in use by {}", port, holder),
+            format!(
+                "Stop {} or set {} to a free port in .env, then press R",
+                holder, key
+            ),
+        )
+    }
+
+    /// Shows the preflight page and runs the checks.
+    pub(super) async fn start_preflight(&mut self) {
+        self.state = AppState::Preflight;
+        self.preflight = Some(self.run_preflight_checks().await);
+    }
+
+    pub(super) fn handle_preflight_events(&mut self) -> Result<Option<PreflightAction>> {
+        if event::poll(std::time::Duration::from_millis(100))?
+            && let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            match key.code {
+                KeyCode::Enter if self.preflight.as_ref().is_some_and(PreflightReport::passed) => {
+                    return Ok(Some(PreflightAction::Continue));
+                }
+                KeyCode::Char('r') | KeyCode::Char('R') => {
+                    return Ok(Some(PreflightAction::Rerun));
+                }
+                KeyCode::Esc => return Ok(Some(PreflightAction::Back)),
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    self.running = false;
+                }
+                _ => {}
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_and_resource_checks() {
+        assert_eq!(parse_version("v2.29.7"), Some((2, 29)));
+        assert_eq!(parse_version("24.0.7-ce"), Some((24, 0)));
+        assert_eq!(
+            version_check(Runtime::Docker, "19.03.5").status,
+            CheckStatus::Warn
+        );
+        assert_eq!(
+            version_check(Runtime::Podman, "4.9.4").status,
+            CheckStatus::Pass
+        );
+
+        let [compose, pull_policy] =
+            compose_version_checks(Runtime::Docker, "docker-compose", "1.25.0");
+        assert_eq!(compose.status, CheckStatus::Warn);
+        assert_eq!(pull_policy.status, CheckStatus::Fail);
+        let [compose, pull_policy] = compose_version_checks(
+            Runtime::Podman,
+            "podman-compose",
+            "podman-compose version: 1.0.6\nusing podman version: 4.9.4\n1.0.6\n",
+        );
+        assert_eq!(compose.status, CheckStatus::Pass);
+        assert_eq!(compose.detail, "podman-compose 1.0.6");
+        assert_eq!(pull_policy.status, CheckStatus::Pass);
+        let [compose, _] = compose_version_checks(Runtime::Podman, "podman compose", "1.27.0");
+        assert_eq!(compose.status, CheckStatus::Warn);
+        assert!(compose.hint.unwrap().contains("podman-compose"));
+        let [compose, pull_policy] =
+            compose_version_checks(Runtime::Docker, "docker compose", "2.29.7");
+        assert_eq!(compose.status, CheckStatus::Pass);
+        assert_eq!(pull_policy.status, CheckStatus::Pass);
+
+        let df = "Filesystem 1024-blocks Used Available Capacity Mounted on\n\
+                  /dev/sda1 41152736 38000000 1048576 98% /\n";
+        let available = parse_df_available(df).unwrap();
+        assert_eq!(available, GIB);
+        assert_eq!(disk_check("/", available).status, CheckStatus::Fail);
+        assert_eq!(disk_check("/", 3 * GIB).status, CheckStatus::Warn);
+
+        let meminfo = "MemTotal:       16303428 kB\nMemAvailable:    1048576 kB\n";
+        assert_eq!(parse_mem_available(meminfo), Some(GIB));
+        assert_eq!(memory_check(GIB).status, CheckStatus::Warn);
+
+        let failure = daemon_failure(
+            Runtime::Docker,
+            "permission denied while trying to connect to the Docker daemon socket",
+        );
+        assert!(failure.hint.unwrap().contains("docker group"));
+        let failure = daemon_failure(
+            Runtime::Podman,
+            "cannot find UID/GID for user admin: no subuid ranges found",
+        );
+        assert!(failure.hint.unwrap().contains("/etc/subuid"));
+
+        let ports = [(HTTP_PORT_KEY, 80), (PG_PORT_KEY, 5432)];
+        let rootless = rootless_port_check(&ports, 1024);
+        assert_eq!(rootless.status, CheckStatus::Fail);
+        assert!(rootless.detail.contains("=80"));
+        assert_eq!(rootless_port_check(&ports, 80).status, CheckStatus::Pass);
+    }
+
+    #[test]
+    fn test_existing_containers() {
+        let names = vec!["identity".to_string(), "identity-db".to_string()];
+        let containers = parse_containers(
+            "identity-db\trunning\tidentity\nidentity\texited\tother\nweb\trunning\t\n",
+        );
+        let checks = existing_container_checks("identity", &names, &containers);
+        assert_eq!(checks.len(), 2);
+        assert_eq!(checks[0].status, CheckStatus::Warn);
+        assert_eq!(checks[1].status, CheckStatus::Fail);
+
+        let report = PreflightReport { checks };
+        assert!(!report.passed());
+        assert_eq!(report.failures().count(), 1);
+
+        let clean = existing_container_checks("identity", &names, &[]);
+        assert_eq!(clean[0].status, CheckStatus::Pass);
+    }
+}
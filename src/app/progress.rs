@@ -0,0 +1,545 @@
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+
+use crate::docker::PullEvent;
+
+/// One line of `docker compose` output (`--progress plain` or `--progress json`),
+/// classified into what the installer cares about.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProgressEvent {
+    /// `<service> Pulling` / `<service> Pulled` / `<service> Skipped ...`.
+    ServicePull {
+        service: String,
+        done: bool,
+    },
+    /// Progress of one image layer; `current`/`total` are bytes when known.
+    Layer {
+        id: String,
+        stage: LayerStage,
+        current: u64,
+        total: u64,
+    },
+    /// `Container <name>  <status>`.
+    Container {
+        name: String,
+        status: ContainerStatus,
+    },
+    /// Networks, volumes and images being created or removed.
+    Resource {
+        kind: String,
+        name: String,
+        status: String,
+    },
+    /// `Step 2/4 : RUN ...` (classic builder) or `#6 [2/4] RUN ...` (BuildKit).
+    BuildStep {
+        step: u32,
+        total: u32,
+    },
+    Error(String),
+    Message(String),
+}
+
+impl ProgressEvent {
+    /// Whether the event reports a container, network, volume or image as
+    /// removed by `compose down`.
+    pub fn is_removal(&self) -> bool {
+        match self {
+            ProgressEvent::Container { status, .. } => *status == ContainerStatus::Removed,
+            ProgressEvent::Resource { status, .. } => status == "Removed" || status == "Deleted",
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayerStage {
+    Waiting,
+    Downloading,
+    Extracting,
+    Complete,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContainerStatus {
+    Creating,
+    Created,
+    Starting,
+    Started,
+    Waiting,
+    Healthy,
+    Running,
+    Stopping,
+    Stopped,
+    Removing,
+    Removed,
+    Error(String),
+    Other(String),
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct JsonMessage {
+    #[serde(default)]
+    id: String,
+    #[serde(default)]
+    parent_id: String,
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    status: String,
+    #[serde(default)]
+    current: u64,
+    #[serde(default)]
+    total: u64,
+}
+
+pub fn parse_line(line: &str) -> Option<ProgressEvent> {
+    let trimmed = line.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+
+    if trimmed.starts_with('{')
+        && let Ok(message) = serde_json::from_str::<JsonMessage>(trimmed)
+    {
+        return Some(parse_json(message));
+    }
+
+    Some(parse_plain(trimmed))
+}
+
+/// Classifies one message of the Docker Engine API pull stream. Layer
+/// messages become `Layer` events; the rest (`Pulling from ...`,
+/// `Digest: ...`, `Status: ...`) are passed on as messages.
+pub fn pull_event(event: &PullEvent) -> Option<ProgressEvent> {
+    let id = event.id.as_deref().unwrap_or_default();
+    if is_layer_id(id) {
+        let stage = layer_stage(&event.status);
+        let detail = event.progress_detail.unwrap_or_default();
+        let (current, total) = match stage {
+            LayerStage::Complete => (detail.total.max(1), detail.total.max(1)),
+            _ => (detail.current, detail.total),
+        };
+        return Some(ProgressEvent::Layer {
+            id: id.to_string(),
+            stage,
+            current,
+            total,
+        });
+    }
+    if event.status.is_empty() {
+        return None;
+    }
+    Some(ProgressEvent::Message(event.status.clone()))
+}
+
+fn parse_json(message: JsonMessage) -> ProgressEvent {
+    if let Some((kind, name)) = message.id.split_once(' ') {
+        return resource_event(kind, name, &message.text, &message.status);
+    }
+
+    if !message.parent_id.is_empty() || is_layer_id(&message.id) {
+        let stage = layer_stage(&message.text);
+        let (current, total) = match stage {
+            LayerStage::Complete => (message.total.max(1), message.total.max(1)),
+            _ => (message.current, message.total),
+        };
+        return ProgressEvent::Layer {
+            id: message.id,
+            stage,
+            current,
+            total,
+        };
+    }
+
+    if message.text == "Error" {
+        let subject = if message.id.is_empty() {
+            message.status
+        } else {
+            format!("{}: {}", message.id, message.status)
+        };
+        return ProgressEvent::Error(subject);
+    }
+
+    service_pull_event(&message.id, &message.text).unwrap_or_else(|| {
+        ProgressEvent::Message(
+            [message.id, message.text, message.status]
+                .iter()
+                .filter(|part| !part.is_empty())
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(" "),
+        )
+    })
+}
+
+fn parse_plain(line: &str) -> ProgressEvent {
+    if let Some(step) = parse_build_step(line) {
+        return step;
+    }
+
+    if is_error_line(line) {
+        return ProgressEvent::Error(line.to_string());
+    }
+
+    let (first, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let rest = rest.trim();
+
+    if matches!(first, "Container" | "Network" | "Volume" | "Image")
+        && let Some((name, status)) = rest.split_once(char::is_whitespace)
+    {
+        let (text, detail) = status
+            .trim()
+            .split_once(char::is_whitespace)
+            .unwrap_or((status.trim(), ""));
+        return resource_event(first, name.trim_matches('"'), text, detail.trim());
+    }
+
+    if is_layer_id(first) {
+        let stage = layer_stage(rest);
+        let (current, total) = match stage {
+            LayerStage::Complete => (1, 1),
+            _ => rest
+                .rsplit_once(' ')
+                .and_then(|(_, sizes)| sizes.split_once('/'))
+                .and_then(|(current, total)| Some((parse_size(current)?, parse_size(total)?)))
+                .unwrap_or((0, 0)),
+        };
+        return ProgressEvent::Layer {
+            id: first.to_string(),
+            stage,
+            current,
+            total,
+        };
+    }
+
+    if let Some(error) = rest.strip_prefix("Error") {
+        return ProgressEvent::Error(format!("{}:{}", first, error));
+    }
+
+    let text = rest.split_whitespace().next().unwrap_or("");
+    service_pull_event(first, text).unwrap_or_else(|| ProgressEvent::Message(line.to_string()))
+}
+
+fn resource_event(kind: &str, name: &str, text: &str, detail: &str) -> ProgressEvent {
+    if kind != "Container" {
+        return ProgressEvent::Resource {
+            kind: kind.to_string(),
+            name: name.to_string(),
+            status: text.to_string(),
+        };
+    }
+
+    let status = match text {
+        "Creating" | "Recreate" => ContainerStatus::Creating,
+        "Created" | "Recreated" => ContainerStatus::Created,
+        "Starting" => ContainerStatus::Starting,
+        "Started" => ContainerStatus::Started,
+        "Waiting" => ContainerStatus::Waiting,
+        "Healthy" => ContainerStatus::Healthy,
+        "Running" => ContainerStatus::Running,
+        "Stopping" => ContainerStatus::Stopping,
+        "Stopped" => ContainerStatus::Stopped,
+        "Removing" => ContainerStatus::Removing,
+        "Removed" => ContainerStatus::Removed,
+        "Error" => ContainerStatus::Error(detail.to_string()),
+        other => ContainerStatus::Other(format!("{} {}", other, detail).trim().to_string()),
+    };
+
+    ProgressEvent::Container {
+        name: name.to_string(),
+        status,
+    }
+}
+
+fn service_pull_event(service: &str, text: &str) -> Option<ProgressEvent> {
+    let done = match text {
+        "Pulling" => false,
+        "Pulled" | "Skipped" => true,
+        _ => return None,
+    };
+    Some(ProgressEvent::ServicePull {
+        service: service.to_string(),
+        done,
+    })
+}
+
+fn layer_stage(text: &str) -> LayerStage {
+    if text.starts_with("Downloading") {
+        LayerStage::Downloading
+    } else if text.starts_with("Extracting") {
+        LayerStage::Extracting
+    } else if text.starts_with("Pull complete") || text.starts_with("Already exists") {
+        LayerStage::Complete
+    } else {
+        LayerStage::Waiting
+    }
+}
+
+/// Image layers are reported by their 12 character short digest.
+fn is_layer_id(token: &str) -> bool {
+    token.len() == 12 && token.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn is_error_line(line: &str) -> bool {
+    let first = line
+        .split_whitespace()
+        .next()
+        .unwrap_or("")
+        .trim_end_matches(':')
+        .to_ascii_lowercase();
+    first == "error" || line.starts_with("failed to ") || line.starts_with("ERROR")
+}
+
+fn parse_build_step(line: &str) -> Option<ProgressEvent> {
+    let fraction = if let Some(after) = line.strip_prefix("Step ") {
+        after.split_whitespace().next()?
+    } else if line.starts_with('#') {
+        let (_, after) = line.split_once(" [")?;
+        let (inner, _) = after.split_once(']')?;
+        inner.split_whitespace().last()?
+    } else {
+        return None;
+    };
+
+    let (step, total) = fraction.split_once('/')?;
+    Some(ProgressEvent::BuildStep {
+        step: step.parse().ok()?,
+        total: total.parse().ok()?,
+    })
+}
+
+/// Parses Docker's decimal sizes such as `35.05kB` or `3.408MB`.
+fn parse_size(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let multiplier = match unit {
+        "" | "B" => 1.0,
+        "kB" | "KB" => 1e3,
+        "MB" => 1e6,
+        "GB" => 1e9,
+        "TB" => 1e12,
+        _ => return None,
+    };
+    Some((number.parse::<f64>().ok()? * multiplier) as u64)
+}
+
+/// Tracks the layers of the image currently being pulled. Downloading counts
+/// for the first half of a layer and extracting for the second.
+#[derive(Debug, Default, Clone)]
+pub struct PullProgress {
+    layers: BTreeMap<String, f64>,
+}
+
+impl PullProgress {
+    pub fn update(&mut self, id: &str, stage: LayerStage, current: u64, total: u64) {
+        let ratio = if total > 0 {
+            (current as f64 / total as f64).min(1.0)
+        } else {
+            0.0
+        };
+        let done = match stage {
+            LayerStage::Waiting => 0.0,
+            LayerStage::Downloading => ratio * 0.5,
+            LayerStage::Extracting => 0.5 + ratio * 0.5,
+            LayerStage::Complete => 1.0,
+        };
+
+        let entry = self.layers.entry(id.to_string()).or_insert(0.0);
+        *entry = entry.max(done);
+    }
+
+    /// Fraction of the image downloaded and extracted, from 0.0 to 1.0.
+    pub fn fraction(&self) -> f64 {
+        if self.layers.is_empty() {
+            return 0.0;
+        }
+        self.layers.values().sum::<f64>() / self.layers.len() as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Captured from `docker compose pull postgres` and `docker compose up -d`
+    // with stdout redirected to a file (compose falls back to plain progress).
+    const PLAIN_OUTPUT: &str = " postgres Pulling
+ a2318d6c47ec Pulling fs layer
+ a2318d6c47ec Downloading [>                                                  ]  35.05kB/3.408MB
+ a2318d6c47ec Verifying Checksum
+ a2318d6c47ec Download complete
+ a2318d6c47ec Extracting [=========>                                         ]  655.4kB/3.408MB
+ a2318d6c47ec Pull complete
+ 3e2b4ae6bd0e Already exists
+ postgres Pulled
+ Network identity_default  Creating
+ Network identity_default  Created
+ Volume \"identity_identity_pgdata\"  Creating
+ Container identity-db  Creating
+ Container identity-db  Created
+ Container identity-db  Starting
+ Container identity-db  Started
+ Container identity-db  Waiting
+ Container identity-db  Healthy
+Error response from daemon: driver failed programming external connectivity on endpoint identity: Bind for 0.0.0.0:8080 failed: port is already allocated
+";
+
+    #[test]
+    fn test_parse_plain_output() {
+        let events: Vec<ProgressEvent> = PLAIN_OUTPUT.lines().filter_map(parse_line).collect();
+
+        assert_eq!(
+            events[0],
+            ProgressEvent::ServicePull {
+                service: "postgres".to_string(),
+                done: false
+            }
+        );
+        assert_eq!(
+            events[2],
+            ProgressEvent::Layer {
+                id: "a2318d6c47ec".to_string(),
+                stage: LayerStage::Downloading,
+                current: 35_050,
+                total: 3_408_000,
+            }
+        );
+        assert!(matches!(
+            &events[7],
+            ProgressEvent::Layer { stage: LayerStage::Complete, id, .. } if id == "3e2b4ae6bd0e"
+        ));
+        assert_eq!(
+            events[8],
+            ProgressEvent::ServicePull {
+                service: "postgres".to_string(),
+                done: true
+            }
+        );
+        assert!(
+            matches!(&events[11], ProgressEvent::Resource { kind, name, .. }
+            if kind == "Volume" && name == "identity_identity_pgdata")
+        );
+        assert_eq!(
+            events[15],
+            ProgressEvent::Container {
+                name: "identity-db".to_string(),
+                status: ContainerStatus::Started
+            }
+        );
+        assert!(matches!(
+            &events[17],
+            ProgressEvent::Container {
+                status: ContainerStatus::Healthy,
+                ..
+            }
+        ));
+        assert!(
+            matches!(&events[18], ProgressEvent::Error(message) if message.contains("port is already allocated"))
+        );
+    }
+
+    #[test]
+    fn test_parse_json_output() {
+        let layer = parse_line(
+            r#"{"id":"a2318d6c47ec","parent_id":"postgres","text":"Downloading","status":"[>   ]  1.7MB/3.4MB","current":1704000,"total":3408000,"percent":50}"#,
+        );
+        assert_eq!(
+            layer,
+            Some(ProgressEvent::Layer {
+                id: "a2318d6c47ec".to_string(),
+                stage: LayerStage::Downloading,
+                current: 1_704_000,
+                total: 3_408_000,
+            })
+        );
+
+        let started = parse_line(r#"{"id":"Container identity","text":"Started"}"#);
+        assert_eq!(
+            started,
+            Some(ProgressEvent::Container {
+                name: "identity".to_string(),
+                status: ContainerStatus::Started
+            })
+        );
+
+        let pulled = parse_line(r#"{"id":"identity","text":"Pulled"}"#);
+        assert!(matches!(
+            pulled,
+            Some(ProgressEvent::ServicePull { done: true, .. })
+        ));
+    }
+
+    #[test]
+    fn test_harmless_lines_are_not_errors() {
+        for line in [
+            "#8 [2/3] RUN grep -r error-handler /opt/keycloak/themes",
+            "Step 3/5 : COPY error-pages /srv/errors",
+            " identity Skipped - Image is already present locally",
+            "keycloak-1  | 2024-05-01 INFO [org.keycloak] No errors found",
+        ] {
+            assert!(
+                !matches!(parse_line(line), Some(ProgressEvent::Error(_))),
+                "{}",
+                line
+            );
+        }
+
+        assert_eq!(
+            parse_line("#8 [2/3] RUN grep -r error-handler /opt/keycloak/themes"),
+            Some(ProgressEvent::BuildStep { step: 2, total: 3 })
+        );
+        assert!(matches!(
+            parse_line(" identity Error manifest unknown"),
+            Some(ProgressEvent::Error(_))
+        ));
+    }
+
+    #[test]
+    fn test_pull_progress_never_exceeds_one() {
+        let mut progress = PullProgress::default();
+        progress.update("a", LayerStage::Downloading, 50, 100);
+        progress.update("b", LayerStage::Waiting, 0, 0);
+        assert_eq!(progress.fraction(), 0.125);
+
+        progress.update("a", LayerStage::Extracting, 200, 100);
+        progress.update("b", LayerStage::Complete, 1, 1);
+        progress.update("b", LayerStage::Downloading, 10, 100);
+        assert_eq!(progress.fraction(), 1.0);
+    }
+
+    #[test]
+    fn test_pull_event() {
+        let event = |json: &str| pull_event(&serde_json::from_str(json).unwrap());
+
+        assert_eq!(
+            event(
+                r#"{"status":"Downloading","progressDetail":{"current":35050,"total":3408000},"progress":"[>  ]","id":"a2318d6c47ec"}"#
+            ),
+            Some(ProgressEvent::Layer {
+                id: "a2318d6c47ec".to_string(),
+                stage: LayerStage::Downloading,
+                current: 35050,
+                total: 3408000,
+            })
+        );
+        assert_eq!(
+            event(r#"{"status":"Already exists","progressDetail":{},"id":"3e2b4ae6bd0e"}"#),
+            Some(ProgressEvent::Layer {
+                id: "3e2b4ae6bd0e".to_string(),
+                stage: LayerStage::Complete,
+                current: 1,
+                total: 1,
+            })
+        );
+        assert_eq!(
+            event(r#"{"status":"Pulling from library/postgres","id":"16-alpine"}"#),
+            Some(ProgressEvent::Message(
+                "Pulling from library/postgres".to_string()
+            ))
+        );
+    }
+}
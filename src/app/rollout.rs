@@ -0,0 +1,399 @@
+use std::collections::HashMap;
+
+use color_eyre::{Result, eyre::eyre};
+use serde::Deserialize;
+
+use super::surface::Surface;
+use super::{App, UpdateInfo};
+use crate::docker::DockerClient;
+use crate::env_file::EnvFile;
+use crate::registry::RegistryConfig;
+use crate::runtime::Runtime;
+
+/// The image a container runs, as recorded before and after an update.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunningImage {
+    /// Local image ID (`sha256:...`), always present.
+    pub id: String,
+    /// `repo@sha256:...` when the image came from a registry.
+    pub digest: Option<String>,
+    /// The `org.opencontainers.image.version` label, when the image sets it.
+    pub version: Option<String>,
+}
+
+impl RunningImage {
+    pub fn label(&self) -> &str {
+        self.digest.as_deref().unwrap_or(&self.id)
+    }
+
+    /// Short form for reports: `sha256:0123456789ab (1.4.2)`.
+    pub fn describe(&self) -> String {
+        let hash = self.label().rsplit('@').next().unwrap_or_default();
+        let short: String = hash.chars().take("sha256:".len() + 12).collect();
+        match &self.version {
+            Some(version) => format!("{} ({})", short, version),
+            None => short,
+        }
+    }
+}
+
+/// Outcome of a successful update, shown in the update list.
+#[derive(Debug, Clone)]
+pub struct UpdateReport {
+    pub service: String,
+    pub before: Option<RunningImage>,
+    pub after: Option<RunningImage>,
+}
+
+impl UpdateReport {
+    pub fn summary(&self) -> String {
+        let describe = |image: &Option<RunningImage>| {
+            image
+                .as_ref()
+                .map(RunningImage::describe)
+                .unwrap_or_else(|| "not running".to_string())
+        };
+
+        if self.before.is_some() && self.before == self.after {
+            format!(
+                "{} is already up to date: {}",
+                self.service,
+                describe(&self.after)
+            )
+        } else {
+            format!(
+                "{} updated: {} → {}",
+                self.service,
+                describe(&self.before),
+                describe(&self.after)
+            )
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ImageDetails {
+    #[serde(rename = "RepoDigests", default)]
+    repo_digests: Vec<String>,
+    #[serde(rename = "Config", default)]
+    config: Option<ImageConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ImageConfig {
+    #[serde(rename = "Labels", default)]
+    labels: Option<HashMap<String, String>>,
+}
+
+/// Image reference to pin in `.env` before updating, e.g.
+/// `KEYCLOAK_IMAGE=ghcr.io/nexusquantum/nqrust-identity:1.4.2`.
+#[derive(Debug, Clone)]
+pub struct ImagePin {
+    pub env_key: String,
+    pub reference: String,
+}
+
+const VERSION_LABEL: &str = "org.opencontainers.image.version";
+
+/// Image the container is currently running, or `None` when the container
+/// does not exist.
+pub async fn running_image(container: &str) -> Result<Option<RunningImage>> {
+    #[derive(Deserialize)]
+    struct Container {
+        #[serde(rename = "Image")]
+        image: String,
+    }
+
+    let docker = DockerClient::from_env()?;
+    let Some(Container { image: id }) = docker.inspect_container(container).await? else {
+        return Ok(None);
+    };
+    let details = docker
+        .inspect_image::<ImageDetails>(&id)
+        .await
+        .ok()
+        .flatten();
+
+    Ok(Some(image_from_details(id, details)))
+}
+
+fn image_from_details(id: String, details: Option<ImageDetails>) -> RunningImage {
+    let Some(details) = details else {
+        return RunningImage {
+            id,
+            digest: None,
+            version: None,
+        };
+    };
+
+    RunningImage {
+        id,
+        digest: details.repo_digests.into_iter().next(),
+        version: details
+            .config
+            .and_then(|config| config.labels)
+            .and_then(|mut labels| labels.remove(VERSION_LABEL)),
+    }
+}
+
+/// Writes `value` for `key` into the project's `.env`, removing the key when
+/// `value` is `None`. Returns the value it replaced.
+pub(super) fn write_env_pin(key: &str, value: Option<&str>) -> Result<Option<String>> {
+    let path = App::env_path();
+    let mut env = EnvFile::load(&path)?;
+    let previous = env.get(key).map(str::to_string);
+    match value {
+        Some(value) => env.set(key, value),
+        None => env.remove(key),
+    }
+    env.save(&path)?;
+    Ok(previous)
+}
+
+/// Points `reference` back at a previously pulled image.
+async fn retag_image(image_id: &str, reference: &str) -> Result<()> {
+    let output = Runtime::current()
+        .command()
+        .args(["tag", image_id, reference])
+        .output()
+        .await?;
+    if !output.status.success() {
+        return Err(eyre!(
+            "{} tag failed: {}",
+            Runtime::current().binary(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+impl App {
+    /// The compose service an update entry refers to.
+    fn service_for_update(&self, info: &UpdateInfo) -> Result<String> {
+        match &info.service {
+            Some(service) if self.project.services.contains_key(service) => Ok(service.clone()),
+            _ => self
+                .project
+                .service_for_image(&info.image)
+                .map(str::to_string)
+                .ok_or_else(|| eyre!("No compose service uses {}", info.image)),
+        }
+    }
+
+    /// Recreates one service from whatever image is tagged locally, without
+    /// touching its dependencies or pulling.
+    pub(super) async fn recreate_service(
+        &mut self,
+        terminal: &mut impl Surface,
+        compose_cmd: &[String],
+        service: &str,
+    ) -> Result<()> {
+        self.current_service = service.to_string();
+        self.add_log_and_redraw(terminal, &format!("🔁 Recreating {}...", service));
+        let args = [
+            "up",
+            "-d",
+            "--no-deps",
+            "--pull",
+            "never",
+            "--force-recreate",
+            service,
+        ];
+        if !self
+            .run_compose_step(terminal, compose_cmd, &args, &[])
+            .await?
+        {
+            return Err(eyre!("Failed to recreate {}", service));
+        }
+        Ok(())
+    }
+
+    /// Pulls the image of the service running `info`'s image without
+    /// restarting anything.
+    pub(super) async fn pull_service_image(
+        &mut self,
+        terminal: &mut impl Surface,
+        info: &UpdateInfo,
+    ) -> Result<()> {
+        self.load_project_mirrored(&RegistryConfig::configured()?)
+            .await?;
+        let service = self.service_for_update(info)?;
+
+        self.current_service = service.clone();
+        self.pull_service(terminal, &service).await?;
+        self.add_log_and_redraw(terminal, "✅ Image pulled; press A to apply it");
+        Ok(())
+    }
+
+    /// Updates the service running `info`'s image: records the running image,
+    /// backs up the database, pulls, recreates the service and waits for it
+    /// to become healthy. With a `pin`, the new reference is written to `.env`
+    /// first. When the service cannot be recreated or does not come up
+    /// healthy, the previous image, pin and backup are put back.
+    pub(super) async fn update_service(
+        &mut self,
+        terminal: &mut impl Surface,
+        info: &UpdateInfo,
+        pin: Option<&ImagePin>,
+    ) -> Result<UpdateReport> {
+        let compose_cmd = self.detect_compose_command().await?;
+        self.apply_image_rewrites(&RegistryConfig::configured()?, &compose_cmd)
+            .await?;
+        let mut compose_cmd = self.load_project().await?;
+        let service = self.service_for_update(info)?;
+        let previous_reference = self
+            .project
+            .image(&service)
+            .map(str::to_string)
+            .unwrap_or_else(|| info.pull_reference());
+        let container = self.project.container_name(&service);
+
+        let previous = running_image(&container).await?;
+        match &previous {
+            Some(image) => self.add_log_and_redraw(
+                terminal,
+                &format!("📌 {} is running {}", service, image.label()),
+            ),
+            None => self.add_log_and_redraw(
+                terminal,
+                &format!(
+                    "⚠️  {} is not running; the update cannot be rolled back",
+                    service
+                ),
+            ),
+        }
+
+        self.add_log_and_redraw(terminal, "💾 Backing up the database before updating...");
+        let backup = self.create_backup(terminal).await?;
+
+        let mut previous_pin = None;
+        let reference = match pin {
+            Some(pin) => {
+                previous_pin = Some(write_env_pin(&pin.env_key, Some(&pin.reference))?);
+                self.add_log_and_redraw(
+                    terminal,
+                    &format!("📌 Pinned {}={} in .env", pin.env_key, pin.reference),
+                );
+                compose_cmd = self.load_project().await?;
+                pin.reference.clone()
+            }
+            None => previous_reference.clone(),
+        };
+
+        self.progress = 20.0;
+        self.current_service = service.clone();
+        self.add_log_and_redraw(terminal, &format!("⬇️  Pulling {}...", reference));
+        if let Err(e) = self.pull_service(terminal, &service).await {
+            if let (Some(pin), Some(old_value)) = (pin, &previous_pin) {
+                write_env_pin(&pin.env_key, old_value.as_deref())?;
+            }
+            return Err(e);
+        }
+
+        let updated = async {
+            self.progress = 50.0;
+            self.recreate_service(terminal, &compose_cmd, &service)
+                .await?;
+
+            self.progress = 80.0;
+            self.init_service_health();
+            self.service_health.retain(|entry| entry.service == service);
+            self.wait_for_health(terminal).await
+        }
+        .await;
+
+        let error = match updated {
+            Ok(()) => {
+                let report = UpdateReport {
+                    service: service.clone(),
+                    before: previous,
+                    after: running_image(&container).await?,
+                };
+                self.add_log_and_redraw(terminal, &format!("✅ {}", report.summary()));
+                return Ok(report);
+            }
+            Err(e) => e,
+        };
+
+        let Some(previous) = previous else {
+            if let (Some(pin), Some(old_value)) = (pin, &previous_pin) {
+                write_env_pin(&pin.env_key, old_value.as_deref())?;
+            }
+            return Err(eyre!("{} (no previous image to roll back to)", error));
+        };
+
+        self.add_log_and_redraw(
+            terminal,
+            &format!(
+                "↩️  Update failed ({}); rolling back to {}",
+                error,
+                previous.label()
+            ),
+        );
+        let rollback = async {
+            if let (Some(pin), Some(old_value)) = (pin, &previous_pin) {
+                write_env_pin(&pin.env_key, old_value.as_deref())?;
+                self.load_project().await?;
+            }
+            retag_image(&previous.id, &previous_reference).await?;
+            self.restore_backup(terminal, &backup, Some(&service)).await
+        }
+        .await;
+
+        match rollback {
+            Ok(()) => Err(eyre!(
+                "{}; rolled back to {} and restored {}",
+                error,
+                previous.label(),
+                backup.display()
+            )),
+            Err(rollback_error) => Err(eyre!(
+                "{}; rollback also failed: {}. Backup kept at {}",
+                error,
+                rollback_error,
+                backup.display()
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_report_summary() {
+        let details = serde_json::from_str(
+            r#"{
+                "RepoDigests": ["ghcr.io/nexusquantum/nqrust-identity@sha256:0123456789abcdef0123"],
+                "Config": { "Labels": { "org.opencontainers.image.version": "1.4.2" } }
+            }"#,
+        )
+        .ok();
+        let before = image_from_details("sha256:aaaa".to_string(), details);
+        let after = RunningImage {
+            id: "sha256:fedcba9876543210fedc".to_string(),
+            digest: None,
+            version: None,
+        };
+
+        assert_eq!(before.describe(), "sha256:0123456789ab (1.4.2)");
+
+        let report = UpdateReport {
+            service: "identity".to_string(),
+            before: Some(before.clone()),
+            after: Some(after),
+        };
+        assert_eq!(
+            report.summary(),
+            "identity updated: sha256:0123456789ab (1.4.2) → sha256:fedcba987654"
+        );
+
+        let unchanged = UpdateReport {
+            service: "identity".to_string(),
+            before: Some(before.clone()),
+            after: Some(before),
+        };
+        assert!(unchanged.summary().contains("already up to date"));
+    }
+}
@@ -2,17 +2,33 @@
 pub enum AppState {
     RegistrySetup,
     Confirmation,
+    Dashboard,
+    ManagingService,
+    LogViewer,
+    Preflight,
+    Configure,
     UpdateList,
     UpdatePulling,
     Installing,
     Success,
+    Uninstall,
+    Uninstalling,
+    Uninstalled,
+    Backups,
+    BackingUp,
+    Restoring,
+    PgUpgrade,
+    PgUpgrading,
     Error(String),
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum MenuSelection {
+    Dashboard,
     Proceed,
     UpdateToken,
     CheckUpdates,
+    Backups,
+    Uninstall,
     Cancel,
 }
@@ -0,0 +1,26 @@
+use color_eyre::Result;
+use ratatui::DefaultTerminal;
+
+use super::App;
+
+/// Something the app repaints onto while long-running docker work is in flight.
+pub trait Surface {
+    fn refresh(&mut self, app: &mut App) -> Result<()>;
+}
+
+impl Surface for DefaultTerminal {
+    fn refresh(&mut self, app: &mut App) -> Result<()> {
+        self.draw(|frame| app.render(frame))?;
+        Ok(())
+    }
+}
+
+/// Surface used by the CLI subcommands. Nothing is drawn; log lines are echoed
+/// to stdout by `App::add_log` as they arrive instead.
+pub struct Headless;
+
+impl Surface for Headless {
+    fn refresh(&mut self, _app: &mut App) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,240 @@
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use color_eyre::eyre::{Result, bail};
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+
+use super::updates::{Registry, RegistryDenied};
+use crate::registry::RegistryConfig;
+
+const GITHUB_USER_URL: &str = "https://api.github.com/user";
+/// Image pulled by the installer; a token that can read its manifest can pull.
+const PROBE_IMAGE: &str = "ghcr.io/nexusquantum/nqrust-identity";
+const PROBE_TAG: &str = "latest";
+/// Classic scopes that let a token pull from GHCR. `write:packages` implies
+/// `read:packages`.
+const PULL_SCOPES: &[&str] = &["read:packages", "write:packages"];
+pub const REJECTED_MESSAGE: &str = "GitHub rejected the token; it is invalid or revoked";
+pub const REQUIRED_SCOPE: &str = "read:packages";
+/// How long before expiry the installer starts warning.
+pub const EXPIRY_WARNING_DAYS: i64 = 14;
+
+/// How GitHub describes a token's permissions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenKind {
+    /// Classic PAT or OAuth token, with the scopes from `X-OAuth-Scopes`.
+    Classic(Vec<String>),
+    /// Fine-grained PAT; GitHub does not list its permissions, so pull access
+    /// is probed against GHCR instead.
+    FineGrained,
+}
+
+/// What GitHub reported about a token.
+#[derive(Debug, Clone)]
+pub struct TokenCheck {
+    pub username: String,
+    pub kind: TokenKind,
+    /// Missing permissions, e.g. `read:packages`.
+    pub missing: Vec<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl TokenCheck {
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires| expires <= now)
+    }
+
+    /// Why the token cannot be used to pull images, if it cannot.
+    pub fn problem(&self, now: DateTime<Utc>) -> Option<String> {
+        if self.is_expired(now) {
+            return Some(format!(
+                "Token for {} expired on {}",
+                self.username,
+                self.expires_at.unwrap_or(now).format("%Y-%m-%d")
+            ));
+        }
+        if self.missing.is_empty() {
+            return None;
+        }
+        Some(match &self.kind {
+            TokenKind::Classic(scopes) => format!(
+                "Token for {} is missing the {} scope (granted: {})",
+                self.username,
+                self.missing.join(", "),
+                if scopes.is_empty() {
+                    "none".to_string()
+                } else {
+                    scopes.join(", ")
+                }
+            ),
+            TokenKind::FineGrained => format!(
+                "Fine-grained token for {} cannot pull {}; grant it read access to packages",
+                self.username, PROBE_IMAGE
+            ),
+        })
+    }
+
+    /// Expiry in words, e.g. `expires 2026-11-01 (in 14 days)`.
+    pub fn expiry(&self, now: DateTime<Utc>) -> String {
+        match self.expires_at {
+            None => "does not expire".to_string(),
+            Some(expires) => {
+                let days = (expires - now).num_days();
+                format!("expires {} (in {} days)", expires.format("%Y-%m-%d"), days)
+            }
+        }
+    }
+
+    /// A warning when the token expires within `EXPIRY_WARNING_DAYS`.
+    pub fn expiry_warning(&self, now: DateTime<Utc>) -> Option<String> {
+        let expires = self.expires_at?;
+        if self.is_expired(now) || expires - now > Duration::days(EXPIRY_WARNING_DAYS) {
+            return None;
+        }
+        Some(format!(
+            "GHCR token {}; update it from the menu before then",
+            self.expiry(now)
+        ))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubUser {
+    login: String,
+}
+
+/// Asks GitHub who owns `token`, what it may do and when it expires.
+/// Returns `None` when GitHub rejects the token as invalid or revoked.
+pub async fn check_token(client: &Client, token: &str) -> Result<Option<TokenCheck>> {
+    let response = client
+        .get(GITHUB_USER_URL)
+        .header("User-Agent", "nqrust-identity")
+        .header("Accept", "application/vnd.github+json")
+        .bearer_auth(token)
+        .send()
+        .await?;
+
+    match response.status() {
+        StatusCode::OK => {}
+        StatusCode::UNAUTHORIZED => return Ok(None),
+        status => {
+            let body = response.text().await.unwrap_or_default();
+            bail!("GitHub API returned {status} when fetching user info: {body}");
+        }
+    }
+
+    let header = |name: &str| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    };
+    let scopes = header("X-OAuth-Scopes");
+    let expires_at = header("github-authentication-token-expiration")
+        .as_deref()
+        .and_then(parse_expiration);
+    let user: GitHubUser = response.json().await?;
+
+    let (kind, missing) = match scopes {
+        Some(scopes) => {
+            let scopes = parse_scopes(&scopes);
+            let missing = missing_scopes(&scopes);
+            (TokenKind::Classic(scopes), missing)
+        }
+        None => {
+            let registry = Registry::for_image(PROBE_IMAGE);
+            let credentials = RegistryConfig::default().credentials(Some(token));
+            let missing = match registry
+                .manifest_digest(client, PROBE_TAG, &credentials)
+                .await
+            {
+                Ok(_) => Vec::new(),
+                Err(e) if e.downcast_ref::<RegistryDenied>().is_some() => {
+                    vec![REQUIRED_SCOPE.to_string()]
+                }
+                Err(e) => {
+                    return Err(e.wrap_err(format!("Could not check {REQUIRED_SCOPE} on ghcr.io")));
+                }
+            };
+            (TokenKind::FineGrained, missing)
+        }
+    };
+
+    Ok(Some(TokenCheck {
+        username: user.login,
+        kind,
+        missing,
+        expires_at,
+    }))
+}
+
+fn parse_scopes(header: &str) -> Vec<String> {
+    header
+        .split(',')
+        .map(str::trim)
+        .filter(|scope| !scope.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn missing_scopes(scopes: &[String]) -> Vec<String> {
+    if scopes
+        .iter()
+        .any(|scope| PULL_SCOPES.contains(&scope.as_str()))
+    {
+        Vec::new()
+    } else {
+        vec![REQUIRED_SCOPE.to_string()]
+    }
+}
+
+/// Parses `github-authentication-token-expiration`, which GitHub sends as
+/// `2026-11-01 12:00:00 UTC` or `2026-11-01 12:00:00 +0200`.
+fn parse_expiration(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Some(naive) = value.strip_suffix(" UTC") {
+        return NaiveDateTime::parse_from_str(naive, "%Y-%m-%d %H:%M:%S")
+            .ok()
+            .map(|naive| naive.and_utc());
+    }
+    DateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S %z")
+        .ok()
+        .map(|date| date.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scopes_and_expiry() {
+        assert!(missing_scopes(&parse_scopes("repo, write:packages")).is_empty());
+        assert_eq!(
+            missing_scopes(&parse_scopes("repo, workflow")),
+            vec!["read:packages"]
+        );
+        assert_eq!(missing_scopes(&parse_scopes("")), vec!["read:packages"]);
+
+        let expires = parse_expiration("2026-11-01 12:00:00 UTC").unwrap();
+        assert_eq!(parse_expiration("2026-11-01 14:00:00 +0200"), Some(expires));
+        assert_eq!(parse_expiration("soon"), None);
+
+        let check = TokenCheck {
+            username: "octo".to_string(),
+            kind: TokenKind::Classic(vec!["read:packages".to_string()]),
+            missing: Vec::new(),
+            expires_at: Some(expires),
+        };
+        let month_before = expires - Duration::days(30);
+        let week_before = expires - Duration::days(7);
+        assert_eq!(check.problem(month_before), None);
+        assert_eq!(check.expiry_warning(month_before), None);
+        assert!(
+            check
+                .expiry_warning(week_before)
+                .unwrap()
+                .contains("expires 2026-11-01 (in 7 days)")
+        );
+        assert!(check.problem(expires).unwrap().contains("expired"));
+    }
+}
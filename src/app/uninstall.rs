@@ -0,0 +1,134 @@
+use super::compose::ComposeProject;
+
+/// What `compose down` will remove, and the optional extras the user opted into.
+#[derive(Debug, Clone, Default)]
+pub struct UninstallPlan {
+    pub containers: Vec<String>,
+    pub networks: Vec<String>,
+    pub volumes: Vec<String>,
+    pub images: Vec<String>,
+    pub remove_volumes: bool,
+    pub remove_images: bool,
+}
+
+/// Rows of the uninstall page: two toggles followed by the two buttons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UninstallOption {
+    RemoveVolumes,
+    RemoveImages,
+    Confirm,
+    Back,
+}
+
+pub const UNINSTALL_OPTIONS: &[UninstallOption] = &[
+    UninstallOption::RemoveVolumes,
+    UninstallOption::RemoveImages,
+    UninstallOption::Confirm,
+    UninstallOption::Back,
+];
+
+impl UninstallPlan {
+    pub fn from_project(project: &ComposeProject) -> Self {
+        let mut images: Vec<String> = project
+            .service_names()
+            .iter()
+            .filter_map(|service| project.image(service).map(str::to_string))
+            .collect();
+        images.sort();
+        images.dedup();
+
+        Self {
+            containers: project
+                .service_names()
+                .iter()
+                .map(|service| project.container_name(service))
+                .collect(),
+            networks: project.network_names(),
+            volumes: project.volume_names(),
+            images,
+            remove_volumes: false,
+            remove_images: false,
+        }
+    }
+
+    pub fn down_args(&self) -> Vec<&'static str> {
+        let mut args = vec!["down", "--remove-orphans"];
+        if self.remove_volumes {
+            args.push("--volumes");
+        }
+        if self.remove_images {
+            args.extend(["--rmi", "all"]);
+        }
+        args
+    }
+
+    /// Every Docker object the teardown deletes, labelled by kind.
+    pub fn deletions(&self) -> Vec<String> {
+        let mut items: Vec<String> = self
+            .containers
+            .iter()
+            .map(|name| format!("container {}", name))
+            .chain(self.networks.iter().map(|name| format!("network {}", name)))
+            .collect();
+        if self.remove_volumes {
+            items.extend(self.volumes.iter().map(|name| format!("volume {}", name)));
+        }
+        if self.remove_images {
+            items.extend(self.images.iter().map(|name| format!("image {}", name)));
+        }
+        items
+    }
+
+    pub fn toggle(&mut self, option: UninstallOption) {
+        match option {
+            UninstallOption::RemoveVolumes => self.remove_volumes = !self.remove_volumes,
+            UninstallOption::RemoveImages => self.remove_images = !self.remove_images,
+            UninstallOption::Confirm | UninstallOption::Back => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"{
+        "name": "identity",
+        "services": {
+            "identity": { "container_name": "identity", "image": "ghcr.io/nexusquantum/nqrust-identity:latest" },
+            "postgres": { "container_name": "identity-db", "image": "postgres:16-alpine" }
+        },
+        "networks": { "default": { "name": "identity_default" } },
+        "volumes": { "identity_pgdata": { "name": "identity_identity_pgdata" } }
+    }"#;
+
+    #[test]
+    fn test_plan_keeps_data_unless_requested() {
+        let mut plan = UninstallPlan::from_project(&ComposeProject::parse(CONFIG).unwrap());
+
+        assert_eq!(plan.down_args(), vec!["down", "--remove-orphans"]);
+        assert_eq!(
+            plan.deletions(),
+            vec![
+                "container identity",
+                "container identity-db",
+                "network identity_default"
+            ]
+        );
+
+        plan.toggle(UninstallOption::RemoveVolumes);
+        plan.toggle(UninstallOption::RemoveImages);
+        assert_eq!(
+            plan.down_args(),
+            vec!["down", "--remove-orphans", "--volumes", "--rmi", "all"]
+        );
+        assert!(
+            plan.deletions()
+                .contains(&"volume identity_identity_pgdata".to_string())
+        );
+        assert!(
+            plan.deletions()
+                .contains(&"image postgres:16-alpine".to_string())
+        );
+    }
+}
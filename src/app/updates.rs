@@ -1,46 +1,48 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 use chrono::{DateTime, Duration, Utc};
 use color_eyre::eyre::{Result, bail};
-use reqwest::{Client, StatusCode};
+use reqwest::{Client, RequestBuilder, Response, StatusCode, header};
 use semver::Version;
 use serde::Deserialize;
-use tokio::process::Command;
+
+use super::compose::{ComposeProject, image_repository, image_tag};
+use crate::docker::DockerClient;
+use crate::registry::RegistryCredentials;
 
 const OWNER: &str = "NexusQuantum";
+const GITHUB_API_URL: &str = "https://api.github.com";
+const GHCR_REGISTRY: &str = "ghcr.io";
+const DOCKER_HUB_REGISTRY: &str = "registry-1.docker.io";
+const DOCKER_HUB_API_URL: &str = "https://hub.docker.com";
+/// Pages followed before a listing is cut short, in case a registry keeps
+/// linking to more.
+const MAX_PAGES: usize = 100;
 
-struct ServiceConfig {
-    pub display_name: &'static str,
-    pub image: &'static str,
-    pub package: &'static str,
-    pub current_tag: &'static str,
-}
-
-const SERVICE_CONFIGS: &[ServiceConfig] = &[
-    ServiceConfig {
-        display_name: "PostgreSQL Database",
-        image: "postgres",
-        package: "postgres",
-        current_tag: "16-alpine",
-    },
-    ServiceConfig {
-        display_name: "NQRust Identity (Keycloak)",
-        image: "ghcr.io/nexusquantum/nqrust-identity",
-        package: "nqrust-identity",
-        current_tag: "latest",
-    },
-];
+/// Media types accepted when resolving a tag, so the registry answers with the
+/// digest of the multi-arch index `docker pull` records in `RepoDigests`.
+const MANIFEST_MEDIA_TYPES: &str = "application/vnd.oci.image.index.v1+json, \
+    application/vnd.docker.distribution.manifest.list.v2+json, \
+    application/vnd.oci.image.manifest.v1+json, \
+    application/vnd.docker.distribution.manifest.v2+json";
 
 #[derive(Debug, Clone)]
 pub struct UpdateInfo {
     pub display_name: String,
+    /// Compose service running the image; `None` for the installer itself.
+    pub service: Option<String>,
     pub image: String,
     pub current_tag: String,
     pub available_tags: Vec<String>,
+    pub env_key: Option<String>,
     pub latest_release_tag: Option<String>,
     pub latest_release_published: Option<DateTime<Utc>>,
     pub remote_latest_updated: Option<DateTime<Utc>>,
     pub local_created: Option<DateTime<Utc>>,
+    /// Manifest digest the registry currently serves for `current_tag`.
+    pub remote_digest: Option<String>,
+    /// `RepoDigests` of the local image tagged `current_tag`.
+    pub local_digests: Vec<String>,
     pub status_note: Option<String>,
     pub has_update: bool,
     pub is_self: bool,
@@ -49,16 +51,22 @@ pub struct UpdateInfo {
 }
 
 impl UpdateInfo {
-    fn new(config: &ServiceConfig) -> Self {
+    /// Entry for a compose service running `reference`. `env_key` is the
+    /// variable its `image:` is read from, which the tag picker writes.
+    fn for_service(service: &str, reference: &str, env_key: Option<&str>) -> Self {
         Self {
-            display_name: config.display_name.to_string(),
-            image: config.image.to_string(),
-            current_tag: config.current_tag.to_string(),
+            display_name: service.to_string(),
+            service: Some(service.to_string()),
+            image: image_repository(reference).to_string(),
+            current_tag: image_tag(reference).unwrap_or("latest").to_string(),
             available_tags: Vec::new(),
+            env_key: env_key.map(str::to_string),
             latest_release_tag: None,
             latest_release_published: None,
             remote_latest_updated: None,
             local_created: None,
+            remote_digest: None,
+            local_digests: Vec::new(),
             status_note: None,
             has_update: false,
             is_self: false,
@@ -67,8 +75,44 @@ impl UpdateInfo {
         }
     }
 
+    /// Tags offered by the tag picker: `latest` followed by semver tags,
+    /// newest first.
+    pub fn selectable_tags(&self) -> Vec<String> {
+        let mut semver_tags: Vec<(Version, &String)> = self
+            .available_tags
+            .iter()
+            .filter_map(|tag| {
+                Version::parse(tag.trim_start_matches('v'))
+                    .ok()
+                    .map(|version| (version, tag))
+            })
+            .collect();
+        semver_tags.sort_by(|a, b| b.0.cmp(&a.0));
+
+        let latest = self
+            .available_tags
+            .iter()
+            .filter(|tag| tag.as_str() == "latest");
+        latest
+            .chain(semver_tags.into_iter().map(|(_, tag)| tag))
+            .cloned()
+            .collect()
+    }
+
+    pub fn reference_for(&self, tag: &str) -> String {
+        format!("{}:{}", self.image, tag)
+    }
+
+    /// Compares the registry's digest for the tag with the local image's
+    /// `RepoDigests`. Falls back to comparing timestamps when the registry
+    /// digest could not be resolved.
     pub fn recompute_status(&mut self) {
-        if let Some(remote) = self.remote_latest_updated {
+        if let Some(remote) = &self.remote_digest {
+            self.has_update = !self
+                .local_digests
+                .iter()
+                .any(|local| local.rsplit('@').next() == Some(remote.as_str()));
+        } else if let Some(remote) = self.remote_latest_updated {
             match self.local_created {
                 Some(local) => {
                     let delta = remote - local;
@@ -83,13 +127,21 @@ impl UpdateInfo {
         }
     }
 
-    pub fn apply_local_created(&mut self, created: Option<DateTime<Utc>>) {
-        self.local_created = created;
+    pub fn apply_local_image(&mut self, image: Option<LocalImage>) {
+        let image = image.unwrap_or_default();
+        self.local_created = image.created;
+        self.local_digests = image.repo_digests;
         self.recompute_status();
     }
 
+    /// Whether the status came from digests rather than the timestamp
+    /// fallback.
+    pub fn compared_by_digest(&self) -> bool {
+        self.remote_digest.is_some()
+    }
+
     pub fn pull_reference(&self) -> String {
-        format!("{}:{}", self.image, self.current_tag)
+        self.reference_for(&self.current_tag)
     }
 
     pub fn append_status(&mut self, message: &str) {
@@ -97,14 +149,35 @@ impl UpdateInfo {
     }
 
     pub fn clear_local_error(&mut self) {
-        if let Some(note) = &self.status_note {
-            if note.contains("Failed to inspect local image") {
-                self.status_note = None;
-            }
+        if let Some(note) = &self.status_note
+            && note.contains("Failed to inspect local image")
+        {
+            self.status_note = None;
         }
     }
 }
 
+/// What `docker image inspect` reports about a local image.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct LocalImage {
+    #[serde(rename = "Created", default, deserialize_with = "deserialize_created")]
+    pub created: Option<DateTime<Utc>>,
+    #[serde(rename = "RepoDigests", default)]
+    pub repo_digests: Vec<String>,
+}
+
+fn deserialize_created<'de, D>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = Option::<String>::deserialize(deserializer)?;
+    Ok(value.and_then(|value| {
+        DateTime::parse_from_rfc3339(&value)
+            .map(|dt| dt.with_timezone(&Utc))
+            .ok()
+    }))
+}
+
 #[derive(Debug, Deserialize)]
 struct PackageVersion {
     #[serde(default)]
@@ -140,28 +213,59 @@ struct ContainerMetadata {
     tags: Option<Vec<String>>,
 }
 
-pub async fn collect_update_infos(client: &Client, token: Option<&str>) -> Result<Vec<UpdateInfo>> {
+/// Checks every compose service that runs a registry image (locally built
+/// services are skipped), plus the installer itself. `image_variables` maps
+/// services to the variable their image is read from.
+pub async fn collect_update_infos(
+    client: &Client,
+    credentials: &RegistryCredentials,
+    project: &ComposeProject,
+    image_variables: &BTreeMap<String, String>,
+) -> Result<Vec<UpdateInfo>> {
     let mut infos = Vec::new();
 
-    for config in SERVICE_CONFIGS {
-        let mut info = UpdateInfo::new(config);
+    for service in project.pull_services() {
+        let Some(reference) = project.image(&service) else {
+            continue;
+        };
+        let env_key = image_variables.get(&service).map(String::as_str);
+        let mut info = UpdateInfo::for_service(&service, reference, env_key);
+
+        if reference.contains('@') {
+            info.append_status("Pinned by digest; edit the image reference to update");
+            infos.push(info);
+            continue;
+        }
+
+        let registry = Registry::for_image(&info.image);
+        match registry.list_tags(client, credentials).await {
+            Ok(Some(tags)) => apply_remote_tags(&mut info, tags),
+            Ok(None) => append_status(&mut info.status_note, "Image not found in its registry"),
+            Err(e) => append_status(
+                &mut info.status_note,
+                &format!("Failed to list tags: {}", e),
+            ),
+        }
 
-        match fetch_package_versions(client, OWNER, config.package, token).await? {
-            Some(versions) => apply_remote_versions(&mut info, versions),
-            None => append_status(
+        match registry
+            .manifest_digest(client, &info.current_tag, credentials)
+            .await
+        {
+            Ok(digest) => info.remote_digest = digest,
+            Err(e) => append_status(
                 &mut info.status_note,
-                "Package not found in GitHub Container Registry",
+                &format!("Digest lookup failed, comparing dates instead: {}", e),
             ),
         }
 
-        match inspect_local_image_created_at(config.image, config.current_tag).await {
-            Ok(created) => info.apply_local_created(created),
+        match inspect_local_image(&info.image, &info.current_tag).await {
+            Ok(image) => info.apply_local_image(image),
             Err(e) => {
                 append_status(
                     &mut info.status_note,
                     &format!("Failed to inspect local image: {}", e),
                 );
-                info.apply_local_created(None);
+                info.apply_local_image(None);
             }
         }
 
@@ -177,7 +281,7 @@ pub async fn collect_update_infos(client: &Client, token: Option<&str>) -> Resul
 
 async fn fetch_installer_update(client: &Client) -> Result<Option<UpdateInfo>> {
     let url = format!(
-        "https://api.github.com/repos/{owner}/installer-NQRust-Identity/releases/latest",
+        "{GITHUB_API_URL}/repos/{owner}/installer-NQRust-Identity/releases/latest",
         owner = OWNER
     );
 
@@ -217,13 +321,17 @@ async fn fetch_installer_update(client: &Client) -> Result<Option<UpdateInfo>> {
 
     let mut info = UpdateInfo {
         display_name: "Installer (self-update)".to_string(),
+        service: None,
         image: "installer".to_string(),
         current_tag: format!("v{}", env!("CARGO_PKG_VERSION")),
         available_tags: Vec::new(),
+        env_key: None,
         latest_release_tag: Some(release.tag_name.clone()),
         latest_release_published: release.published_at,
         remote_latest_updated: release.published_at,
         local_created: None,
+        remote_digest: None,
+        local_digests: Vec::new(),
         status_note: None,
         has_update: false,
         is_self: true,
@@ -238,30 +346,17 @@ async fn fetch_installer_update(client: &Client) -> Result<Option<UpdateInfo>> {
     Ok(Some(info))
 }
 
-fn apply_remote_versions(info: &mut UpdateInfo, versions: Vec<PackageVersion>) {
+fn apply_remote_tags(info: &mut UpdateInfo, remote_tags: Vec<RemoteTag>) {
     let mut tags = Vec::new();
     let mut seen = HashSet::new();
     let mut tag_dates: HashMap<String, DateTime<Utc>> = HashMap::new();
 
-    for version in versions {
-        let timestamp = version.updated_at.or(version.created_at);
-        let Some(metadata) = version.metadata else {
-            continue;
-        };
-        let Some(container) = metadata.container else {
-            continue;
-        };
-        let Some(version_tags) = container.tags else {
-            continue;
-        };
-
-        for tag in version_tags {
-            if seen.insert(tag.clone()) {
-                tags.push(tag.clone());
-            }
-            if let Some(ts) = timestamp {
-                tag_dates.entry(tag.clone()).or_insert(ts);
-            }
+    for tag in remote_tags {
+        if seen.insert(tag.name.clone()) {
+            tags.push(tag.name.clone());
+        }
+        if let Some(ts) = tag.updated {
+            tag_dates.entry(tag.name).or_insert(ts);
         }
     }
 
@@ -273,7 +368,7 @@ fn apply_remote_versions(info: &mut UpdateInfo, versions: Vec<PackageVersion>) {
         info.latest_release_tag = Some(latest_tag);
     }
 
-    info.remote_latest_updated = tag_dates.get("latest").cloned();
+    info.remote_latest_updated = tag_dates.get(&info.current_tag).cloned();
     info.recompute_status();
 
     if info.available_tags.is_empty() {
@@ -281,6 +376,35 @@ fn apply_remote_versions(info: &mut UpdateInfo, versions: Vec<PackageVersion>) {
     }
 }
 
+/// Popup on the update page for choosing which tag a service runs.
+#[derive(Debug, Clone)]
+pub struct TagPicker {
+    pub service: String,
+    pub current_tag: String,
+    pub tags: Vec<String>,
+    pub selected: usize,
+}
+
+impl TagPicker {
+    pub fn new(info: &UpdateInfo) -> Self {
+        let tags = info.selectable_tags();
+        let selected = tags
+            .iter()
+            .position(|tag| *tag == info.current_tag)
+            .unwrap_or(0);
+        Self {
+            service: info.display_name.clone(),
+            current_tag: info.current_tag.clone(),
+            tags,
+            selected,
+        }
+    }
+
+    pub fn selected_tag(&self) -> Option<&str> {
+        self.tags.get(self.selected).map(String::as_str)
+    }
+}
+
 fn determine_latest_release_tag(tags: &[String]) -> Option<String> {
     let mut semver_tags: Vec<(Version, String)> = tags
         .iter()
@@ -302,33 +426,41 @@ fn determine_latest_release_tag(tags: &[String]) -> Option<String> {
 
 async fn fetch_package_versions(
     client: &Client,
+    api_url: &str,
     owner: &str,
     package: &str,
     token: Option<&str>,
 ) -> Result<Option<Vec<PackageVersion>>> {
     let endpoints = [
-        format!(
-            "https://api.github.com/orgs/{owner}/packages/container/{package}/versions?per_page=100"
-        ),
-        format!(
-            "https://api.github.com/users/{owner}/packages/container/{package}/versions?per_page=100"
-        ),
+        format!("{api_url}/orgs/{owner}/packages/container/{package}/versions?per_page=100"),
+        format!("{api_url}/users/{owner}/packages/container/{package}/versions?per_page=100"),
     ];
 
-    for url in endpoints {
-        let mut request = client
-            .get(&url)
+    let get = |url: &str| {
+        let request = client
+            .get(url)
             .header("User-Agent", "nqrust-identity")
             .header("Accept", "application/vnd.github+json");
-
-        if let Some(token) = token {
-            request = request.header("Authorization", format!("Bearer {}", token));
+        match token {
+            Some(token) => request.header("Authorization", format!("Bearer {}", token)),
+            None => request,
         }
+    };
 
-        let response = request.send().await?;
+    for url in endpoints {
+        let response = get(&url).send().await?;
         match response.status() {
             StatusCode::OK => {
-                let data = response.json::<Vec<PackageVersion>>().await?;
+                let mut next = next_page(&response);
+                let mut data = response.json::<Vec<PackageVersion>>().await?;
+                for _ in 1..MAX_PAGES {
+                    let Some(url) = next else {
+                        break;
+                    };
+                    let response = get(&url).send().await?.error_for_status()?;
+                    next = next_page(&response);
+                    data.extend(response.json::<Vec<PackageVersion>>().await?);
+                }
                 return Ok(Some(data));
             }
             StatusCode::NOT_FOUND => continue,
@@ -350,33 +482,411 @@ async fn fetch_package_versions(
     Ok(None)
 }
 
-async fn inspect_local_image_created_at(image: &str, tag: &str) -> Result<Option<DateTime<Utc>>> {
-    let reference = format!("{}:{}", image, tag);
-    let output = Command::new("docker")
-        .args(["image", "inspect", &reference, "--format", "{{.Created}}"])
-        .output()
-        .await?;
+/// The `rel="next"` target of the response's `Link` header, which paginated
+/// GitHub and registry listings use to point at their next page.
+fn next_page(response: &Response) -> Option<String> {
+    let links = response.headers().get(header::LINK)?.to_str().ok()?;
+    links.split(',').find_map(|link| {
+        let (target, params) = link.split_once(';')?;
+        let is_next = params
+            .split(';')
+            .any(|param| matches!(param.trim(), r#"rel="next""# | "rel=next"));
+        if !is_next {
+            return None;
+        }
+        let target = target.trim().strip_prefix('<')?.strip_suffix('>')?;
+        response.url().join(target).ok().map(String::from)
+    })
+}
 
-    if !output.status.success() {
-        return Ok(None);
+/// Registry API host and repository path for an image name, following
+/// Docker's rules: no registry host means Docker Hub, and single-name Hub
+/// images live under `library/`.
+fn registry_location(image: &str) -> (String, String) {
+    let (first, rest) = image.split_once('/').unwrap_or(("", image));
+    let is_host = first.contains('.') || first.contains(':') || first == "localhost";
+
+    if is_host && first != "docker.io" {
+        return (first.to_string(), rest.to_string());
     }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let value = stdout.trim();
+    let name = if is_host { rest } else { image };
+    let repository = if name.contains('/') {
+        name.to_string()
+    } else {
+        format!("library/{}", name)
+    };
+    (DOCKER_HUB_REGISTRY.to_string(), repository)
+}
+
+/// How a registry is queried for tags; manifests always go through the OCI
+/// distribution API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegistryKind {
+    /// ghcr.io: tags and their dates come from GitHub's packages API.
+    Ghcr,
+    /// Docker Hub: tags and their dates come from hub.docker.com.
+    DockerHub,
+    /// Any other registry: tags come from `/v2/<name>/tags/list`, undated.
+    Oci,
+}
 
-    if value.is_empty() {
-        return Ok(None);
+/// A repository in a container registry.
+#[derive(Debug, Clone)]
+pub struct Registry {
+    pub kind: RegistryKind,
+    /// Base URL of the OCI distribution API, e.g. `https://ghcr.io`.
+    registry_url: String,
+    /// Base URL of the vendor API that lists tags with dates.
+    api_url: String,
+    /// Repository path inside the registry, e.g. `library/postgres`.
+    repository: String,
+}
+
+/// A tag as reported by a registry, with the time it was last pushed when the
+/// registry tells.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RemoteTag {
+    name: String,
+    updated: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OciTagList {
+    #[serde(default)]
+    tags: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HubTagPage {
+    #[serde(default)]
+    results: Vec<HubTag>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HubTag {
+    name: String,
+    #[serde(default)]
+    last_updated: Option<DateTime<Utc>>,
+}
+
+impl Registry {
+    pub fn for_image(image: &str) -> Self {
+        let (host, repository) = registry_location(image);
+        let (kind, api_url) = match host.as_str() {
+            GHCR_REGISTRY => (RegistryKind::Ghcr, GITHUB_API_URL.to_string()),
+            DOCKER_HUB_REGISTRY => (RegistryKind::DockerHub, DOCKER_HUB_API_URL.to_string()),
+            _ => (RegistryKind::Oci, format!("https://{host}")),
+        };
+
+        Self {
+            kind,
+            registry_url: format!("https://{host}"),
+            api_url,
+            repository,
+        }
+    }
+
+    pub fn host(&self) -> &str {
+        self.registry_url
+            .split_once("://")
+            .map_or(self.registry_url.as_str(), |(_, host)| host)
+    }
+
+    /// Lists the repository's tags, or `None` when the repository does not
+    /// exist.
+    async fn list_tags(
+        &self,
+        client: &Client,
+        credentials: &RegistryCredentials,
+    ) -> Result<Option<Vec<RemoteTag>>> {
+        match (self.kind, credentials.github_token()) {
+            // The packages API needs a token even for public packages.
+            (RegistryKind::Ghcr, Some(token)) => self.list_ghcr_tags(client, token).await,
+            (RegistryKind::DockerHub, _) => self.list_hub_tags(client).await,
+            (RegistryKind::Ghcr | RegistryKind::Oci, _) => {
+                self.list_oci_tags(client, credentials).await
+            }
+        }
+    }
+
+    async fn list_ghcr_tags(&self, client: &Client, token: &str) -> Result<Option<Vec<RemoteTag>>> {
+        let Some((owner, package)) = self.repository.split_once('/') else {
+            return Ok(None);
+        };
+        let package = package.replace('/', "%2F");
+        let Some(versions) =
+            fetch_package_versions(client, &self.api_url, owner, &package, Some(token)).await?
+        else {
+            return Ok(None);
+        };
+
+        let mut tags = Vec::new();
+        for version in versions {
+            let updated = version.updated_at.or(version.created_at);
+            let names = version
+                .metadata
+                .and_then(|metadata| metadata.container)
+                .and_then(|container| container.tags)
+                .unwrap_or_default();
+            tags.extend(names.into_iter().map(|name| RemoteTag { name, updated }));
+        }
+        Ok(Some(tags))
     }
 
-    let created = DateTime::parse_from_rfc3339(value)
-        .map(|dt| dt.with_timezone(&Utc))
-        .ok();
+    async fn list_hub_tags(&self, client: &Client) -> Result<Option<Vec<RemoteTag>>> {
+        let url = format!(
+            "{}/v2/repositories/{}/tags?page_size=100&ordering=last_updated",
+            self.api_url, self.repository
+        );
+        let response = client
+            .get(&url)
+            .header("User-Agent", "nqrust-identity")
+            .send()
+            .await?;
 
-    Ok(created)
+        match response.status() {
+            StatusCode::OK => {
+                let page: HubTagPage = response.json().await?;
+                Ok(Some(
+                    page.results
+                        .into_iter()
+                        .map(|tag| RemoteTag {
+                            name: tag.name,
+                            updated: tag.last_updated,
+                        })
+                        .collect(),
+                ))
+            }
+            StatusCode::NOT_FOUND => Ok(None),
+            status => bail!("Docker Hub answered {status} for {}", self.repository),
+        }
+    }
+
+    async fn list_oci_tags(
+        &self,
+        client: &Client,
+        credentials: &RegistryCredentials,
+    ) -> Result<Option<Vec<RemoteTag>>> {
+        let mut url = format!("{}/v2/{}/tags/list", self.registry_url, self.repository);
+        let mut tags = Vec::new();
+        for _ in 0..MAX_PAGES {
+            let response = self
+                .send_authorized(client, credentials, || client.get(&url))
+                .await?;
+            match response.status() {
+                StatusCode::OK => {}
+                StatusCode::NOT_FOUND => return Ok(None),
+                status => bail!("{} answered {status} for {}", self.host(), self.repository),
+            }
+
+            let next = next_page(&response);
+            let list: OciTagList = response.json().await?;
+            tags.extend(
+                list.tags
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|name| RemoteTag {
+                        name,
+                        updated: None,
+                    }),
+            );
+            match next {
+                Some(next) => url = next,
+                None => break,
+            }
+        }
+        Ok(Some(tags))
+    }
+
+    /// Resolves `tag` to the manifest digest the registry serves for it.
+    /// Returns `None` when the tag does not exist.
+    pub(super) async fn manifest_digest(
+        &self,
+        client: &Client,
+        tag: &str,
+        credentials: &RegistryCredentials,
+    ) -> Result<Option<String>> {
+        let url = format!(
+            "{}/v2/{}/manifests/{tag}",
+            self.registry_url, self.repository
+        );
+        let response = self
+            .send_authorized(client, credentials, || {
+                client
+                    .head(&url)
+                    .header(header::ACCEPT, MANIFEST_MEDIA_TYPES)
+            })
+            .await?;
+
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(None),
+            status if status.is_success() => Ok(response
+                .headers()
+                .get("Docker-Content-Digest")
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)),
+            status @ (StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) => {
+                Err(self.denied(status).into())
+            }
+            status => bail!(
+                "{} answered {status} for {}:{tag}",
+                self.host(),
+                self.repository
+            ),
+        }
+    }
+
+    /// Sends a registry request, answering a bearer challenge with a pull
+    /// token from the registry's token service. The configured credentials
+    /// are sent to the token service when it belongs to their registry.
+    async fn send_authorized(
+        &self,
+        client: &Client,
+        credentials: &RegistryCredentials,
+        request: impl Fn() -> RequestBuilder,
+    ) -> Result<Response> {
+        let response = request()
+            .header("User-Agent", "nqrust-identity")
+            .send()
+            .await?;
+        if response.status() != StatusCode::UNAUTHORIZED {
+            return Ok(response);
+        }
+
+        let challenge = response
+            .headers()
+            .get(header::WWW_AUTHENTICATE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_bearer_challenge);
+        let Some(challenge) = challenge else {
+            bail!(
+                "{} asked for authentication without a bearer challenge",
+                self.host()
+            );
+        };
+
+        let mut token_request = client
+            .get(&challenge.realm)
+            .header("User-Agent", "nqrust-identity");
+        if let Some(service) = &challenge.service {
+            token_request = token_request.query(&[("service", service)]);
+        }
+        let scope = challenge
+            .scope
+            .unwrap_or_else(|| format!("repository:{}:pull", self.repository));
+        token_request = token_request.query(&[("scope", scope)]);
+        if let Some((username, secret)) = credentials.basic_auth_for(self.host()) {
+            token_request = token_request.basic_auth(username, Some(secret));
+        }
+
+        let token_response = token_request.send().await?;
+        let status = token_response.status();
+        if matches!(status, StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) {
+            return Err(self.denied(status).into());
+        }
+        let registry_token: RegistryToken = token_response.error_for_status()?.json().await?;
+        Ok(request()
+            .header("User-Agent", "nqrust-identity")
+            .bearer_auth(registry_token.token)
+            .send()
+            .await?)
+    }
+
+    fn denied(&self, status: StatusCode) -> RegistryDenied {
+        RegistryDenied {
+            host: self.host().to_string(),
+            repository: self.repository.clone(),
+            status,
+        }
+    }
+}
+
+/// The registry refused the credentials access to a repository, as opposed
+/// to being unreachable or failing.
+#[derive(Debug)]
+pub(super) struct RegistryDenied {
+    host: String,
+    repository: String,
+    status: StatusCode,
 }
 
-pub async fn get_local_image_created(image: &str, tag: &str) -> Result<Option<DateTime<Utc>>> {
-    inspect_local_image_created_at(image, tag).await
+impl std::fmt::Display for RegistryDenied {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} denied access to {} ({})",
+            self.host, self.repository, self.status
+        )
+    }
+}
+
+impl std::error::Error for RegistryDenied {}
+
+/// The `realm`, `service` and `scope` of a `WWW-Authenticate: Bearer ...`
+/// challenge.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct BearerChallenge {
+    realm: String,
+    service: Option<String>,
+    scope: Option<String>,
+}
+
+fn parse_bearer_challenge(value: &str) -> Option<BearerChallenge> {
+    let mut rest = value.strip_prefix("Bearer ")?;
+    let mut challenge = BearerChallenge::default();
+
+    // `key=value` or `key="value"` pairs; quoted values may contain commas
+    // and backslash-escaped quotes.
+    loop {
+        rest = rest.trim_start_matches([' ', '\t', ',']);
+        let Some((key, after)) = rest.split_once('=') else {
+            break;
+        };
+        let value;
+        (value, rest) = match after.strip_prefix('"') {
+            Some(quoted) => unquote(quoted)?,
+            None => {
+                let (value, after) = after.split_once(',').unwrap_or((after, ""));
+                (value.trim().to_string(), after)
+            }
+        };
+        match key.trim() {
+            "realm" => challenge.realm = value,
+            "service" => challenge.service = Some(value),
+            "scope" => challenge.scope = Some(value),
+            _ => {}
+        }
+    }
+
+    (!challenge.realm.is_empty()).then_some(challenge)
+}
+
+/// Splits a quoted string, opening quote already stripped, into its
+/// unescaped value and what follows the closing quote.
+fn unquote(quoted: &str) -> Option<(String, &str)> {
+    let mut value = String::new();
+    let mut chars = quoted.char_indices();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '"' => return Some((value, &quoted[index + 1..])),
+            '\\' => value.push(chars.next()?.1),
+            c => value.push(c),
+        }
+    }
+    None
+}
+
+#[derive(Debug, Deserialize)]
+struct RegistryToken {
+    #[serde(alias = "access_token")]
+    token: String,
+}
+
+/// Inspects the local `image:tag`; `None` when it has not been pulled.
+pub async fn inspect_local_image(image: &str, tag: &str) -> Result<Option<LocalImage>> {
+    let reference = format!("{}:{}", image, tag);
+    Ok(DockerClient::from_env()?.inspect_image(&reference).await?)
 }
 
 fn append_status(target: &mut Option<String>, message: &str) {
@@ -390,3 +900,260 @@ fn append_status(target: &mut Option<String>, message: &str) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::RegistryConfig;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Serves each connection with `handler(base_url, request_head)`, which
+    /// returns `(status, headers, body)`.
+    async fn mock_server<F>(handler: F) -> String
+    where
+        F: Fn(&str, &str) -> (u16, Vec<(&'static str, String)>, String) + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let server_base = base.clone();
+
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut head = Vec::new();
+                let mut buf = [0u8; 1024];
+                while !head.ends_with(b"\r\n\r\n") {
+                    let n = stream.read(&mut buf).await.unwrap();
+                    if n == 0 {
+                        break;
+                    }
+                    head.extend_from_slice(&buf[..n]);
+                }
+
+                let head = String::from_utf8_lossy(&head);
+                let (status, headers, body) = handler(&server_base, &head);
+                let mut response = format!(
+                    "HTTP/1.1 {status} Mock\r\nContent-Length: {}\r\nConnection: close\r\n",
+                    body.len()
+                );
+                for (name, value) in headers {
+                    response.push_str(&format!("{name}: {value}\r\n"));
+                }
+                response.push_str("\r\n");
+                if !head.starts_with("HEAD ") {
+                    response.push_str(&body);
+                }
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+
+        base
+    }
+
+    fn mock_registry(kind: RegistryKind, base: &str, repository: &str) -> Registry {
+        Registry {
+            kind,
+            registry_url: base.to_string(),
+            api_url: base.to_string(),
+            repository: repository.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_registries_against_mock_server() {
+        let base = mock_server(|base, head| {
+            let path = head.split_whitespace().nth(1).unwrap_or_default();
+            let authorized = head.contains("authorization: Bearer pull-token");
+
+            if path.starts_with("/orgs/nexusquantum/packages/container/nqrust-identity/versions") {
+                let body = r#"[
+                    { "updated_at": "2026-05-01T10:00:00Z",
+                      "metadata": { "container": { "tags": ["1.2.0", "latest"] } } },
+                    { "updated_at": "2026-03-01T10:00:00Z",
+                      "metadata": { "container": { "tags": ["1.1.0"] } } }
+                ]"#;
+                (200, vec![], body.to_string())
+            } else if path.starts_with("/v2/repositories/library/postgres/tags") {
+                let body = r#"{ "results": [
+                    { "name": "16-alpine", "last_updated": "2026-06-01T00:00:00Z" },
+                    { "name": "17-alpine", "last_updated": "2026-06-02T00:00:00Z" }
+                ] }"#;
+                (200, vec![], body.to_string())
+            } else if path.starts_with("/token?")
+                && path.contains("mirror")
+                && !head.contains("authorization: Basic ")
+            {
+                (401, vec![], String::new())
+            } else if path.starts_with("/token?") {
+                (200, vec![], r#"{ "token": "pull-token" }"#.to_string())
+            } else if !authorized && path.starts_with("/v2/") {
+                let challenge = format!(r#"Bearer realm="{base}/token",service="mock""#);
+                (401, vec![("WWW-Authenticate", challenge)], String::new())
+            } else if path == "/v2/team/app/tags/list" || path == "/v2/mirror/app/tags/list" {
+                let next = format!(r#"<{path}?n=2&last=edge>; rel="next""#);
+                (
+                    200,
+                    vec![("Link", next)],
+                    r#"{ "name": "team/app", "tags": ["2.0.0", "edge"] }"#.to_string(),
+                )
+            } else if path.ends_with("/tags/list?n=2&last=edge") {
+                (200, vec![], r#"{ "tags": ["2.1.0"] }"#.to_string())
+            } else if path == "/v2/library/postgres/manifests/16-alpine" {
+                (
+                    200,
+                    vec![("Docker-Content-Digest", "sha256:pg16".to_string())],
+                    String::new(),
+                )
+            } else {
+                (404, vec![], String::new())
+            }
+        })
+        .await;
+        let client = Client::new();
+        let anonymous = RegistryCredentials::default();
+        let github = RegistryConfig::default().credentials(Some("ghp_test"));
+
+        let ghcr = mock_registry(RegistryKind::Ghcr, &base, "nexusquantum/nqrust-identity");
+        let tags = ghcr.list_tags(&client, &github).await.unwrap().unwrap();
+        let mut info = UpdateInfo::for_service(
+            "identity",
+            "ghcr.io/nexusquantum/nqrust-identity:latest",
+            None,
+        );
+        apply_remote_tags(&mut info, tags);
+        assert_eq!(info.latest_release_tag.as_deref(), Some("1.2.0"));
+        assert_eq!(info.available_tags, vec!["1.1.0", "1.2.0", "latest"]);
+        assert!(info.remote_latest_updated.is_some());
+
+        let hub = mock_registry(RegistryKind::DockerHub, &base, "library/postgres");
+        let tags = hub.list_tags(&client, &anonymous).await.unwrap().unwrap();
+        assert_eq!(tags.len(), 2);
+        assert!(tags.iter().all(|tag| tag.updated.is_some()));
+        assert_eq!(
+            hub.manifest_digest(&client, "16-alpine", &anonymous)
+                .await
+                .unwrap(),
+            Some("sha256:pg16".to_string())
+        );
+        assert_eq!(
+            hub.manifest_digest(&client, "9-alpine", &anonymous)
+                .await
+                .unwrap(),
+            None
+        );
+
+        let oci = mock_registry(RegistryKind::Oci, &base, "team/app");
+        let tags = oci.list_tags(&client, &anonymous).await.unwrap().unwrap();
+        assert_eq!(
+            tags.iter().map(|tag| tag.name.as_str()).collect::<Vec<_>>(),
+            vec!["2.0.0", "edge", "2.1.0"]
+        );
+
+        let mirror = mock_registry(RegistryKind::Oci, &base, "mirror/app");
+        assert!(mirror.list_tags(&client, &anonymous).await.is_err());
+        let robot = RegistryCredentials {
+            host: mirror.host().to_string(),
+            username: Some("robot-installer".to_string()),
+            secret: Some("secret".to_string()),
+        };
+        let tags = mirror.list_tags(&client, &robot).await.unwrap().unwrap();
+        assert_eq!(tags.len(), 3);
+    }
+
+    #[test]
+    fn test_service_entry_and_selectable_tags() {
+        let mut info = UpdateInfo::for_service(
+            "identity",
+            "ghcr.io/nexusquantum/nqrust-identity:1.9.1",
+            Some("KEYCLOAK_IMAGE"),
+        );
+        info.available_tags = ["1.2.0", "latest", "v1.10.0", "sha-abc123", "1.9.1"]
+            .iter()
+            .map(|tag| tag.to_string())
+            .collect();
+
+        assert_eq!(
+            info.selectable_tags(),
+            vec!["latest", "v1.10.0", "1.9.1", "1.2.0"]
+        );
+
+        assert_eq!(info.image, "ghcr.io/nexusquantum/nqrust-identity");
+        assert_eq!(info.current_tag, "1.9.1");
+        assert_eq!(info.env_key.as_deref(), Some("KEYCLOAK_IMAGE"));
+        assert_eq!(
+            info.reference_for("1.10.0"),
+            "ghcr.io/nexusquantum/nqrust-identity:1.10.0"
+        );
+        assert_eq!(
+            UpdateInfo::for_service("cache", "redis", None).pull_reference(),
+            "redis:latest"
+        );
+    }
+
+    #[test]
+    fn test_digest_comparison_overrides_dates() {
+        let mut info = UpdateInfo::for_service("postgres", "postgres:16-alpine", None);
+        info.remote_latest_updated = Some(Utc::now());
+        info.remote_digest = Some("sha256:aaaa".to_string());
+
+        let local: LocalImage = serde_json::from_str(
+            r#"{ "Created": "2020-01-01T00:00:00Z", "RepoDigests": ["postgres@sha256:aaaa"] }"#,
+        )
+        .unwrap();
+        info.apply_local_image(Some(local));
+        assert!(
+            !info.has_update,
+            "an old build with the same digest is current"
+        );
+
+        info.remote_digest = Some("sha256:bbbb".to_string());
+        info.recompute_status();
+        assert!(info.has_update);
+
+        info.remote_digest = None;
+        info.recompute_status();
+        assert!(info.has_update, "falls back to the creation dates");
+
+        assert_eq!(
+            registry_location("postgres"),
+            (
+                "registry-1.docker.io".to_string(),
+                "library/postgres".to_string()
+            )
+        );
+        assert_eq!(
+            registry_location("docker.io/bitnami/keycloak"),
+            (
+                "registry-1.docker.io".to_string(),
+                "bitnami/keycloak".to_string()
+            )
+        );
+        assert_eq!(
+            registry_location("ghcr.io/nexusquantum/nqrust-identity"),
+            (
+                "ghcr.io".to_string(),
+                "nexusquantum/nqrust-identity".to_string()
+            )
+        );
+        assert_eq!(
+            parse_bearer_challenge(
+                r#"Bearer realm="https://ghcr.io/token",service="ghcr.io",scope="repository:nexusquantum/nqrust-identity:pull""#
+            ),
+            Some(BearerChallenge {
+                realm: "https://ghcr.io/token".to_string(),
+                service: Some("ghcr.io".to_string()),
+                scope: Some("repository:nexusquantum/nqrust-identity:pull".to_string()),
+            })
+        );
+        assert_eq!(
+            parse_bearer_challenge(
+                r#"Bearer realm="https://registry.example/token", scope="repository:a:pull,push",service=registry"#
+            ),
+            Some(BearerChallenge {
+                realm: "https://registry.example/token".to_string(),
+                service: Some("registry".to_string()),
+                scope: Some("repository:a:pull,push".to_string()),
+            })
+        );
+    }
+}
@@ -0,0 +1,81 @@
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "nqrust-identity",
+    version,
+    about = "Interactive TUI installer for NQRust Identity (Keycloak) stack",
+    long_about = "Runs the interactive installer when started without a subcommand. \
+                  The subcommands drive the same installation logic without a terminal UI \
+                  so they can be used from Ansible, cloud-init or CI."
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Install (or re-apply) the Identity stack without the TUI
+    Install {
+        /// Do not ask for confirmation before starting the installation
+        #[arg(short, long)]
+        yes: bool,
+
+        /// Install offline from a bundle made by `bundle create`
+        #[arg(long, value_name = "PATH")]
+        from_bundle: Option<PathBuf>,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Pull every service image that has an update available
+    Update {
+        /// Only report available updates; exits with code 3 when any are found
+        #[arg(long)]
+        check: bool,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Show the state of the deployed services; exits with code 3 when any are not running
+    Status {
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Manage offline bundles for air-gapped installs
+    Bundle {
+        #[command(subcommand)]
+        command: BundleCommand,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum BundleCommand {
+    /// Save every stack image, the compose file and a digest manifest into one tarball
+    Create {
+        /// Where to write the bundle
+        #[arg(short, long, default_value = "nqrust-identity-bundle.tar.gz")]
+        file: PathBuf,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Args)]
+pub struct OutputArgs {
+    /// Output format for progress and results
+    #[arg(long, value_enum, default_value_t = OutputFormat::Plain)]
+    pub output: OutputFormat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human readable lines
+    Plain,
+    /// One JSON object per line
+    Json,
+}
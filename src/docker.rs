@@ -0,0 +1,1050 @@
+use std::env;
+use std::fmt;
+use std::io;
+#[cfg(unix)]
+use std::path::PathBuf;
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+#[cfg(unix)]
+use tokio::net::UnixStream;
+
+use crate::runtime::Runtime;
+use crate::secrets;
+
+/// Environment variable naming the daemon, as the docker CLI reads it.
+pub const HOST_ENV: &str = "DOCKER_HOST";
+const TLS_VERIFY_ENV: &str = "DOCKER_TLS_VERIFY";
+/// Podman's equivalent of `DOCKER_HOST`.
+const PODMAN_HOST_ENV: &str = "CONTAINER_HOST";
+/// API version spoken by Docker Engine 20.10; newer engines still accept it.
+const API_VERSION: &str = "v1.41";
+const RAW_STREAM: &str = "application/vnd.docker.raw-stream";
+const READ_SIZE: usize = 8192;
+const DOCKER_HUB_HOST: &str = "registry-1.docker.io";
+/// Key `docker login` uses for Docker Hub in `config.json`.
+const DOCKER_HUB_AUTH_KEY: &str = "https://index.docker.io/v1/";
+
+/// Why a Docker Engine API call failed.
+#[derive(Debug)]
+pub enum DockerError {
+    /// The daemon could not be reached.
+    Connect { endpoint: String, source: io::Error },
+    /// The connection broke while talking to the daemon.
+    Io(io::Error),
+    /// The daemon answered with an error status and message.
+    Api { status: u16, message: String },
+    /// A pull reported an error in its progress stream.
+    Pull(String),
+    /// The response was not what the API documents.
+    Decode(String),
+    /// `DOCKER_HOST` points at something this client cannot talk to.
+    Unsupported(String),
+}
+
+impl fmt::Display for DockerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DockerError::Connect { endpoint, source } => write!(
+                f,
+                "Cannot connect to the {} API at {}: {}. {}",
+                Runtime::current().name(),
+                endpoint,
+                source,
+                Runtime::current().start_hint()
+            ),
+            DockerError::Io(e) => write!(f, "Docker API connection failed: {}", e),
+            DockerError::Api { status, message } => {
+                write!(f, "Docker API returned {}: {}", status, message)
+            }
+            DockerError::Pull(message) => write!(f, "pull failed: {}", message),
+            DockerError::Decode(message) => {
+                write!(f, "unexpected Docker API response: {}", message)
+            }
+            DockerError::Unsupported(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for DockerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DockerError::Connect { source, .. } => Some(source),
+            DockerError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for DockerError {
+    fn from(e: io::Error) -> Self {
+        DockerError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for DockerError {
+    fn from(e: serde_json::Error) -> Self {
+        DockerError::Decode(e.to_string())
+    }
+}
+
+pub type DockerResult<T> = std::result::Result<T, DockerError>;
+
+/// Where the daemon listens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Endpoint {
+    #[cfg(unix)]
+    Unix(PathBuf),
+    /// Plain TCP `host:port`; TLS is not supported.
+    Tcp(String),
+}
+
+impl Endpoint {
+    /// Parses a `DOCKER_HOST` value such as `unix:///run/docker.sock` or
+    /// `tcp://127.0.0.1:2375`. Unix sockets only exist on unix hosts.
+    pub fn parse(host: &str) -> DockerResult<Self> {
+        #[cfg(unix)]
+        if let Some(path) = host.strip_prefix("unix://") {
+            return Ok(Endpoint::Unix(PathBuf::from(path)));
+        }
+        if let Some(address) = host.strip_prefix("tcp://") {
+            return Ok(Endpoint::Tcp(address.trim_end_matches('/').to_string()));
+        }
+        Err(DockerError::Unsupported(format!(
+            "{}={} is not supported; use a unix:// socket or plain tcp:// address",
+            HOST_ENV, host
+        )))
+    }
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(unix)]
+            Endpoint::Unix(path) => write!(f, "unix://{}", path.display()),
+            Endpoint::Tcp(address) => write!(f, "tcp://{}", address),
+        }
+    }
+}
+
+/// Which stream of a container a log or exec line was written to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamKind {
+    Stdout,
+    Stderr,
+}
+
+/// One message of the `POST /images/create` progress stream.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PullEvent {
+    #[serde(default)]
+    pub status: String,
+    /// Layer the event is about, as a 12 character short digest.
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(rename = "progressDetail", default)]
+    pub progress_detail: Option<ProgressDetail>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct ProgressDetail {
+    #[serde(default)]
+    pub current: u64,
+    #[serde(default)]
+    pub total: u64,
+}
+
+/// Credentials sent with a pull in the `X-Registry-Auth` header.
+#[derive(Debug, Clone)]
+pub struct RegistryAuth {
+    pub username: String,
+    pub password: String,
+    pub server: String,
+}
+
+impl RegistryAuth {
+    /// Credentials `docker login` or `podman login` stored for `server`
+    /// (see [`Runtime::auth_files`]). The daemon does not read those files
+    /// itself; the CLI sends them along with each pull.
+    pub fn stored(server: &str) -> Option<Self> {
+        Runtime::current().auth_files().iter().find_map(|path| {
+            let config: Value = serde_json::from_slice(&std::fs::read(path).ok()?).ok()?;
+            Self::from_config(&config, server, secrets::helper_credentials)
+        })
+    }
+
+    /// Looks `server` up the way the docker CLI does: its `credHelpers`
+    /// entry, then an inline `auths` entry, then the `credsStore` helper.
+    /// `helper(name, server_url)` asks a credential helper.
+    fn from_config(
+        config: &Value,
+        server: &str,
+        helper: impl Fn(&str, &str) -> Option<(String, String)>,
+    ) -> Option<Self> {
+        let keys = auth_keys(server);
+        let from_helper = |name: &str| {
+            keys.iter().find_map(|key| {
+                let (username, password) = helper(name, key)?;
+                // `<token>` marks an identity token, which the pull API does
+                // not take as a password.
+                (username != "<token>").then(|| Self {
+                    username,
+                    password,
+                    server: server.to_string(),
+                })
+            })
+        };
+
+        let cred_helper = config
+            .get("credHelpers")
+            .and_then(|helpers| keys.iter().find_map(|key| helpers.get(key)?.as_str()));
+        if let Some(name) = cred_helper {
+            return from_helper(name);
+        }
+        if let Some(auth) = config
+            .get("auths")
+            .and_then(|auths| Self::from_auths(auths, server))
+        {
+            return Some(auth);
+        }
+        let store = config
+            .get("credsStore")
+            .and_then(Value::as_str)
+            .filter(|store| !store.is_empty())?;
+        from_helper(store)
+    }
+
+    fn from_auths(auths: &Value, server: &str) -> Option<Self> {
+        let encoded = auth_keys(server)
+            .iter()
+            .find_map(|key| auths.get(key)?.get("auth")?.as_str())?;
+        let decoded = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .ok()?;
+        let (username, password) = std::str::from_utf8(&decoded).ok()?.split_once(':')?;
+        Some(Self {
+            username: username.to_string(),
+            password: password.to_string(),
+            server: server.to_string(),
+        })
+    }
+
+    fn header(&self) -> String {
+        let config = json!({
+            "username": self.username,
+            "password": self.password,
+            "serveraddress": self.server,
+        });
+        URL_SAFE.encode(config.to_string())
+    }
+}
+
+/// Keys `login` may have stored `server` under.
+fn auth_keys(server: &str) -> Vec<String> {
+    let mut keys = vec![server.to_string(), format!("https://{}", server)];
+    if server == DOCKER_HUB_HOST {
+        keys.push(DOCKER_HUB_AUTH_KEY.to_string());
+    }
+    keys
+}
+
+/// Output of a command run with `exec`.
+#[derive(Debug, Clone, Default)]
+pub struct ExecOutput {
+    pub exit_code: i64,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+trait Connection: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Connection for T {}
+
+enum Framing {
+    Chunked,
+    Length(usize),
+    UntilClose,
+}
+
+/// A response body, decoded from chunked or fixed-length framing as it is read.
+struct Body {
+    reader: BufReader<Box<dyn Connection>>,
+    framing: Framing,
+    buffer: Vec<u8>,
+    done: bool,
+}
+
+impl Body {
+    /// Reads the next piece of the body into `buffer`. Returns `false` at the end.
+    async fn fill(&mut self) -> DockerResult<bool> {
+        if self.done {
+            return Ok(false);
+        }
+        match &mut self.framing {
+            Framing::Chunked => {
+                let mut line = String::new();
+                self.reader.read_line(&mut line).await?;
+                let size = line.trim().split(';').next().unwrap_or_default();
+                let size = usize::from_str_radix(size, 16)
+                    .map_err(|_| DockerError::Decode(format!("bad chunk size {:?}", line)))?;
+                if size == 0 {
+                    // Trailers, if any, end with an empty line.
+                    loop {
+                        line.clear();
+                        if self.reader.read_line(&mut line).await? == 0 || line.trim().is_empty() {
+                            break;
+                        }
+                    }
+                    self.done = true;
+                    return Ok(false);
+                }
+                let start = self.buffer.len();
+                self.buffer.resize(start + size, 0);
+                self.reader.read_exact(&mut self.buffer[start..]).await?;
+                let mut crlf = [0; 2];
+                self.reader.read_exact(&mut crlf).await?;
+                Ok(true)
+            }
+            Framing::Length(remaining) => {
+                if *remaining == 0 {
+                    self.done = true;
+                    return Ok(false);
+                }
+                let mut chunk = vec![0; (*remaining).min(READ_SIZE)];
+                let read = self.reader.read(&mut chunk).await?;
+                if read == 0 {
+                    return Err(DockerError::Io(io::ErrorKind::UnexpectedEof.into()));
+                }
+                *remaining -= read;
+                self.buffer.extend_from_slice(&chunk[..read]);
+                Ok(true)
+            }
+            Framing::UntilClose => {
+                let mut chunk = vec![0; READ_SIZE];
+                let read = self.reader.read(&mut chunk).await?;
+                if read == 0 {
+                    self.done = true;
+                    return Ok(false);
+                }
+                self.buffer.extend_from_slice(&chunk[..read]);
+                Ok(true)
+            }
+        }
+    }
+
+    async fn next_line(&mut self) -> DockerResult<Option<String>> {
+        loop {
+            if let Some(end) = self.buffer.iter().position(|&byte| byte == b'\n') {
+                let line: Vec<u8> = self.buffer.drain(..=end).collect();
+                return Ok(Some(String::from_utf8_lossy(&line).trim_end().to_string()));
+            }
+            if !self.fill().await? {
+                if self.buffer.is_empty() {
+                    return Ok(None);
+                }
+                let line = std::mem::take(&mut self.buffer);
+                return Ok(Some(String::from_utf8_lossy(&line).trim_end().to_string()));
+            }
+        }
+    }
+
+    /// Exactly `count` bytes, or `None` when the body ended cleanly first.
+    async fn take(&mut self, count: usize) -> DockerResult<Option<Vec<u8>>> {
+        while self.buffer.len() < count {
+            if !self.fill().await? {
+                if self.buffer.is_empty() {
+                    return Ok(None);
+                }
+                return Err(DockerError::Decode("stream ended mid-frame".to_string()));
+            }
+        }
+        Ok(Some(self.buffer.drain(..count).collect()))
+    }
+
+    async fn bytes(mut self) -> DockerResult<Vec<u8>> {
+        while self.fill().await? {}
+        Ok(self.buffer)
+    }
+
+    /// Next frame of a log or exec stream. Multiplexed streams carry an
+    /// 8 byte header per frame: the stream (1 stdout, 2 stderr), three zero
+    /// bytes and the big-endian payload length.
+    async fn next_frame(
+        &mut self,
+        multiplexed: bool,
+    ) -> DockerResult<Option<(StreamKind, Vec<u8>)>> {
+        if !multiplexed {
+            if self.buffer.is_empty() && !self.fill().await? {
+                return Ok(None);
+            }
+            return Ok(Some((StreamKind::Stdout, std::mem::take(&mut self.buffer))));
+        }
+        let Some(header) = self.take(8).await? else {
+            return Ok(None);
+        };
+        let kind = if header[0] == 2 {
+            StreamKind::Stderr
+        } else {
+            StreamKind::Stdout
+        };
+        let length = u32::from_be_bytes([header[4], header[5], header[6], header[7]]) as usize;
+        let payload = self
+            .take(length)
+            .await?
+            .ok_or_else(|| DockerError::Decode("stream ended mid-frame".to_string()))?;
+        Ok(Some((kind, payload)))
+    }
+
+    /// Calls `on_line` with each complete line of a log or exec stream until
+    /// it ends or `on_line` returns `false`.
+    async fn for_each_line(
+        &mut self,
+        multiplexed: bool,
+        mut on_line: impl FnMut(StreamKind, String) -> bool,
+    ) -> DockerResult<()> {
+        let mut pending: [Vec<u8>; 2] = [Vec::new(), Vec::new()];
+        while let Some((kind, payload)) = self.next_frame(multiplexed).await? {
+            let partial = &mut pending[kind as usize];
+            partial.extend_from_slice(&payload);
+            while let Some(end) = partial.iter().position(|&byte| byte == b'\n') {
+                let line: Vec<u8> = partial.drain(..=end).collect();
+                if !on_line(kind, String::from_utf8_lossy(&line).trim_end().to_string()) {
+                    return Ok(());
+                }
+            }
+        }
+        for (kind, rest) in [StreamKind::Stdout, StreamKind::Stderr]
+            .into_iter()
+            .zip(pending)
+        {
+            if !rest.is_empty() && !on_line(kind, String::from_utf8_lossy(&rest).to_string()) {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Response {
+    content_type: Option<String>,
+    body: Body,
+}
+
+impl Response {
+    fn is_multiplexed(&self) -> bool {
+        self.content_type.as_deref() != Some(RAW_STREAM)
+    }
+
+    async fn json<T: DeserializeOwned>(self) -> DockerResult<T> {
+        Ok(serde_json::from_slice(&self.body.bytes().await?)?)
+    }
+}
+
+/// Talks to the Docker Engine API over its socket (or `DOCKER_HOST`), so
+/// image, container, log and exec calls return typed results instead of CLI
+/// output. Each call uses its own connection.
+#[derive(Debug, Clone)]
+pub struct DockerClient {
+    endpoint: Endpoint,
+}
+
+impl DockerClient {
+    pub fn new(endpoint: Endpoint) -> Self {
+        Self { endpoint }
+    }
+
+    /// The daemon named by `DOCKER_HOST` (or `CONTAINER_HOST` with Podman),
+    /// or the runtime's socket. Podman serves the same API through its
+    /// compatibility layer.
+    pub fn from_env() -> DockerResult<Self> {
+        if env::var(TLS_VERIFY_ENV).is_ok_and(|value| !value.is_empty()) {
+            return Err(DockerError::Unsupported(format!(
+                "{} is set, but TLS connections to the Docker daemon are not supported",
+                TLS_VERIFY_ENV
+            )));
+        }
+        let runtime = Runtime::current();
+        let host = env::var(HOST_ENV).ok().or_else(|| {
+            (runtime == Runtime::Podman)
+                .then(|| env::var(PODMAN_HOST_ENV).ok())
+                .flatten()
+        });
+        let endpoint = match host {
+            Some(host) if !host.is_empty() => Endpoint::parse(&host)?,
+            #[cfg(unix)]
+            _ => Endpoint::Unix(runtime.socket()),
+            #[cfg(not(unix))]
+            _ => {
+                return Err(DockerError::Unsupported(format!(
+                    "Set {} to a tcp:// address; only unix sockets are found automatically",
+                    HOST_ENV
+                )));
+            }
+        };
+        Ok(Self::new(endpoint))
+    }
+
+    async fn connect(&self) -> DockerResult<Box<dyn Connection>> {
+        let connected: io::Result<Box<dyn Connection>> = match &self.endpoint {
+            #[cfg(unix)]
+            Endpoint::Unix(path) => UnixStream::connect(path)
+                .await
+                .map(|stream| Box::new(stream) as Box<dyn Connection>),
+            Endpoint::Tcp(address) => TcpStream::connect(address)
+                .await
+                .map(|stream| Box::new(stream) as Box<dyn Connection>),
+        };
+        connected.map_err(|source| DockerError::Connect {
+            endpoint: self.endpoint.to_string(),
+            source,
+        })
+    }
+
+    /// Sends one request and reads the response head. Error statuses are
+    /// turned into `DockerError::Api` with the daemon's message.
+    async fn request(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<&Value>,
+        headers: &[(&str, String)],
+    ) -> DockerResult<Response> {
+        let mut connection = self.connect().await?;
+
+        let body = body.map(Value::to_string).unwrap_or_default();
+        let mut head = format!(
+            "{} /{}{} HTTP/1.1\r\nHost: docker\r\nUser-Agent: nqrust-identity\r\nConnection: close\r\n",
+            method, API_VERSION, path
+        );
+        for (name, value) in headers {
+            head.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        if !body.is_empty() {
+            head.push_str("Content-Type: application/json\r\n");
+        }
+        head.push_str(&format!("Content-Length: {}\r\n\r\n", body.len()));
+        connection.write_all(head.as_bytes()).await?;
+        connection.write_all(body.as_bytes()).await?;
+        connection.flush().await?;
+
+        let mut reader = BufReader::new(connection);
+        let mut line = String::new();
+        reader.read_line(&mut line).await?;
+        let status: u16 = line
+            .split_whitespace()
+            .nth(1)
+            .and_then(|status| status.parse().ok())
+            .ok_or_else(|| DockerError::Decode(format!("bad status line {:?}", line.trim())))?;
+
+        let mut framing = Framing::UntilClose;
+        let mut content_type = None;
+        loop {
+            line.clear();
+            if reader.read_line(&mut line).await? == 0 || line.trim().is_empty() {
+                break;
+            }
+            let Some((name, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            match name.trim().to_ascii_lowercase().as_str() {
+                "transfer-encoding" if value.eq_ignore_ascii_case("chunked") => {
+                    framing = Framing::Chunked;
+                }
+                "content-length" if !matches!(framing, Framing::Chunked) => {
+                    let length = value
+                        .parse()
+                        .map_err(|_| DockerError::Decode(format!("bad length {:?}", value)))?;
+                    framing = Framing::Length(length);
+                }
+                "content-type" => content_type = Some(value.to_string()),
+                _ => {}
+            }
+        }
+
+        let response = Response {
+            content_type,
+            body: Body {
+                reader,
+                framing,
+                buffer: Vec::new(),
+                done: false,
+            },
+        };
+        if status >= 400 {
+            let body = response.body.bytes().await.unwrap_or_default();
+            let message = serde_json::from_slice::<Value>(&body)
+                .ok()
+                .and_then(|value| value.get("message")?.as_str().map(str::to_string))
+                .unwrap_or_else(|| String::from_utf8_lossy(&body).trim().to_string());
+            return Err(DockerError::Api { status, message });
+        }
+        Ok(response)
+    }
+
+    /// `GET /_ping`: whether the API answers at all.
+    pub async fn ping(&self) -> DockerResult<()> {
+        self.request("GET", "/_ping", None, &[])
+            .await?
+            .body
+            .bytes()
+            .await?;
+        Ok(())
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, path: &str) -> DockerResult<Option<T>> {
+        match self.request("GET", path, None, &[]).await {
+            Ok(response) => Ok(Some(response.json().await?)),
+            Err(DockerError::Api { status: 404, .. }) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// `GET /images/{name}/json`, into whatever shape the caller needs.
+    /// `None` when the image is not present locally.
+    pub async fn inspect_image<T: DeserializeOwned>(
+        &self,
+        reference: &str,
+    ) -> DockerResult<Option<T>> {
+        self.get_json(&format!("/images/{}/json", encode(reference, "/:@")))
+            .await
+    }
+
+    /// `GET /containers/{name}/json`; `None` when there is no such container.
+    pub async fn inspect_container<T: DeserializeOwned>(
+        &self,
+        name: &str,
+    ) -> DockerResult<Option<T>> {
+        self.get_json(&format!("/containers/{}/json", encode(name, "")))
+            .await
+    }
+
+    /// Pulls `reference`, calling `on_event` with each progress message.
+    /// Errors reported inside the stream (e.g. `manifest unknown`) fail the pull.
+    pub async fn pull(
+        &self,
+        reference: &str,
+        auth: Option<&RegistryAuth>,
+        mut on_event: impl FnMut(&PullEvent),
+    ) -> DockerResult<()> {
+        let (image, tag) = split_reference(reference);
+        let path = format!(
+            "/images/create?fromImage={}&tag={}",
+            encode(image, ""),
+            encode(tag, "")
+        );
+        let headers: Vec<(&str, String)> = auth
+            .map(|auth| vec![("X-Registry-Auth", auth.header())])
+            .unwrap_or_default();
+        let mut response = self.request("POST", &path, None, &headers).await?;
+
+        while let Some(line) = response.body.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let event: PullEvent = serde_json::from_str(&line)?;
+            if let Some(error) = event.error {
+                return Err(DockerError::Pull(error));
+            }
+            on_event(&event);
+        }
+        Ok(())
+    }
+
+    /// Streams a container's stdout and stderr line by line, starting with
+    /// the last `tail` lines. With `follow`, keeps streaming until the
+    /// container stops or `on_line` returns `false`.
+    pub async fn logs(
+        &self,
+        container: &str,
+        tail: usize,
+        follow: bool,
+        on_line: impl FnMut(StreamKind, String) -> bool,
+    ) -> DockerResult<()> {
+        let path = format!(
+            "/containers/{}/logs?stdout=1&stderr=1&tail={}&follow={}",
+            encode(container, ""),
+            tail,
+            u8::from(follow)
+        );
+        let mut response = self.request("GET", &path, None, &[]).await?;
+        let multiplexed = response.is_multiplexed();
+        response.body.for_each_line(multiplexed, on_line).await
+    }
+
+    /// Runs `cmd` inside `container` and collects its output and exit code.
+    pub async fn exec(&self, container: &str, cmd: &[&str]) -> DockerResult<ExecOutput> {
+        #[derive(Deserialize)]
+        struct Created {
+            #[serde(rename = "Id")]
+            id: String,
+        }
+        #[derive(Deserialize)]
+        struct Inspected {
+            #[serde(rename = "ExitCode", default)]
+            exit_code: Option<i64>,
+        }
+
+        let config = json!({ "AttachStdout": true, "AttachStderr": true, "Cmd": cmd });
+        let created: Created = self
+            .request(
+                "POST",
+                &format!("/containers/{}/exec", encode(container, "")),
+                Some(&config),
+                &[],
+            )
+            .await?
+            .json()
+            .await?;
+
+        let start = json!({ "Detach": false, "Tty": false });
+        let mut response = self
+            .request(
+                "POST",
+                &format!("/exec/{}/start", created.id),
+                Some(&start),
+                &[],
+            )
+            .await?;
+        let multiplexed = response.is_multiplexed();
+        let mut output = ExecOutput::default();
+        response
+            .body
+            .for_each_line(multiplexed, |kind, line| {
+                let target = match kind {
+                    StreamKind::Stdout => &mut output.stdout,
+                    StreamKind::Stderr => &mut output.stderr,
+                };
+                target.push_str(&line);
+                target.push('\n');
+                true
+            })
+            .await?;
+
+        let inspected: Inspected = self
+            .get_json(&format!("/exec/{}/json", created.id))
+            .await?
+            .ok_or_else(|| DockerError::Decode("exec instance disappeared".to_string()))?;
+        output.exit_code = inspected.exit_code.unwrap_or(-1);
+        Ok(output)
+    }
+}
+
+/// Splits `repo:tag` or `repo@digest` the way the pull endpoint expects;
+/// a bare repository means `latest`.
+fn split_reference(reference: &str) -> (&str, &str) {
+    if let Some((image, digest)) = reference.split_once('@') {
+        return (image, digest);
+    }
+    match reference.rfind(':') {
+        Some(colon) if !reference[colon..].contains('/') => {
+            (&reference[..colon], &reference[colon + 1..])
+        }
+        _ => (reference, "latest"),
+    }
+}
+
+/// Percent-encodes `value` for a URL, leaving unreserved characters and
+/// `keep` alone.
+fn encode(value: &str, keep: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| {
+            let c = byte as char;
+            if c.is_ascii_alphanumeric() || "-._~".contains(c) || keep.contains(c) {
+                c.to_string()
+            } else {
+                format!("%{:02X}", byte)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(unix)]
+    use tokio::net::UnixListener;
+
+    /// Serves each connection on a temporary socket with `handler(request_head)`,
+    /// which returns the raw response bytes.
+    #[cfg(unix)]
+    fn fake_daemon<F>(handler: F) -> DockerClient
+    where
+        F: Fn(&str) -> Vec<u8> + Send + Sync + 'static,
+    {
+        let path = env::temp_dir().join(format!("nqrust-docker-{}.sock", uuid::Uuid::new_v4()));
+        let listener = UnixListener::bind(&path).unwrap();
+        let handler = std::sync::Arc::new(handler);
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let handler = handler.clone();
+                tokio::spawn(async move {
+                    let mut reader = BufReader::new(stream);
+                    let mut head = String::new();
+                    let mut length = 0;
+                    loop {
+                        let mut line = String::new();
+                        if reader.read_line(&mut line).await.unwrap() == 0 || line == "\r\n" {
+                            break;
+                        }
+                        if let Some(value) =
+                            line.to_ascii_lowercase().strip_prefix("content-length:")
+                        {
+                            length = value.trim().parse().unwrap();
+                        }
+                        head.push_str(&line);
+                    }
+                    let mut body = vec![0; length];
+                    reader.read_exact(&mut body).await.unwrap();
+                    let response = handler(&head);
+                    reader.get_mut().write_all(&response).await.unwrap();
+                });
+            }
+        });
+        DockerClient::new(Endpoint::Unix(path))
+    }
+
+    #[cfg(unix)]
+    fn chunked(content_type: &str, chunks: &[&[u8]]) -> Vec<u8> {
+        let mut response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nTransfer-Encoding: chunked\r\n\r\n",
+            content_type
+        )
+        .into_bytes();
+        for chunk in chunks {
+            response.extend(format!("{:x}\r\n", chunk.len()).into_bytes());
+            response.extend_from_slice(chunk);
+            response.extend(b"\r\n");
+        }
+        response.extend(b"0\r\n\r\n");
+        response
+    }
+
+    #[cfg(unix)]
+    fn frame(stream: u8, text: &str) -> Vec<u8> {
+        let mut frame = vec![stream, 0, 0, 0];
+        frame.extend((text.len() as u32).to_be_bytes());
+        frame.extend(text.as_bytes());
+        frame
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_fake_daemon_round_trips() {
+        let docker = fake_daemon(|head| {
+            let request = head.lines().next().unwrap_or_default().to_string();
+            if request
+                .starts_with("GET /v1.41/images/ghcr.io/nexusquantum/nqrust-identity:1.4/json")
+            {
+                let body = r#"{"Id":"sha256:abc","RepoDigests":["ghcr.io/nexusquantum/nqrust-identity@sha256:def"]}"#;
+                format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                    body.len(),
+                    body
+                )
+                .into_bytes()
+            } else if request.starts_with("GET /v1.41/containers/missing/json") {
+                let body = r#"{"message":"No such container: missing"}"#;
+                format!(
+                    "HTTP/1.1 404 Not Found\r\nContent-Length: {}\r\n\r\n{}",
+                    body.len(),
+                    body
+                )
+                .into_bytes()
+            } else if request
+                .starts_with("POST /v1.41/images/create?fromImage=postgres&tag=16-alpine")
+            {
+                assert!(head.contains("X-Registry-Auth: "));
+                chunked(
+                    "application/json",
+                    &[
+                        br#"{"status":"Pulling fs layer","id":"a2318d6c47ec"}"#,
+                        b"\r\n{\"status\":\"Downloading\",\"id\":\"a2318d6c47ec\",\"progressDetail\":{\"current\":512,",
+                        b"\"total\":1024}}\r\n",
+                    ],
+                )
+            } else if request.starts_with("POST /v1.41/images/create?fromImage=ghcr.io") {
+                chunked(
+                    "application/json",
+                    &[br#"{"error":"manifest unknown","errorDetail":{"message":"manifest unknown"}}"#],
+                )
+            } else if request.starts_with("GET /v1.41/containers/identity/logs") {
+                assert!(request.contains("tail=2&follow=0"));
+                let mut body = frame(1, "2024-05-01 INFO started\n2024-05-01 WARN sl");
+                body.extend(frame(2, "oops\n"));
+                body.extend(frame(1, "ow\n"));
+                chunked("application/vnd.docker.multiplexed-stream", &[&body])
+            } else if request.starts_with("POST /v1.41/containers/identity-db/exec") {
+                let body = r#"{"Id":"e1"}"#;
+                format!(
+                    "HTTP/1.1 201 Created\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                    body.len(),
+                    body
+                )
+                .into_bytes()
+            } else if request.starts_with("POST /v1.41/exec/e1/start") {
+                let mut body = frame(1, "3\n");
+                body.extend(frame(2, "NOTICE: done\n"));
+                let mut response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/vnd.docker.multiplexed-stream\r\nContent-Length: {}\r\n\r\n",
+                    body.len()
+                )
+                .into_bytes();
+                response.extend(body);
+                response
+            } else if request.starts_with("GET /v1.41/exec/e1/json") {
+                let body = r#"{"ExitCode":0,"Running":false}"#;
+                format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}",
+                    body.len(),
+                    body
+                )
+                .into_bytes()
+            } else {
+                b"HTTP/1.1 500 Internal Server Error\r\nContent-Length: 0\r\n\r\n".to_vec()
+            }
+        });
+
+        #[derive(Deserialize)]
+        struct Image {
+            #[serde(rename = "RepoDigests")]
+            repo_digests: Vec<String>,
+        }
+        let image: Image = docker
+            .inspect_image("ghcr.io/nexusquantum/nqrust-identity:1.4")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            image.repo_digests,
+            vec!["ghcr.io/nexusquantum/nqrust-identity@sha256:def"]
+        );
+        assert!(
+            docker
+                .inspect_container::<Value>("missing")
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let auth = RegistryAuth {
+            username: "robot".to_string(),
+            password: "secret".to_string(),
+            server: "docker.io".to_string(),
+        };
+        let mut events = Vec::new();
+        docker
+            .pull("postgres:16-alpine", Some(&auth), |event| {
+                events.push(event.clone())
+            })
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].status, "Downloading");
+        assert_eq!(events[1].progress_detail.unwrap().current, 512);
+
+        let error = docker
+            .pull("ghcr.io/nexusquantum/nqrust-identity:nope", None, |_| {})
+            .await
+            .unwrap_err();
+        assert!(matches!(error, DockerError::Pull(message) if message == "manifest unknown"));
+
+        let mut lines = Vec::new();
+        docker
+            .logs("identity", 2, false, |kind, line| {
+                lines.push((kind, line));
+                true
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            lines,
+            vec![
+                (StreamKind::Stdout, "2024-05-01 INFO started".to_string()),
+                (StreamKind::Stderr, "oops".to_string()),
+                (StreamKind::Stdout, "2024-05-01 WARN slow".to_string()),
+            ]
+        );
+
+        let output = docker
+            .exec(
+                "identity-db",
+                &["psql", "-tAc", "SELECT count(*) FROM realm"],
+            )
+            .await
+            .unwrap();
+        assert_eq!(output.exit_code, 0);
+        assert_eq!(output.stdout, "3\n");
+        assert_eq!(output.stderr, "NOTICE: done\n");
+    }
+
+    #[test]
+    fn test_endpoint_and_reference_parsing() {
+        #[cfg(unix)]
+        assert_eq!(
+            Endpoint::parse("unix:///run/user/1000/docker.sock").unwrap(),
+            Endpoint::Unix(PathBuf::from("/run/user/1000/docker.sock"))
+        );
+        assert_eq!(
+            Endpoint::parse("tcp://10.0.0.5:2375/").unwrap(),
+            Endpoint::Tcp("10.0.0.5:2375".to_string())
+        );
+        assert!(matches!(
+            Endpoint::parse("ssh://admin@host"),
+            Err(DockerError::Unsupported(_))
+        ));
+
+        assert_eq!(split_reference("postgres"), ("postgres", "latest"));
+        assert_eq!(
+            split_reference("localhost:5000/identity:1.2"),
+            ("localhost:5000/identity", "1.2")
+        );
+        assert_eq!(
+            split_reference("ghcr.io/org/app@sha256:abc"),
+            ("ghcr.io/org/app", "sha256:abc")
+        );
+        assert_eq!(encode("a b/c", "/"), "a%20b/c");
+    }
+
+    #[test]
+    fn test_registry_auth_from_docker_config() {
+        let auths = json!({
+            "https://index.docker.io/v1/": { "auth": "cm9ib3Q6aHViLXNlY3JldA==" },
+            "harbor.example.com": { "auth": "YWRtaW46czNjcjN0" },
+        });
+        let hub = RegistryAuth::from_auths(&auths, "registry-1.docker.io").unwrap();
+        assert_eq!(
+            (hub.username.as_str(), hub.password.as_str()),
+            ("robot", "hub-secret")
+        );
+        let harbor = RegistryAuth::from_auths(&auths, "harbor.example.com").unwrap();
+        assert_eq!(harbor.password, "s3cr3t");
+        assert!(RegistryAuth::from_auths(&auths, "ghcr.io").is_none());
+
+        let config = json!({
+            "auths": { "harbor.example.com": {}, "ghcr.io": {} },
+            "credsStore": "desktop",
+            "credHelpers": { "ghcr.io": "gh" },
+        });
+        let helper = |name: &str, server: &str| match (name, server) {
+            ("desktop", "harbor.example.com") => {
+                Some(("robot".to_string(), "from-store".to_string()))
+            }
+            ("gh", "ghcr.io") => Some(("octo".to_string(), "ghp_helper".to_string())),
+            _ => None,
+        };
+        let harbor = RegistryAuth::from_config(&config, "harbor.example.com", helper).unwrap();
+        assert_eq!(harbor.password, "from-store");
+        let ghcr = RegistryAuth::from_config(&config, "ghcr.io", helper).unwrap();
+        assert_eq!(ghcr.username, "octo");
+        assert!(RegistryAuth::from_config(&config, "quay.io", helper).is_none());
+    }
+}
@@ -0,0 +1,195 @@
+use std::fs;
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+
+use color_eyre::eyre::Result;
+
+/// Minimal reader/writer for the compose project's `.env` file. Comments,
+/// blank lines and key order are preserved so hand edits survive a rewrite.
+#[derive(Debug, Default, Clone)]
+pub struct EnvFile {
+    lines: Vec<EnvLine>,
+}
+
+#[derive(Debug, Clone)]
+enum EnvLine {
+    Entry { key: String, value: String },
+    Other(String),
+}
+
+impl EnvFile {
+    /// Loads `path`, returning an empty file when it does not exist yet.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(Self::parse(&fs::read_to_string(path)?))
+    }
+
+    pub fn parse(content: &str) -> Self {
+        let lines = content
+            .lines()
+            .map(|line| {
+                let trimmed = line.trim();
+                if trimmed.is_empty() || trimmed.starts_with('#') {
+                    return EnvLine::Other(line.to_string());
+                }
+
+                let trimmed = trimmed.strip_prefix("export ").unwrap_or(trimmed);
+                match trimmed.split_once('=') {
+                    Some((key, value)) => EnvLine::Entry {
+                        key: key.trim().to_string(),
+                        value: unquote(value.trim()),
+                    },
+                    None => EnvLine::Other(line.to_string()),
+                }
+            })
+            .collect();
+
+        Self { lines }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.lines.iter().find_map(|line| match line {
+            EnvLine::Entry { key: k, value } if k == key => Some(value.as_str()),
+            _ => None,
+        })
+    }
+
+    pub fn set(&mut self, key: &str, value: &str) {
+        for line in &mut self.lines {
+            if let EnvLine::Entry { key: k, value: v } = line
+                && k == key
+            {
+                *v = value.to_string();
+                return;
+            }
+        }
+
+        self.lines.push(EnvLine::Entry {
+            key: key.to_string(),
+            value: value.to_string(),
+        });
+    }
+
+    pub fn remove(&mut self, key: &str) {
+        self.lines
+            .retain(|line| !matches!(line, EnvLine::Entry { key: k, .. } if k == key));
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for line in &self.lines {
+            match line {
+                EnvLine::Entry { key, value } => {
+                    out.push_str(key);
+                    out.push('=');
+                    out.push_str(&quote(value));
+                }
+                EnvLine::Other(raw) => out.push_str(raw),
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Writes the file readable by the owner only, since it holds passwords.
+    /// The content goes to a private temporary file first, which is renamed
+    /// over `path`, so the secrets are never readable by others.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+        file_name.push(".tmp");
+        let temp = path.with_file_name(file_name);
+        let _ = fs::remove_file(&temp);
+
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+        let written = options.open(&temp).and_then(|mut file| {
+            file.write_all(self.render().as_bytes())?;
+            file.sync_all()
+        });
+        if let Err(e) = written.and_then(|()| fs::rename(&temp, path)) {
+            let _ = fs::remove_file(&temp);
+            return Err(e.into());
+        }
+        Ok(())
+    }
+}
+
+fn unquote(value: &str) -> String {
+    if value.len() >= 2 {
+        if value.starts_with('\'') && value.ends_with('\'') {
+            return value[1..value.len() - 1].to_string();
+        }
+        if value.starts_with('"') && value.ends_with('"') {
+            return value[1..value.len() - 1]
+                .replace("\\\"", "\"")
+                .replace("\\\\", "\\");
+        }
+    }
+    value.to_string()
+}
+
+fn quote(value: &str) -> String {
+    let plain = value
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "-_.:/@,+".contains(c));
+
+    if plain {
+        value.to_string()
+    } else {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_preserves_comments_and_order() {
+        let mut env = EnvFile::parse("# generated\nPG_PORT=5432\nKC_THEME_DEFAULT='starter'\n");
+
+        env.set("PG_PORT", "5433");
+        env.set("KEYCLOAK_ADMIN", "admin");
+
+        assert_eq!(env.get("KC_THEME_DEFAULT"), Some("starter"));
+        assert_eq!(
+            env.render(),
+            "# generated\nPG_PORT=5433\nKC_THEME_DEFAULT=starter\nKEYCLOAK_ADMIN=admin\n"
+        );
+    }
+
+    #[test]
+    fn test_values_with_spaces_round_trip() {
+        let mut env = EnvFile::default();
+        env.set("KC_HOSTNAME", "auth \"edge\" host");
+
+        let parsed = EnvFile::parse(&env.render());
+        assert_eq!(parsed.get("KC_HOSTNAME"), Some("auth \"edge\" host"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_save_replaces_file_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = std::env::temp_dir().join(format!("nqrust-env-{}", uuid::Uuid::new_v4()));
+        fs::write(&path, "OLD=1\n").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        let mut env = EnvFile::default();
+        env.set("POSTGRES_PASSWORD", "s3cr3t");
+        env.save(&path).unwrap();
+
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        let content = fs::read_to_string(&path).unwrap();
+        let _ = fs::remove_file(&path);
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(content, "POSTGRES_PASSWORD=s3cr3t\n");
+    }
+}
@@ -1,15 +1,31 @@
 mod app;
+mod cli;
+mod docker;
+mod env_file;
 mod pages;
+mod registry;
+mod runtime;
+mod secrets;
 mod ui;
 mod utils;
 
+use std::process::ExitCode;
+
 use app::App;
+use clap::Parser;
+use cli::Cli;
 
 #[tokio::main]
-async fn main() -> color_eyre::Result<()> {
+async fn main() -> color_eyre::Result<ExitCode> {
     color_eyre::install()?;
+    let cli = Cli::parse();
+
+    if let Some(command) = cli.command {
+        return app::headless::run(command).await;
+    }
+
     let terminal = ratatui::init();
     let result = App::new().run(terminal).await;
     ratatui::restore();
-    result
+    result.map(|_| ExitCode::SUCCESS)
 }
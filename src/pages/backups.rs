@@ -0,0 +1,115 @@
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Wrap},
+};
+
+use crate::app::backup::{Backup, format_size};
+use crate::ui::{get_orange_accent, get_orange_color};
+
+pub struct BackupsView<'a> {
+    pub backups: &'a [Backup],
+    pub selected_index: usize,
+    pub message: Option<&'a str>,
+    pub backup_dir: &'a str,
+}
+
+pub fn render_backups(frame: &mut Frame, view: &BackupsView<'_>) {
+    let area = frame.area();
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(2)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(8),
+            Constraint::Length(4),
+            Constraint::Length(2),
+        ])
+        .split(area);
+
+    let title = Paragraph::new("💾 Database Backups")
+        .style(
+            Style::default()
+                .fg(get_orange_color())
+                .add_modifier(Modifier::BOLD),
+        )
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent())),
+        )
+        .centered();
+    frame.render_widget(title, chunks[0]);
+
+    let mut lines = vec![
+        Line::from(format!("Stored in {}", view.backup_dir)),
+        Line::from(""),
+    ];
+
+    if view.backups.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "No backups found",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+
+    for (index, backup) in view.backups.iter().enumerate() {
+        let style = if index == view.selected_index {
+            Style::default()
+                .fg(Color::Black)
+                .bg(get_orange_color())
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::White)
+        };
+
+        lines.push(Line::from(Span::styled(
+            format!(
+                "  ▶  {}   {}   {}",
+                backup.created.format("%Y-%m-%d %H:%M:%S"),
+                format_size(backup.size),
+                backup.file_name
+            ),
+            style,
+        )));
+    }
+
+    let list = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent()))
+                .title("Backups")
+                .title_style(
+                    Style::default()
+                        .fg(get_orange_color())
+                        .add_modifier(Modifier::BOLD),
+                ),
+        )
+        .wrap(Wrap { trim: false });
+    frame.render_widget(list, chunks[1]);
+
+    let status = Paragraph::new(view.message.unwrap_or(""))
+        .style(Style::default().fg(Color::Yellow))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent()))
+                .title("Status")
+                .title_style(
+                    Style::default()
+                        .fg(get_orange_color())
+                        .add_modifier(Modifier::BOLD),
+                ),
+        )
+        .wrap(Wrap { trim: true });
+    frame.render_widget(status, chunks[2]);
+
+    let help = Paragraph::new("↑↓ to select, B to back up now, Enter/R to restore, U to upgrade PostgreSQL, Esc to go back")
+        .style(Style::default().fg(Color::DarkGray))
+        .centered();
+    frame.render_widget(help, chunks[3]);
+}
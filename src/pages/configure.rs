@@ -0,0 +1,102 @@
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, Paragraph, Wrap},
+};
+
+use super::form::form_lines;
+use crate::app::form::Form;
+use crate::ui::{get_orange_accent, get_orange_color};
+
+pub struct ConfigureView<'a> {
+    pub form: &'a Form,
+    pub env_path: &'a str,
+}
+
+pub fn render_configure(frame: &mut Frame, view: &ConfigureView<'_>) {
+    let area = frame.area();
+
+    let form_height = view.form.fields.len() as u16 + 6;
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(2)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Length(form_height),
+            Constraint::Min(4),
+            Constraint::Length(2),
+        ])
+        .split(area);
+
+    let header = Paragraph::new("⚙️  Configure Identity Stack")
+        .style(
+            Style::default()
+                .fg(get_orange_color())
+                .add_modifier(Modifier::BOLD),
+        )
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent())),
+        )
+        .centered();
+    frame.render_widget(header, chunks[0]);
+
+    let mut field_lines = vec![
+        Line::from(format!("Values are saved to {}.", view.env_path)),
+        Line::from(""),
+    ];
+    field_lines.extend(form_lines(view.form));
+
+    let form_block = Paragraph::new(field_lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent()))
+                .title("Settings")
+                .title_style(
+                    Style::default()
+                        .fg(get_orange_color())
+                        .add_modifier(Modifier::BOLD),
+                ),
+        )
+        .wrap(Wrap { trim: false });
+    frame.render_widget(form_block, chunks[1]);
+
+    let (status_message, status_style) = if view.form.error_message.is_empty() {
+        (
+            "Review the settings, then choose \"Save and install\".".to_string(),
+            Style::default().fg(Color::Yellow),
+        )
+    } else {
+        (
+            view.form.error_message.clone(),
+            Style::default().fg(Color::Red),
+        )
+    };
+
+    let status_block = Paragraph::new(status_message)
+        .style(status_style)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent()))
+                .title("Status")
+                .title_style(
+                    Style::default()
+                        .fg(get_orange_color())
+                        .add_modifier(Modifier::BOLD),
+                ),
+        )
+        .wrap(Wrap { trim: true });
+    frame.render_widget(status_block, chunks[2]);
+
+    let help =
+        Paragraph::new("↑↓ to move, Enter to edit, Ctrl+S to save and install, Esc to go back")
+            .style(Style::default().fg(Color::DarkGray))
+            .centered();
+    frame.render_widget(help, chunks[3]);
+}
@@ -12,6 +12,10 @@ use crate::ui::{ASCII_HEADER, get_orange_accent, get_orange_color};
 pub struct ConfirmationView<'a> {
     pub menu_selection: &'a MenuSelection,
     pub menu_options: &'a [MenuSelection],
+    /// Warning about the saved GHCR token, e.g. that it expires soon.
+    pub notice: Option<&'a str>,
+    /// The stack already runs here, so proceeding reinstalls it.
+    pub deployed: bool,
 }
 
 pub fn render_confirmation(frame: &mut Frame, view: &ConfirmationView<'_>) {
@@ -23,7 +27,7 @@ pub fn render_confirmation(frame: &mut Frame, view: &ConfirmationView<'_>) {
         .constraints([
             Constraint::Length(5), // ASCII header (smaller - 6 lines but compact)
             Constraint::Min(10),
-            Constraint::Length(6),
+            Constraint::Length(8),
             Constraint::Length(2),
         ])
         .split(area);
@@ -51,7 +55,9 @@ pub fn render_confirmation(frame: &mut Frame, view: &ConfirmationView<'_>) {
         Line::from(""),
         Line::from(Span::styled(
             "NQRust Identity Stack",
-            Style::default().fg(Color::Green).add_modifier(Modifier::BOLD),
+            Style::default()
+                .fg(Color::Green)
+                .add_modifier(Modifier::BOLD),
         )),
         Line::from(""),
     ];
@@ -60,18 +66,30 @@ pub fn render_confirmation(frame: &mut Frame, view: &ConfirmationView<'_>) {
     content_lines.push(Line::from("  • postgres (PostgreSQL 16 database)"));
     content_lines.push(Line::from("  • identity (Keycloak server)"));
     content_lines.push(Line::from(""));
-    
-    content_lines.push(Line::from("Default configuration:"));
-    content_lines.push(Line::from("  • Admin: admin / admin"));
-    content_lines.push(Line::from("  • Database: identity / identity"));
+
+    content_lines.push(Line::from(
+        "Default configuration (adjustable on the next page):",
+    ));
+    content_lines.push(Line::from("  • Admin user: admin"));
+    content_lines.push(Line::from("  • Database: identity"));
     content_lines.push(Line::from("  • Port: 8080"));
     content_lines.push(Line::from(""));
-    
+
     content_lines.push(Line::from(Span::styled(
-        "⚠️  Change default password after first login!",
+        "🔑 Admin and database passwords are generated into .env on first install",
         Style::default().fg(Color::Yellow),
     )));
 
+    if let Some(notice) = view.notice {
+        content_lines.push(Line::from(""));
+        content_lines.push(Line::from(Span::styled(
+            notice,
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )));
+    }
+
     let content = Paragraph::new(content_lines)
         .block(
             Block::default()
@@ -93,7 +111,13 @@ pub fn render_confirmation(frame: &mut Frame, view: &ConfirmationView<'_>) {
         let (label, fg_color, highlight_color) = match option {
             MenuSelection::CheckUpdates => ("Check for updates", Color::Cyan, Color::Cyan),
             MenuSelection::UpdateToken => ("Update GHCR token", Color::Yellow, Color::Yellow),
+            MenuSelection::Dashboard => ("Manage running services", Color::Green, Color::Green),
+            MenuSelection::Proceed if view.deployed => {
+                ("Reinstall the whole stack", Color::Green, Color::Green)
+            }
             MenuSelection::Proceed => ("Proceed with installation", Color::Green, Color::Green),
+            MenuSelection::Backups => ("Backup and restore", Color::Blue, Color::Blue),
+            MenuSelection::Uninstall => ("Uninstall", Color::Magenta, Color::Magenta),
             MenuSelection::Cancel => ("Cancel", Color::Red, Color::Red),
         };
 
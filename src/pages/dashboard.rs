@@ -0,0 +1,169 @@
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, Wrap},
+};
+
+use crate::app::dashboard::ServiceStatus;
+use crate::ui::{get_orange_accent, get_orange_color};
+
+pub struct DashboardView<'a> {
+    pub services: &'a [ServiceStatus],
+    pub selected_index: usize,
+    pub message: Option<&'a str>,
+}
+
+pub fn render_dashboard(frame: &mut Frame, view: &DashboardView<'_>) {
+    let area = frame.area();
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(8),
+            Constraint::Length(4),
+            Constraint::Length(2),
+        ])
+        .split(area);
+
+    let title = Paragraph::new("📊 NQRust Identity Dashboard")
+        .style(
+            Style::default()
+                .fg(get_orange_color())
+                .add_modifier(Modifier::BOLD),
+        )
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent())),
+        )
+        .centered();
+    frame.render_widget(title, chunks[0]);
+
+    let services_block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(get_orange_accent()))
+        .title("Services")
+        .title_style(
+            Style::default()
+                .fg(get_orange_color())
+                .add_modifier(Modifier::BOLD),
+        );
+
+    if view.services.is_empty() {
+        let placeholder = Paragraph::new("No services found")
+            .style(Style::default().fg(Color::DarkGray))
+            .block(services_block)
+            .centered();
+        frame.render_widget(placeholder, chunks[1]);
+    } else {
+        let header = Row::new(vec![
+            Cell::from("Service").style(header_style()),
+            Cell::from("Container").style(header_style()),
+            Cell::from("State").style(header_style()),
+            Cell::from("Health").style(header_style()),
+            Cell::from("Uptime").style(header_style()),
+            Cell::from("Tag").style(header_style()),
+            Cell::from("Image").style(header_style()),
+            Cell::from("Ports").style(header_style()),
+        ]);
+
+        let rows: Vec<Row> = view
+            .services
+            .iter()
+            .enumerate()
+            .map(|(index, service)| {
+                let mut style = if service.is_healthy() {
+                    Style::default().fg(Color::Green)
+                } else if service.is_running() {
+                    Style::default().fg(Color::Yellow)
+                } else {
+                    Style::default().fg(Color::Red)
+                };
+                if index == view.selected_index {
+                    style = style.add_modifier(Modifier::REVERSED | Modifier::BOLD);
+                }
+
+                let ports = service.ports();
+                Row::new(vec![
+                    Cell::from(service.service.clone()),
+                    Cell::from(or_dash(&service.name)),
+                    Cell::from(service.state.clone()),
+                    Cell::from(or_dash(&service.health)),
+                    Cell::from(service.uptime().unwrap_or("—").to_string()),
+                    Cell::from(service.tag().to_string()),
+                    Cell::from(
+                        service
+                            .running
+                            .as_ref()
+                            .map(|image| image.describe())
+                            .unwrap_or_else(|| "—".to_string()),
+                    ),
+                    Cell::from(if ports.is_empty() {
+                        "—".to_string()
+                    } else {
+                        ports.join(", ")
+                    }),
+                ])
+                .style(style)
+            })
+            .collect();
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(12),
+                Constraint::Length(14),
+                Constraint::Length(12),
+                Constraint::Length(10),
+                Constraint::Length(16),
+                Constraint::Length(12),
+                Constraint::Length(30),
+                Constraint::Min(14),
+            ],
+        )
+        .header(header)
+        .block(services_block)
+        .column_spacing(1);
+        frame.render_widget(table, chunks[1]);
+    }
+
+    let status = Paragraph::new(view.message.unwrap_or(""))
+        .style(Style::default().fg(Color::Yellow))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent()))
+                .title("Status")
+                .title_style(
+                    Style::default()
+                        .fg(get_orange_color())
+                        .add_modifier(Modifier::BOLD),
+                ),
+        )
+        .wrap(Wrap { trim: true });
+    frame.render_widget(status, chunks[2]);
+
+    let help = Paragraph::new(
+        "↑↓ to select, Enter/L logs, S start, X stop, R restart, I reinstall, F refresh, M/Esc menu, Q quit",
+    )
+    .style(Style::default().fg(Color::DarkGray))
+    .centered();
+    frame.render_widget(help, chunks[3]);
+}
+
+fn header_style() -> Style {
+    Style::default()
+        .fg(get_orange_color())
+        .add_modifier(Modifier::BOLD)
+}
+
+fn or_dash(value: &str) -> String {
+    if value.is_empty() {
+        "—".to_string()
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,62 @@
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+
+use crate::app::form::Form;
+use crate::ui::get_orange_color;
+
+/// Renders every field of `form` followed by its submit button.
+pub fn form_lines(form: &Form) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+
+    for (index, field) in form.fields.iter().enumerate() {
+        let is_selected = form.current_field == index;
+
+        let display = if field.value.is_empty() {
+            field.placeholder.to_string()
+        } else if field.masked {
+            "*".repeat(field.value.chars().count())
+        } else {
+            field.value.clone()
+        };
+        let cursor = if is_selected && form.editing {
+            "▏"
+        } else {
+            ""
+        };
+
+        let style = if is_selected {
+            Style::default()
+                .fg(Color::Black)
+                .bg(get_orange_color())
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::White)
+        };
+
+        lines.push(Line::from(vec![
+            Span::styled("  ▶  ", style),
+            Span::styled(field.label, style),
+            Span::raw(": "),
+            Span::styled(format!("{}{}", display, cursor), style),
+        ]));
+    }
+
+    let submit_style = if form.current_field == form.fields.len() {
+        Style::default()
+            .fg(Color::Black)
+            .bg(Color::Green)
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Color::Green)
+    };
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        format!("  ▶  {}", form.submit_label),
+        submit_style,
+    )));
+
+    lines
+}
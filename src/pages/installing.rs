@@ -6,13 +6,17 @@ use ratatui::{
     widgets::{Block, Borders, Paragraph, Wrap},
 };
 
+use crate::app::health::{HealthState, ServiceHealth};
 use crate::ui::{get_orange_accent, get_orange_color};
 
 pub struct InstallingView<'a> {
+    pub title: &'a str,
+    pub help: &'a str,
     pub progress: f64,
     pub current_service: &'a str,
     pub completed_services: usize,
     pub total_services: usize,
+    pub services: &'a [ServiceHealth],
     pub logs: &'a [String],
 }
 
@@ -25,13 +29,13 @@ pub fn render_installing(frame: &mut Frame, view: &InstallingView<'_>) {
         .constraints([
             Constraint::Length(3),
             Constraint::Length(3),
-            Constraint::Length(3),
+            Constraint::Length(3 + view.services.len() as u16),
             Constraint::Min(10),
             Constraint::Length(2),
         ])
         .split(area);
 
-    let title = Paragraph::new("🔄 Installing Identity... Please wait")
+    let title = Paragraph::new(view.title)
         .style(
             Style::default()
                 .fg(get_orange_color())
@@ -76,8 +80,27 @@ pub fn render_installing(frame: &mut Frame, view: &InstallingView<'_>) {
         "Initializing...".to_string()
     };
 
-    let current_widget = Paragraph::new(current)
-        .style(Style::default().fg(Color::Green))
+    let mut status_lines = vec![Line::from(Span::styled(
+        current,
+        Style::default().fg(Color::Green),
+    ))];
+
+    for entry in view.services {
+        let (icon, color) = match entry.state {
+            HealthState::Healthy => ("●", Color::Green),
+            HealthState::Unhealthy | HealthState::Exited => ("✖", Color::Red),
+            HealthState::Starting => ("◐", Color::Yellow),
+            HealthState::Waiting => ("○", Color::DarkGray),
+        };
+
+        let mut text = format!("{} {:<10} {}", icon, entry.service, entry.state.label());
+        if !entry.detail.is_empty() {
+            text.push_str(&format!(" ({})", entry.detail));
+        }
+        status_lines.push(Line::from(Span::styled(text, Style::default().fg(color))));
+    }
+
+    let current_widget = Paragraph::new(status_lines)
         .block(Block::default().borders(Borders::ALL).title("Status"))
         .centered();
     frame.render_widget(current_widget, chunks[2]);
@@ -86,9 +109,9 @@ pub fn render_installing(frame: &mut Frame, view: &InstallingView<'_>) {
         .logs
         .iter()
         .map(|log| {
-            let style = if log.contains("❌") || log.to_lowercase().contains("error") {
+            let style = if log.contains("❌") {
                 Style::default().fg(Color::Red)
-            } else if log.contains("✅") || log.contains("started") {
+            } else if log.contains("✅") {
                 Style::default().fg(Color::Green)
             } else if log.contains("⬇️") {
                 Style::default().fg(Color::Blue)
@@ -123,7 +146,7 @@ pub fn render_installing(frame: &mut Frame, view: &InstallingView<'_>) {
         ));
     frame.render_widget(logs_widget, chunks[3]);
 
-    let help = Paragraph::new("Press Ctrl+C to cancel")
+    let help = Paragraph::new(view.help)
         .style(Style::default().fg(Color::DarkGray))
         .centered();
     frame.render_widget(help, chunks[4]);
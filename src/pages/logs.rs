@@ -0,0 +1,124 @@
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::app::logs::{LogLevel, LogViewer, log_level};
+use crate::ui::{get_orange_accent, get_orange_color};
+
+pub struct LogViewerView<'a> {
+    pub viewer: &'a LogViewer,
+}
+
+pub fn render_log_viewer(frame: &mut Frame, view: &LogViewerView<'_>) {
+    let viewer = view.viewer;
+    let area = frame.area();
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(8),
+            Constraint::Length(3),
+            Constraint::Length(2),
+        ])
+        .split(area);
+
+    let title = Paragraph::new(format!("📜 Logs: {}", viewer.service))
+        .style(
+            Style::default()
+                .fg(get_orange_color())
+                .add_modifier(Modifier::BOLD),
+        )
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent())),
+        )
+        .centered();
+    frame.render_widget(title, chunks[0]);
+
+    let height = chunks[1].height.saturating_sub(2) as usize;
+    let bottom = viewer.bottom();
+    let top = bottom.saturating_sub(height);
+    let query = viewer.query.as_deref().map(str::to_lowercase);
+
+    let mut lines: Vec<Line> = viewer
+        .lines
+        .range(top..bottom)
+        .map(|line| {
+            let mut style = match log_level(line) {
+                Some(LogLevel::Error) => Style::default().fg(Color::Red),
+                Some(LogLevel::Warn) => Style::default().fg(Color::Yellow),
+                None => Style::default().fg(Color::White),
+            };
+            if query
+                .as_deref()
+                .is_some_and(|query| line.to_lowercase().contains(query))
+            {
+                style = style.bg(Color::DarkGray).add_modifier(Modifier::BOLD);
+            }
+            Line::from(Span::styled(line.as_str(), style))
+        })
+        .collect();
+    if lines.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "Waiting for log output...",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+
+    let position = if viewer.is_following() {
+        "following".to_string()
+    } else {
+        format!("{} lines below", viewer.offset)
+    };
+    let logs = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(get_orange_accent()))
+            .title(format!("{} lines, {}", viewer.lines.len(), position))
+            .title_style(
+                Style::default()
+                    .fg(get_orange_color())
+                    .add_modifier(Modifier::BOLD),
+            ),
+    );
+    frame.render_widget(logs, chunks[1]);
+
+    let status = if let Some(input) = &viewer.input {
+        Line::from(vec![
+            Span::styled("Search: ", Style::default().fg(get_orange_color())),
+            Span::raw(format!("{}█", input)),
+        ])
+    } else if let Some(message) = &viewer.message {
+        Line::from(Span::styled(
+            message.as_str(),
+            Style::default().fg(Color::Yellow),
+        ))
+    } else if viewer.ended {
+        Line::from(Span::styled(
+            "Log stream ended; the container may have stopped",
+            Style::default().fg(Color::Yellow),
+        ))
+    } else {
+        Line::from("")
+    };
+    let status = Paragraph::new(status).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(get_orange_accent())),
+    );
+    frame.render_widget(status, chunks[2]);
+
+    let help = Paragraph::new(
+        "↑↓/PgUp/PgDn to scroll, Home top, End/F follow, / search, n/N older/newer match, S save, Esc back",
+    )
+    .style(Style::default().fg(Color::DarkGray))
+    .centered();
+    frame.render_widget(help, chunks[3]);
+}
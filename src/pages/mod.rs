@@ -1,13 +1,28 @@
+mod backups;
+mod configure;
 mod confirmation;
+mod dashboard;
 mod error;
+mod form;
 mod installing;
+mod logs;
+mod pg_upgrade;
+mod preflight;
 mod registry;
 mod success;
+mod uninstall;
 mod update;
 
+pub use backups::{BackupsView, render_backups};
+pub use configure::{ConfigureView, render_configure};
 pub use confirmation::{ConfirmationView, render_confirmation};
+pub use dashboard::{DashboardView, render_dashboard};
 pub use error::{ErrorView, render_error};
 pub use installing::{InstallingView, render_installing};
+pub use logs::{LogViewerView, render_log_viewer};
+pub use pg_upgrade::{PgUpgradeView, render_pg_upgrade};
+pub use preflight::{PreflightView, render_preflight};
 pub use registry::{RegistrySetupView, render_registry_setup};
 pub use success::{SuccessView, render_success};
+pub use uninstall::{UninstallView, render_uninstall};
 pub use update::{UpdateListView, render_update_list};
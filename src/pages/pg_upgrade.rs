@@ -0,0 +1,164 @@
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Wrap},
+};
+
+use crate::app::pg_upgrade::PgUpgradePlan;
+use crate::ui::{get_orange_accent, get_orange_color};
+
+pub struct PgUpgradeView<'a> {
+    pub plan: &'a PgUpgradePlan,
+    pub message: Option<&'a str>,
+}
+
+pub fn render_pg_upgrade(frame: &mut Frame, view: &PgUpgradeView<'_>) {
+    let area = frame.area();
+    let plan = view.plan;
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(2)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Length(8),
+            Constraint::Min(7),
+            Constraint::Length(4),
+            Constraint::Length(2),
+        ])
+        .split(area);
+
+    let title = Paragraph::new("🐘 PostgreSQL Major Upgrade")
+        .style(
+            Style::default()
+                .fg(get_orange_color())
+                .add_modifier(Modifier::BOLD),
+        )
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent())),
+        )
+        .centered();
+    frame.render_widget(title, chunks[0]);
+
+    let label = Style::default().fg(Color::Gray);
+    let value = Style::default()
+        .fg(Color::White)
+        .add_modifier(Modifier::BOLD);
+    let target = if plan.completed {
+        format!("{}", plan.target_major)
+    } else {
+        format!("◀ {} ▶", plan.target_major)
+    };
+    let details = vec![
+        Line::from(vec![
+            Span::styled("Data directory:  ", label),
+            Span::styled(
+                format!("PostgreSQL {} on {}", plan.data_version, plan.old_volume),
+                value,
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("Configured:      ", label),
+            Span::styled(plan.current_image.clone(), value),
+        ]),
+        Line::from(vec![
+            Span::styled("Target major:    ", label),
+            Span::styled(
+                target,
+                Style::default()
+                    .fg(get_orange_color())
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(format!("  ({})", plan.target_image()), label),
+        ]),
+        Line::from(vec![
+            Span::styled("New volume:      ", label),
+            Span::styled(plan.new_volume(), value),
+        ]),
+    ];
+    let details = Paragraph::new(details).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(get_orange_accent()))
+            .title("Plan")
+            .title_style(
+                Style::default()
+                    .fg(get_orange_color())
+                    .add_modifier(Modifier::BOLD),
+            ),
+    );
+    frame.render_widget(details, chunks[1]);
+
+    let steps = [
+        format!("Stop Keycloak and {}", plan.old_volume),
+        format!(
+            "Dump the database with {} and count the rows of every table",
+            plan.source_image()
+        ),
+        format!(
+            "Start {} on the new volume and restore the dump",
+            plan.target_image()
+        ),
+        "Compare row counts, then start Keycloak and wait for it to be healthy".to_string(),
+        format!(
+            "Keep {} until you delete it; on failure switch back to it",
+            plan.old_volume
+        ),
+    ];
+    let step_lines: Vec<Line> = steps
+        .iter()
+        .enumerate()
+        .map(|(index, step)| {
+            let style = if plan.completed {
+                Style::default().fg(Color::Green)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            Line::from(Span::styled(format!("  {}. {}", index + 1, step), style))
+        })
+        .collect();
+    let steps = Paragraph::new(step_lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent()))
+                .title("Steps")
+                .title_style(
+                    Style::default()
+                        .fg(get_orange_color())
+                        .add_modifier(Modifier::BOLD),
+                ),
+        )
+        .wrap(Wrap { trim: false });
+    frame.render_widget(steps, chunks[2]);
+
+    let status = Paragraph::new(view.message.unwrap_or(""))
+        .style(Style::default().fg(Color::Yellow))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent()))
+                .title("Status")
+                .title_style(
+                    Style::default()
+                        .fg(get_orange_color())
+                        .add_modifier(Modifier::BOLD),
+                ),
+        )
+        .wrap(Wrap { trim: true });
+    frame.render_widget(status, chunks[3]);
+
+    let help_text = if plan.completed {
+        "D to delete the old volume, Esc to keep it and go back"
+    } else {
+        "←→ to choose the target major, Enter to upgrade, Esc to go back"
+    };
+    let help = Paragraph::new(help_text)
+        .style(Style::default().fg(Color::DarkGray))
+        .centered();
+    frame.render_widget(help, chunks[4]);
+}
@@ -0,0 +1,130 @@
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Wrap},
+};
+
+use crate::app::preflight::{CheckStatus, PreflightReport};
+use crate::ui::{get_orange_accent, get_orange_color};
+
+pub struct PreflightView<'a> {
+    /// `None` while the checks are still running.
+    pub report: Option<&'a PreflightReport>,
+}
+
+pub fn render_preflight(frame: &mut Frame, view: &PreflightView<'_>) {
+    let area = frame.area();
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(2)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(10),
+            Constraint::Length(3),
+            Constraint::Length(2),
+        ])
+        .split(area);
+
+    let title = Paragraph::new("🩺 Preflight Checks")
+        .style(
+            Style::default()
+                .fg(get_orange_color())
+                .add_modifier(Modifier::BOLD),
+        )
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent())),
+        )
+        .centered();
+    frame.render_widget(title, chunks[0]);
+
+    let mut lines = Vec::new();
+    match view.report {
+        None => lines.push(Line::from(Span::styled(
+            "Running checks...",
+            Style::default().fg(Color::Yellow),
+        ))),
+        Some(report) => {
+            for check in &report.checks {
+                let (icon, color) = match check.status {
+                    CheckStatus::Pass => ("✅", Color::Green),
+                    CheckStatus::Warn => ("⚠️ ", Color::Yellow),
+                    CheckStatus::Fail => ("❌", Color::Red),
+                };
+                lines.push(Line::from(vec![
+                    Span::raw(format!(" {} ", icon)),
+                    Span::styled(
+                        format!("{:<22}", check.name),
+                        Style::default().fg(color).add_modifier(Modifier::BOLD),
+                    ),
+                    Span::styled(check.detail.clone(), Style::default().fg(Color::White)),
+                ]));
+                if let Some(hint) = &check.hint {
+                    lines.push(Line::from(Span::styled(
+                        format!("      → {}", hint),
+                        Style::default().fg(Color::DarkGray),
+                    )));
+                }
+            }
+        }
+    }
+
+    let checks = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent()))
+                .title("Checks")
+                .title_style(
+                    Style::default()
+                        .fg(get_orange_color())
+                        .add_modifier(Modifier::BOLD),
+                ),
+        )
+        .wrap(Wrap { trim: false });
+    frame.render_widget(checks, chunks[1]);
+
+    let (summary, summary_color) = match view.report {
+        None => (
+            "Checking Docker, Compose, ports and resources...".to_string(),
+            Color::Yellow,
+        ),
+        Some(report) if report.passed() => (
+            format!(
+                "{} passed, {} warning(s). Ready to configure the installation.",
+                report.count(CheckStatus::Pass),
+                report.count(CheckStatus::Warn)
+            ),
+            Color::Green,
+        ),
+        Some(report) => (
+            format!(
+                "{} check(s) failed. Fix them and press R to run the checks again.",
+                report.count(CheckStatus::Fail)
+            ),
+            Color::Red,
+        ),
+    };
+    let status = Paragraph::new(summary)
+        .style(Style::default().fg(summary_color))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent())),
+        );
+    frame.render_widget(status, chunks[2]);
+
+    let help_text = if view.report.is_some_and(PreflightReport::passed) {
+        "Enter to continue, R to re-run the checks, Esc to go back"
+    } else {
+        "R to re-run the checks, Esc to go back"
+    };
+    let help = Paragraph::new(help_text)
+        .style(Style::default().fg(Color::DarkGray))
+        .centered();
+    frame.render_widget(help, chunks[3]);
+}
@@ -6,12 +6,16 @@ use ratatui::{
     widgets::{Block, Borders, Paragraph},
 };
 
-use crate::app::registry_form::RegistryForm;
+use super::form::form_lines;
+use crate::app::form::Form;
+use crate::app::{TokenCheck, TokenKind};
 use crate::ui::{get_orange_accent, get_orange_color};
 
 pub struct RegistrySetupView<'a> {
-    pub form: &'a RegistryForm,
+    pub form: &'a Form,
     pub status: Option<&'a str>,
+    /// Result of the last token check, listing permissions and expiry.
+    pub token: Option<&'a TokenCheck>,
 }
 
 pub fn render_registry_setup(frame: &mut Frame, view: &RegistrySetupView<'_>) {
@@ -22,13 +26,13 @@ pub fn render_registry_setup(frame: &mut Frame, view: &RegistrySetupView<'_>) {
         .margin(2)
         .constraints([
             Constraint::Length(5),
-            Constraint::Length(7),
+            Constraint::Length(13),
             Constraint::Min(6),
             Constraint::Length(3),
         ])
         .split(area);
 
-    let header = Paragraph::new("🔐 GitHub Container Registry Login")
+    let header = Paragraph::new("🔐 Container Registry Login")
         .style(
             Style::default()
                 .fg(get_orange_color())
@@ -42,58 +46,16 @@ pub fn render_registry_setup(frame: &mut Frame, view: &RegistrySetupView<'_>) {
         .centered();
     frame.render_widget(header, chunks[0]);
 
-    let mut field_lines = Vec::new();
-    field_lines.push(Line::from(
-        "Provide a GitHub token with `read:packages` scope to pull GHCR images.",
-    ));
-    field_lines.push(Line::from(
-        "We will detect your username automatically from the token.",
-    ));
-    field_lines.push(Line::from(
-        "Press Enter to edit, Ctrl+S to submit, Esc to skip.",
-    ));
-    field_lines.push(Line::from(""));
+    let mut field_lines = vec![
+        Line::from("Provide a GitHub token with `read:packages` scope to pull GHCR images,"),
+        Line::from(
+            "or a mirror host with its username and password; ghcr.io usernames are detected.",
+        ),
+        Line::from("Press Enter to edit, Ctrl+S to submit, Esc to skip."),
+        Line::from(""),
+    ];
 
-    let is_selected = view.form.current_field == 0;
-
-    let raw_value = view.form.token.as_str();
-
-    let display = if raw_value.is_empty() {
-        "<paste token here>".to_string()
-    } else {
-        "*".repeat(raw_value.chars().count())
-    };
-
-    let style = if is_selected {
-        Style::default()
-            .fg(Color::Black)
-            .bg(get_orange_color())
-            .add_modifier(Modifier::BOLD)
-    } else {
-        Style::default().fg(Color::White)
-    };
-
-    field_lines.push(Line::from(vec![
-        Span::styled("  ▶  ", style),
-        Span::styled("Personal access token", style),
-        Span::raw(": "),
-        Span::styled(display, style),
-    ]));
-
-    let submit_style = if view.form.current_field == 1 {
-        Style::default()
-            .fg(Color::Black)
-            .bg(Color::Green)
-            .add_modifier(Modifier::BOLD)
-    } else {
-        Style::default().fg(Color::Green)
-    };
-
-    field_lines.push(Line::from(""));
-    field_lines.push(Line::from(Span::styled(
-        "  ▶  Submit and login",
-        submit_style,
-    )));
+    field_lines.extend(form_lines(view.form));
 
     let form_block = Paragraph::new(field_lines)
         .block(
@@ -126,8 +88,43 @@ pub fn render_registry_setup(frame: &mut Frame, view: &RegistrySetupView<'_>) {
         Style::default().fg(Color::Yellow)
     };
 
-    let status_block = Paragraph::new(status_message)
-        .style(status_style)
+    let mut status_lines = vec![Line::from(Span::styled(status_message, status_style))];
+    if let Some(check) = view.token {
+        let now = chrono::Utc::now();
+        let permissions = match &check.kind {
+            TokenKind::Classic(scopes) if scopes.is_empty() => {
+                "classic token, no scopes".to_string()
+            }
+            TokenKind::Classic(scopes) => format!("classic token, scopes: {}", scopes.join(", ")),
+            TokenKind::FineGrained if check.missing.is_empty() => {
+                "fine-grained token, GHCR pull access confirmed".to_string()
+            }
+            TokenKind::FineGrained => "fine-grained token, no GHCR pull access".to_string(),
+        };
+        let (expiry, expiry_color) = if check.is_expired(now) {
+            ("expired".to_string(), Color::Red)
+        } else if check.expiry_warning(now).is_some() {
+            (check.expiry(now), Color::Yellow)
+        } else {
+            (check.expiry(now), Color::Gray)
+        };
+
+        status_lines.push(Line::from(""));
+        status_lines.push(Line::from(format!("User: {}", check.username)));
+        status_lines.push(Line::from(format!("Permissions: {}", permissions)));
+        if !check.missing.is_empty() {
+            status_lines.push(Line::from(Span::styled(
+                format!("Missing: {}", check.missing.join(", ")),
+                Style::default().fg(Color::Red),
+            )));
+        }
+        status_lines.push(Line::from(Span::styled(
+            format!("Token {}", expiry),
+            Style::default().fg(expiry_color),
+        )));
+    }
+
+    let status_block = Paragraph::new(status_lines)
         .block(
             Block::default()
                 .borders(Borders::ALL)
@@ -6,10 +6,16 @@ use ratatui::{
     widgets::{Block, Borders, Paragraph},
 };
 
+use std::path::Path;
+
 use crate::ui::{get_orange_accent, get_orange_color};
+use crate::utils::GeneratedCredentials;
 
 pub struct SuccessView<'a> {
     pub logs: &'a [String],
+    pub console_url: &'a str,
+    pub credentials: Option<&'a GeneratedCredentials>,
+    pub env_path: &'a Path,
 }
 
 pub fn render_success(frame: &mut Frame, view: &SuccessView<'_>) {
@@ -40,7 +46,7 @@ pub fn render_success(frame: &mut Frame, view: &SuccessView<'_>) {
         .centered();
     frame.render_widget(title, chunks[0]);
 
-    let message = vec![
+    let mut message = vec![
         Line::from(""),
         Line::from(Span::styled(
             "NQRust Identity (Keycloak) has been successfully installed!",
@@ -51,24 +57,44 @@ pub fn render_success(frame: &mut Frame, view: &SuccessView<'_>) {
         Line::from(""),
         Line::from("You can access Keycloak Admin Console at:"),
         Line::from(Span::styled(
-            "http://localhost:8080",
+            view.console_url.to_string(),
             Style::default()
                 .fg(Color::Cyan)
                 .add_modifier(Modifier::UNDERLINED),
         )),
         Line::from(""),
-        Line::from("Default credentials:"),
-        Line::from("  Username: admin"),
-        Line::from("  Password: admin"),
-        Line::from(""),
-        Line::from(Span::styled(
-            "⚠️  Change the default password after first login!",
+    ];
+
+    if let Some(credentials) = view.credentials {
+        message.push(Line::from("Generated admin credentials:"));
+        message.push(Line::from(format!(
+            "  Username: {}",
+            credentials.admin_user
+        )));
+        message.push(Line::from(vec![
+            Span::raw("  Password: "),
+            Span::styled(
+                credentials.admin_password.clone(),
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]));
+        message.push(Line::from(""));
+        message.push(Line::from(Span::styled(
+            "⚠️  This password is shown only once. Store it somewhere safe!",
             Style::default()
                 .fg(Color::Yellow)
                 .add_modifier(Modifier::BOLD),
-        )),
-        Line::from(""),
-    ];
+        )));
+    } else {
+        message.push(Line::from("Admin credentials are stored in:"));
+        message.push(Line::from(format!(
+            "  {} (KEYCLOAK_ADMIN_PASSWORD)",
+            view.env_path.display()
+        )));
+    }
+    message.push(Line::from(""));
 
     let message_widget = Paragraph::new(message)
         .block(
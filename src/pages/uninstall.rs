@@ -0,0 +1,137 @@
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Wrap},
+};
+
+use crate::app::uninstall::{UNINSTALL_OPTIONS, UninstallOption, UninstallPlan};
+use crate::ui::{get_orange_accent, get_orange_color};
+
+pub struct UninstallView<'a> {
+    pub plan: &'a UninstallPlan,
+    pub selected: UninstallOption,
+}
+
+pub fn render_uninstall(frame: &mut Frame, view: &UninstallView<'_>) {
+    let area = frame.area();
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(2)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Length(UNINSTALL_OPTIONS.len() as u16 + 4),
+            Constraint::Min(6),
+            Constraint::Length(2),
+        ])
+        .split(area);
+
+    let title = Paragraph::new("🗑  Uninstall Identity Stack")
+        .style(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent())),
+        )
+        .centered();
+    frame.render_widget(title, chunks[0]);
+
+    let volumes = view.plan.volumes.join(", ");
+    let mut option_lines = vec![Line::from("")];
+    for option in UNINSTALL_OPTIONS {
+        let label = match option {
+            UninstallOption::RemoveVolumes => format!(
+                "[{}] Also delete the database volume ({}) - users and realms are lost",
+                checkbox(view.plan.remove_volumes),
+                volumes
+            ),
+            UninstallOption::RemoveImages => format!(
+                "[{}] Also delete the pulled images",
+                checkbox(view.plan.remove_images)
+            ),
+            UninstallOption::Confirm => "Uninstall".to_string(),
+            UninstallOption::Back => "Back".to_string(),
+        };
+
+        let color = match option {
+            UninstallOption::Confirm => Color::Red,
+            UninstallOption::Back => Color::Green,
+            _ => Color::White,
+        };
+        let style = if *option == view.selected {
+            Style::default()
+                .fg(Color::Black)
+                .bg(get_orange_color())
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(color)
+        };
+
+        if *option == UninstallOption::Confirm {
+            option_lines.push(Line::from(""));
+        }
+        option_lines.push(Line::from(Span::styled(format!("  ▶  {}", label), style)));
+    }
+
+    let options = Paragraph::new(option_lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(get_orange_accent()))
+            .title("Options")
+            .title_style(
+                Style::default()
+                    .fg(get_orange_color())
+                    .add_modifier(Modifier::BOLD),
+            ),
+    );
+    frame.render_widget(options, chunks[1]);
+
+    let mut deletion_lines: Vec<Line> = view
+        .plan
+        .deletions()
+        .into_iter()
+        .map(|item| {
+            Line::from(Span::styled(
+                format!("  • {}", item),
+                Style::default().fg(Color::Red),
+            ))
+        })
+        .collect();
+    deletion_lines.push(Line::from(""));
+    deletion_lines.push(Line::from(Span::styled(
+        "Kept: .env (generated passwords) and the installer itself",
+        Style::default().fg(Color::DarkGray),
+    )));
+    if !view.plan.remove_volumes {
+        deletion_lines.push(Line::from(Span::styled(
+            format!("Kept: volume {}", volumes),
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+
+    let deletions = Paragraph::new(deletion_lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent()))
+                .title("Will be deleted")
+                .title_style(
+                    Style::default()
+                        .fg(get_orange_color())
+                        .add_modifier(Modifier::BOLD),
+                ),
+        )
+        .wrap(Wrap { trim: false });
+    frame.render_widget(deletions, chunks[2]);
+
+    let help = Paragraph::new("↑↓ to move, Space/Enter to toggle or select, Esc to go back")
+        .style(Style::default().fg(Color::DarkGray))
+        .centered();
+    frame.render_widget(help, chunks[3]);
+}
+
+fn checkbox(checked: bool) -> &'static str {
+    if checked { "x" } else { " " }
+}
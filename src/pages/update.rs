@@ -1,13 +1,13 @@
 use chrono::{DateTime, Utc};
 use ratatui::{
     Frame,
-    layout::{Constraint, Direction, Layout},
+    layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Cell, Paragraph, Row, Table, Wrap},
+    widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, Wrap},
 };
 
-use crate::app::UpdateInfo;
+use crate::app::{TagPicker, UpdateInfo};
 use crate::ui::{get_orange_accent, get_orange_color};
 
 pub struct UpdateListView<'a> {
@@ -17,6 +17,7 @@ pub struct UpdateListView<'a> {
     pub logs: &'a [String],
     pub pulling: bool,
     pub progress: Option<f64>,
+    pub tag_picker: Option<&'a TagPicker>,
 }
 
 pub fn render_update_list(frame: &mut Frame, view: &UpdateListView<'_>) {
@@ -34,7 +35,7 @@ pub fn render_update_list(frame: &mut Frame, view: &UpdateListView<'_>) {
         .split(area);
 
     let title_text = if view.pulling {
-        "🔄 Pulling selected image..."
+        "🔄 Updating selected service..."
     } else {
         "🚀 Check for Updates"
     };
@@ -54,7 +55,7 @@ pub fn render_update_list(frame: &mut Frame, view: &UpdateListView<'_>) {
     frame.render_widget(title, chunks[0]);
 
     if view.updates.is_empty() {
-        let placeholder = Paragraph::new("No GHCR-backed services found")
+        let placeholder = Paragraph::new("No services with registry images found")
             .block(
                 Block::default()
                     .borders(Borders::ALL)
@@ -141,7 +142,7 @@ pub fn render_update_list(frame: &mut Frame, view: &UpdateListView<'_>) {
 
     let message_text = view
         .message
-        .unwrap_or("Enter/P: pull image or self-update installer | R: refresh | Esc: back");
+        .unwrap_or("Enter/A: apply update (backup, pull, recreate, verify) | P: pull only | T: pick tag | R: refresh | Esc: back");
 
     let message = Paragraph::new(message_text)
         .style(Style::default().fg(Color::Gray))
@@ -184,19 +185,19 @@ pub fn render_update_list(frame: &mut Frame, view: &UpdateListView<'_>) {
     };
 
     // Prepend a simple progress bar when pulling and a value is provided.
-    if view.pulling {
-        if let Some(pct) = view.progress {
-            let pct = pct.clamp(0.0, 100.0);
-            let bar_space = chunks[3].width.saturating_sub(12) as usize;
-            let filled_width = ((bar_space as f64) * (pct / 100.0)).round() as usize;
-            let filled = "█".repeat(filled_width.min(bar_space));
-            let empty = "░".repeat(bar_space.saturating_sub(filled.len()));
-            let bar = format!("Progress: [{filled}{empty}] {pct:.0}%");
-            log_lines.insert(
-                0,
-                Line::from(Span::styled(bar, Style::default().fg(get_orange_color()))),
-            );
-        }
+    if view.pulling
+        && let Some(pct) = view.progress
+    {
+        let pct = pct.clamp(0.0, 100.0);
+        let bar_space = chunks[3].width.saturating_sub(12) as usize;
+        let filled_width = ((bar_space as f64) * (pct / 100.0)).round() as usize;
+        let filled = "█".repeat(filled_width.min(bar_space));
+        let empty = "░".repeat(bar_space.saturating_sub(filled.len()));
+        let bar = format!("Progress: [{filled}{empty}] {pct:.0}%");
+        log_lines.insert(
+            0,
+            Line::from(Span::styled(bar, Style::default().fg(get_orange_color()))),
+        );
     }
 
     let logs_widget = Paragraph::new(log_lines)
@@ -219,6 +220,68 @@ pub fn render_update_list(frame: &mut Frame, view: &UpdateListView<'_>) {
             0,
         ));
     frame.render_widget(logs_widget, chunks[3]);
+
+    if let Some(picker) = view.tag_picker {
+        render_tag_picker(frame, picker, area);
+    }
+}
+
+fn render_tag_picker(frame: &mut Frame, picker: &TagPicker, area: Rect) {
+    let height = (picker.tags.len() as u16 + 4).min(area.height.saturating_sub(4));
+    let width = 48.min(area.width.saturating_sub(4));
+    let popup = Rect {
+        x: area.x + (area.width.saturating_sub(width)) / 2,
+        y: area.y + (area.height.saturating_sub(height)) / 2,
+        width,
+        height,
+    };
+
+    // Keep the selected tag visible when the list is taller than the popup.
+    let visible = height.saturating_sub(4) as usize;
+    let scroll = picker.selected.saturating_sub(visible.saturating_sub(1));
+
+    let mut lines: Vec<Line> = picker
+        .tags
+        .iter()
+        .enumerate()
+        .map(|(index, tag)| {
+            let marker = if *tag == picker.current_tag {
+                " (current)"
+            } else {
+                ""
+            };
+            let style = if index == picker.selected {
+                Style::default()
+                    .fg(Color::Black)
+                    .bg(get_orange_color())
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            Line::from(Span::styled(format!("  ▶  {}{}", tag, marker), style))
+        })
+        .skip(scroll)
+        .take(visible)
+        .collect();
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "Enter: pin and apply | Esc: cancel",
+        Style::default().fg(Color::DarkGray),
+    )));
+
+    let list = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(get_orange_accent()))
+            .title(format!("Tag for {}", picker.service))
+            .title_style(
+                Style::default()
+                    .fg(get_orange_color())
+                    .add_modifier(Modifier::BOLD),
+            ),
+    );
+    frame.render_widget(Clear, popup);
+    frame.render_widget(list, popup);
 }
 
 fn header_style() -> Style {
@@ -230,10 +293,16 @@ fn header_style() -> Style {
 fn status_text(info: &UpdateInfo) -> String {
     if let Some(note) = &info.status_note {
         note.clone()
+    } else if info.is_self || info.compared_by_digest() {
+        if info.has_update {
+            "Update available".to_string()
+        } else {
+            "Up to date".to_string()
+        }
     } else if info.has_update {
-        "Update available".to_string()
+        "Update available (by date)".to_string()
     } else {
-        "Up to date".to_string()
+        "Up to date (by date)".to_string()
     }
 }
 
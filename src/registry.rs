@@ -0,0 +1,251 @@
+use std::env;
+
+use color_eyre::eyre::{Result, bail};
+
+use crate::env_file::EnvFile;
+use crate::utils;
+
+/// `.env` key (or environment variable) with the registry `docker login`
+/// targets, e.g. `harbor.example.com`.
+pub const HOST_KEY: &str = "REGISTRY_HOST";
+/// `.env` key with the registry user. Optional for GHCR, where it is resolved
+/// from the token.
+pub const USERNAME_KEY: &str = "REGISTRY_USERNAME";
+/// `.env` key with comma-separated `from=to` image prefix rewrites, e.g.
+/// `ghcr.io/nexusquantum=harbor.example.com/nqrust`.
+pub const REWRITE_KEY: &str = "IMAGE_PREFIX_REWRITE";
+
+pub const GHCR_HOST: &str = "ghcr.io";
+/// User name sent to GHCR with a token; GHCR accepts any.
+const GHCR_PLACEHOLDER_USER: &str = "nqrust-identity";
+
+/// Replaces the leading `from` of an image reference with `to`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrefixRewrite {
+    pub from: String,
+    pub to: String,
+}
+
+/// The registry the installer logs in to, and how image references are
+/// rewritten to point at a mirror.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegistryConfig {
+    pub host: String,
+    pub username: Option<String>,
+    pub rewrites: Vec<PrefixRewrite>,
+}
+
+impl Default for RegistryConfig {
+    fn default() -> Self {
+        Self {
+            host: GHCR_HOST.to_string(),
+            username: None,
+            rewrites: Vec::new(),
+        }
+    }
+}
+
+impl RegistryConfig {
+    /// Reads the settings from `env`, letting environment variables override
+    /// each key.
+    pub fn from_env(env: &EnvFile) -> Result<Self> {
+        let lookup = |key: &str| {
+            env::var(key)
+                .ok()
+                .or_else(|| env.get(key).map(str::to_string))
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+
+        Ok(Self {
+            host: lookup(HOST_KEY).unwrap_or_else(|| GHCR_HOST.to_string()),
+            username: lookup(USERNAME_KEY),
+            rewrites: parse_rewrites(lookup(REWRITE_KEY).as_deref().unwrap_or_default())?,
+        })
+    }
+
+    /// The settings from the environment and the project's `.env`.
+    pub fn configured() -> Result<Self> {
+        let env = EnvFile::load(&utils::project_root().join(utils::ENV_FILE)).unwrap_or_default();
+        Self::from_env(&env)
+    }
+
+    /// Writes the settings into `env`, leaving out defaults.
+    pub fn save(&self, env: &mut EnvFile) {
+        if self.is_ghcr() {
+            env.remove(HOST_KEY);
+        } else {
+            env.set(HOST_KEY, &self.host);
+        }
+        match &self.username {
+            Some(username) => env.set(USERNAME_KEY, username),
+            None => env.remove(USERNAME_KEY),
+        }
+        if self.rewrites.is_empty() {
+            env.remove(REWRITE_KEY);
+        } else {
+            env.set(REWRITE_KEY, &format_rewrites(&self.rewrites));
+        }
+    }
+
+    pub fn is_ghcr(&self) -> bool {
+        self.host == GHCR_HOST
+    }
+
+    /// `reference` with the first matching prefix rewritten, or `None` when no
+    /// rule applies. Docker Hub references match in both their short and
+    /// fully qualified forms (`postgres` and `docker.io/library/postgres`).
+    pub fn rewrite(&self, reference: &str) -> Option<String> {
+        let qualified = qualified_reference(reference);
+        self.rewrites.iter().find_map(|rule| {
+            [reference, qualified.as_str()]
+                .iter()
+                .find_map(|candidate| {
+                    let rest = candidate.strip_prefix(rule.from.as_str())?;
+                    (rest.is_empty() || rest.starts_with(['/', ':', '@']))
+                        .then(|| format!("{}{}", rule.to, rest))
+                })
+        })
+    }
+
+    /// Credentials for this registry with `secret` as the token or password.
+    pub fn credentials(&self, secret: Option<&str>) -> RegistryCredentials {
+        RegistryCredentials {
+            host: self.host.clone(),
+            username: self.username.clone(),
+            secret: secret.map(str::to_string),
+        }
+    }
+}
+
+/// What the installer authenticates with against the configured registry.
+#[derive(Debug, Clone, Default)]
+pub struct RegistryCredentials {
+    pub host: String,
+    pub username: Option<String>,
+    pub secret: Option<String>,
+}
+
+impl RegistryCredentials {
+    /// The secret as a GitHub token, when the configured registry is GHCR.
+    pub fn github_token(&self) -> Option<&str> {
+        (self.host == GHCR_HOST)
+            .then_some(self.secret.as_deref())
+            .flatten()
+    }
+
+    /// User and secret to send to `host`, when they are this registry's.
+    pub fn basic_auth_for(&self, host: &str) -> Option<(&str, &str)> {
+        if host != self.host {
+            return None;
+        }
+        let secret = self.secret.as_deref()?;
+        let username = match &self.username {
+            Some(username) => username.as_str(),
+            None if self.host == GHCR_HOST => GHCR_PLACEHOLDER_USER,
+            None => return None,
+        };
+        Some((username, secret))
+    }
+}
+
+/// Parses `from=to,from=to`.
+pub fn parse_rewrites(value: &str) -> Result<Vec<PrefixRewrite>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|rule| !rule.is_empty())
+        .map(|rule| {
+            let Some((from, to)) = rule.split_once('=') else {
+                bail!("Image rewrite '{}' must look like from=to", rule);
+            };
+            let (from, to) = (
+                from.trim().trim_end_matches('/'),
+                to.trim().trim_end_matches('/'),
+            );
+            if from.is_empty() || to.is_empty() {
+                bail!("Image rewrite '{}' must look like from=to", rule);
+            }
+            Ok(PrefixRewrite {
+                from: from.to_string(),
+                to: to.to_string(),
+            })
+        })
+        .collect()
+}
+
+fn format_rewrites(rewrites: &[PrefixRewrite]) -> String {
+    rewrites
+        .iter()
+        .map(|rule| format!("{}={}", rule.from, rule.to))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// `reference` with Docker Hub's implied `docker.io/` and `library/` added.
+fn qualified_reference(reference: &str) -> String {
+    match reference.split_once('/') {
+        None => format!("docker.io/library/{}", reference),
+        Some((first, _)) if first.contains(['.', ':']) || first == "localhost" => {
+            reference.to_string()
+        }
+        Some(_) => format!("docker.io/{}", reference),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefix_rewrites() {
+        let env = EnvFile::parse(
+            "REGISTRY_HOST=harbor.example.com\n\
+             REGISTRY_USERNAME=robot-installer\n\
+             IMAGE_PREFIX_REWRITE=ghcr.io/nexusquantum=harbor.example.com/nqrust/, docker.io/library=harbor.example.com/hub\n",
+        );
+        let config = RegistryConfig::from_env(&env).unwrap();
+        assert_eq!(config.rewrites.len(), 2);
+
+        assert_eq!(
+            config
+                .rewrite("ghcr.io/nexusquantum/nqrust-identity:latest")
+                .as_deref(),
+            Some("harbor.example.com/nqrust/nqrust-identity:latest")
+        );
+        assert_eq!(
+            config.rewrite("postgres:16-alpine").as_deref(),
+            Some("harbor.example.com/hub/postgres:16-alpine")
+        );
+        assert_eq!(config.rewrite("ghcr.io/nexusquantum-labs/app"), None);
+        assert_eq!(
+            config.rewrite("harbor.example.com/nqrust/nqrust-identity:1.2"),
+            None
+        );
+
+        let credentials = config.credentials(Some("secret"));
+        assert_eq!(credentials.github_token(), None);
+        assert_eq!(
+            credentials.basic_auth_for("harbor.example.com"),
+            Some(("robot-installer", "secret"))
+        );
+        assert_eq!(credentials.basic_auth_for("ghcr.io"), None);
+
+        let mut saved = EnvFile::default();
+        config.save(&mut saved);
+        assert_eq!(
+            saved.get(REWRITE_KEY),
+            Some(
+                "ghcr.io/nexusquantum=harbor.example.com/nqrust,docker.io/library=harbor.example.com/hub"
+            )
+        );
+
+        assert!(parse_rewrites("ghcr.io/nexusquantum").is_err());
+        assert_eq!(
+            RegistryConfig::default()
+                .credentials(Some("ghp_x"))
+                .github_token(),
+            Some("ghp_x")
+        );
+    }
+}
@@ -0,0 +1,222 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use tokio::process::Command;
+
+/// Environment variable forcing the runtime (`docker` or `podman`) when both
+/// are installed.
+pub const RUNTIME_ENV: &str = "CONTAINER_RUNTIME";
+
+const DOCKER_SOCKET: &str = "/var/run/docker.sock";
+const PODMAN_SOCKET: &str = "/run/podman/podman.sock";
+
+/// The container engine the installer drives: the CLI it runs, the compose
+/// front-ends it tries, the API socket it talks to and where `login` keeps
+/// credentials.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Runtime {
+    Docker,
+    Podman,
+}
+
+impl Runtime {
+    /// The runtime of this host, detected once. `CONTAINER_RUNTIME` wins;
+    /// otherwise Docker is preferred unless `docker` is missing or is the
+    /// `podman-docker` wrapper script.
+    pub fn current() -> Self {
+        static CURRENT: OnceLock<Runtime> = OnceLock::new();
+        *CURRENT.get_or_init(|| {
+            let forced = env::var(RUNTIME_ENV).ok();
+            let docker = find_in_path("docker");
+            let docker_is_podman = docker
+                .as_deref()
+                .and_then(|path| fs::read(path).ok())
+                .is_some_and(|content| is_podman_wrapper(&content));
+            choose(
+                forced.as_deref(),
+                docker.is_some() && !docker_is_podman,
+                find_in_path("podman").is_some(),
+            )
+        })
+    }
+
+    pub fn binary(self) -> &'static str {
+        match self {
+            Runtime::Docker => "docker",
+            Runtime::Podman => "podman",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Runtime::Docker => "Docker",
+            Runtime::Podman => "Podman",
+        }
+    }
+
+    /// A command running this runtime's CLI.
+    pub fn command(self) -> Command {
+        Command::new(self.binary())
+    }
+
+    /// Compose front-ends to try, in order of preference.
+    pub fn compose_candidates(self) -> [&'static [&'static str]; 2] {
+        match self {
+            Runtime::Docker => [&["docker", "compose"], &["docker-compose"]],
+            Runtime::Podman => [&["podman", "compose"], &["podman-compose"]],
+        }
+    }
+
+    /// Podman runs rootless for every user but root; Docker only when its
+    /// API socket is a per-user one.
+    pub fn is_rootless(self) -> bool {
+        match self {
+            Runtime::Podman => current_uid().is_some_and(|uid| uid != 0),
+            Runtime::Docker => self
+                .socket()
+                .starts_with(runtime_dir(current_uid().unwrap_or(0))),
+        }
+    }
+
+    /// API socket to use when `DOCKER_HOST` is unset: the first that exists
+    /// of [`Runtime::socket_candidates`], else the first candidate.
+    pub fn socket(self) -> PathBuf {
+        let candidates = self.socket_candidates(current_uid().unwrap_or(0));
+        candidates
+            .iter()
+            .find(|path| path.exists())
+            .unwrap_or(&candidates[0])
+            .clone()
+    }
+
+    /// Rootless sockets under `$XDG_RUNTIME_DIR` come first for non-root
+    /// users; root uses the system sockets.
+    fn socket_candidates(self, uid: u32) -> Vec<PathBuf> {
+        let user_dir = runtime_dir(uid);
+        match (self, uid) {
+            (Runtime::Docker, 0) => vec![PathBuf::from(DOCKER_SOCKET)],
+            (Runtime::Docker, _) => {
+                vec![user_dir.join("docker.sock"), PathBuf::from(DOCKER_SOCKET)]
+            }
+            (Runtime::Podman, 0) => vec![PathBuf::from(PODMAN_SOCKET)],
+            (Runtime::Podman, _) => vec![user_dir.join("podman/podman.sock")],
+        }
+    }
+
+    /// How to start the API service when its socket cannot be reached.
+    pub fn start_hint(self) -> &'static str {
+        match (self, self.is_rootless()) {
+            (Runtime::Docker, false) => "Start Docker (sudo systemctl start docker)",
+            (Runtime::Docker, true) => "Start rootless Docker (systemctl --user start docker)",
+            (Runtime::Podman, false) => {
+                "Start the Podman API (sudo systemctl enable --now podman.socket)"
+            }
+            (Runtime::Podman, true) => {
+                "Start the Podman API (systemctl --user enable --now podman.socket; loginctl enable-linger $USER)"
+            }
+        }
+    }
+
+    /// Files `login` stores registry credentials in, most specific first.
+    /// Podman also falls back to Docker's `config.json`.
+    pub fn auth_files(self) -> Vec<PathBuf> {
+        let home = env::var_os("HOME").map(PathBuf::from);
+        let docker_config = env::var_os("DOCKER_CONFIG")
+            .map(PathBuf::from)
+            .or_else(|| home.as_ref().map(|home| home.join(".docker")))
+            .map(|dir| dir.join("config.json"));
+
+        let mut files = Vec::new();
+        if self == Runtime::Podman {
+            files.extend(env::var_os("REGISTRY_AUTH_FILE").map(PathBuf::from));
+            files.push(runtime_dir(current_uid().unwrap_or(0)).join("containers/auth.json"));
+            files.extend(
+                home.as_ref()
+                    .map(|home| home.join(".config/containers/auth.json")),
+            );
+        }
+        files.extend(docker_config);
+        files
+    }
+}
+
+fn choose(forced: Option<&str>, docker: bool, podman: bool) -> Runtime {
+    match forced.map(str::trim) {
+        Some("docker") => return Runtime::Docker,
+        Some("podman") => return Runtime::Podman,
+        _ => {}
+    }
+    if !docker && podman {
+        Runtime::Podman
+    } else {
+        Runtime::Docker
+    }
+}
+
+/// `podman-docker` installs `docker` as a shell script that execs podman.
+fn is_podman_wrapper(content: &[u8]) -> bool {
+    content.starts_with(b"#!")
+        && content
+            .windows(b"podman".len())
+            .any(|window| window == b"podman")
+}
+
+fn find_in_path(binary: &str) -> Option<PathBuf> {
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(binary))
+        .find(|path| path.is_file())
+}
+
+/// `$XDG_RUNTIME_DIR`, or `/run/user/<uid>` where systemd puts it.
+fn runtime_dir(uid: u32) -> PathBuf {
+    env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| Path::new("/run/user").join(uid.to_string()))
+}
+
+/// Real user ID from `/proc/self/status`.
+fn current_uid() -> Option<u32> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("Uid:"))?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_runtime_detection() {
+        assert_eq!(choose(None, true, true), Runtime::Docker);
+        assert_eq!(choose(None, false, true), Runtime::Podman);
+        assert_eq!(choose(None, false, false), Runtime::Docker);
+        assert_eq!(choose(Some("podman"), true, true), Runtime::Podman);
+        assert_eq!(choose(Some("docker"), false, true), Runtime::Docker);
+
+        assert!(is_podman_wrapper(
+            b"#!/bin/sh\n[ -e /etc/containers/nodocker ] || echo \"Emulate Docker CLI using podman.\"\nexec /usr/bin/podman \"$@\"\n"
+        ));
+        assert!(!is_podman_wrapper(b"\x7fELF\x02\x01\x01podman"));
+    }
+
+    #[test]
+    fn test_socket_candidates() {
+        assert_eq!(
+            Runtime::Podman.socket_candidates(0),
+            vec![PathBuf::from("/run/podman/podman.sock")]
+        );
+        let rootless = Runtime::Podman.socket_candidates(1000);
+        assert!(rootless[0].ends_with("podman/podman.sock"));
+        let docker = Runtime::Docker.socket_candidates(1000);
+        assert_eq!(docker.len(), 2);
+        assert!(docker[0].ends_with("docker.sock"));
+        assert_eq!(docker[1], PathBuf::from("/var/run/docker.sock"));
+    }
+}
@@ -0,0 +1,483 @@
+use std::env;
+use std::fs;
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use color_eyre::eyre::{Result, bail, eyre};
+use rand::RngCore;
+use ring::aead::{Aad, CHACHA20_POLY1305, LessSafeKey, NONCE_LEN, Nonce, UnboundKey};
+use serde::Deserialize;
+
+use crate::env_file::EnvFile;
+use crate::registry::{self, RegistryConfig};
+use crate::utils;
+
+/// `.env` key (or environment variable) choosing where the token is kept:
+/// `auto`, `keyring`, `docker`, `docker:<helper>` or `file`.
+pub const STORE_KEY: &str = "SECRET_STORE";
+
+const SERVICE: &str = "nqrust-identity";
+const TOKEN_ACCOUNT: &str = "ghcr-token";
+/// Plain-text token file written by earlier versions; migrated on first use.
+const LEGACY_TOKEN_FILE: &str = ".ghcr_token";
+const ENCRYPTED_TOKEN_FILE: &str = ".ghcr_token.enc";
+const KEY_FILE: &str = "secret.key";
+const ENCRYPTED_PREFIX: &str = "v1:";
+
+/// Where the GHCR token lives.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SecretStore {
+    /// Secret Service (GNOME Keyring, KWallet) over D-Bus, through `secret-tool`.
+    Keyring,
+    /// A `docker-credential-<helper>` program, under the entry for the
+    /// configured registry that `docker login` uses as well.
+    DockerHelper(String),
+    /// ChaCha20-Poly1305 encrypted file in the project. The key is kept in the
+    /// user's config directory, so a copied or committed file is useless.
+    EncryptedFile { path: PathBuf, key_path: PathBuf },
+}
+
+#[derive(Debug, Deserialize)]
+struct DockerConfig {
+    #[serde(rename = "credsStore", default)]
+    creds_store: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HelperCredentials {
+    #[serde(rename = "Username", default)]
+    username: String,
+    #[serde(rename = "Secret")]
+    secret: String,
+}
+
+impl SecretStore {
+    /// Resolves a `SECRET_STORE` value. `auto` (or no value) prefers the
+    /// keyring, then docker's configured credential helper, then the file.
+    pub fn from_config(value: Option<&str>) -> Result<Self> {
+        let value = value.map(str::trim).unwrap_or("auto");
+        match value {
+            "" | "auto" => Ok(if keyring_available() {
+                Self::Keyring
+            } else if let Some(helper) = docker_creds_store() {
+                Self::DockerHelper(helper)
+            } else {
+                Self::encrypted_file()?
+            }),
+            "keyring" => Ok(Self::Keyring),
+            "docker" => docker_creds_store()
+                .map(Self::DockerHelper)
+                .ok_or_else(|| eyre!("No credsStore is configured in ~/.docker/config.json")),
+            "file" => Self::encrypted_file(),
+            other => match other.strip_prefix("docker:") {
+                Some(helper) if !helper.is_empty() => Ok(Self::DockerHelper(helper.to_string())),
+                _ => bail!("Unknown {} value: {}", STORE_KEY, other),
+            },
+        }
+    }
+
+    /// The store selected by the environment or the project's `.env`.
+    pub fn configured() -> Result<Self> {
+        let from_env = env::var(STORE_KEY).ok();
+        let from_file = EnvFile::load(&utils::project_root().join(utils::ENV_FILE))
+            .ok()
+            .and_then(|env| env.get(STORE_KEY).map(str::to_string));
+        Self::from_config(from_env.or(from_file).as_deref())
+    }
+
+    fn encrypted_file() -> Result<Self> {
+        Ok(Self::EncryptedFile {
+            path: utils::project_root().join(ENCRYPTED_TOKEN_FILE),
+            key_path: config_dir()?.join(KEY_FILE),
+        })
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Keyring => "the system keyring".to_string(),
+            Self::DockerHelper(helper) => format!("docker-credential-{}", helper),
+            Self::EncryptedFile { path, .. } => format!("{} (encrypted)", path.display()),
+        }
+    }
+
+    pub fn get(&self) -> Result<Option<String>> {
+        match self {
+            Self::Keyring => {
+                let output = Command::new("secret-tool")
+                    .args(["lookup", "service", SERVICE, "account", TOKEN_ACCOUNT])
+                    .output()?;
+                // secret-tool exits with 1 and no output when nothing is stored.
+                if !output.status.success() {
+                    return Ok(None);
+                }
+                Ok(non_empty(&String::from_utf8_lossy(&output.stdout)))
+            }
+            Self::DockerHelper(helper) => {
+                let output = run_helper(helper, "get", &registry_host())?;
+                if !output.status.success() {
+                    return Ok(None);
+                }
+                let credentials: HelperCredentials = serde_json::from_slice(&output.stdout)?;
+                Ok(non_empty(&credentials.secret))
+            }
+            Self::EncryptedFile { path, key_path } => {
+                if !path.exists() {
+                    return Ok(None);
+                }
+                let key = read_key(key_path)?.ok_or_else(|| {
+                    eyre!(
+                        "{} is missing; cannot decrypt {}",
+                        key_path.display(),
+                        path.display()
+                    )
+                })?;
+                decrypt(&key, fs::read_to_string(path)?.trim()).map(|token| non_empty(&token))
+            }
+        }
+    }
+
+    /// Stores `secret`; `username` is only used by docker credential helpers.
+    pub fn store(&self, username: &str, secret: &str) -> Result<()> {
+        match self {
+            Self::Keyring => {
+                let label = "NQRust Identity GHCR token";
+                let output = command_with_input(
+                    Command::new("secret-tool").args([
+                        "store",
+                        "--label",
+                        label,
+                        "service",
+                        SERVICE,
+                        "account",
+                        TOKEN_ACCOUNT,
+                    ]),
+                    secret,
+                )?;
+                check_output("secret-tool store", &output)
+            }
+            Self::DockerHelper(helper) => {
+                let payload = serde_json::json!({
+                    "ServerURL": registry_host(),
+                    "Username": username,
+                    "Secret": secret,
+                });
+                let output = run_helper(helper, "store", &payload.to_string())?;
+                check_output(&format!("docker-credential-{} store", helper), &output)
+            }
+            Self::EncryptedFile { path, key_path } => {
+                let key = match read_key(key_path)? {
+                    Some(key) => key,
+                    None => create_key(key_path)?,
+                };
+                write_private(path, &encrypt(&key, secret)?)
+            }
+        }
+    }
+}
+
+/// Loads the token from the configured store, first moving a plain-text
+/// `.ghcr_token` left by an earlier version into it. A legacy token that
+/// cannot be migrated yet is used as is.
+pub fn load_token() -> Option<String> {
+    let store = SecretStore::configured().ok()?;
+    let root = utils::project_root();
+    let _ = migrate_legacy_token(&root, &store, None);
+    legacy_token(&root).or_else(|| store.get().ok().flatten())
+}
+
+/// Migrates a legacy `.ghcr_token` holding `token` now that GitHub says the
+/// token belongs to `username`.
+pub fn migrate_legacy_token_for(username: &str, token: &str) -> Result<bool> {
+    let root = utils::project_root();
+    if legacy_token(&root).as_deref() != Some(token) {
+        return Ok(false);
+    }
+    migrate_legacy_token(&root, &SecretStore::configured()?, Some(username))
+}
+
+/// Stores the token in the configured store and returns where it went. A
+/// legacy `.ghcr_token` is superseded and removed.
+pub fn store_token(username: &str, token: &str) -> Result<String> {
+    let store = SecretStore::configured()?;
+    store.store(username, token)?;
+    let legacy = utils::project_root().join(LEGACY_TOKEN_FILE);
+    if legacy.exists() {
+        fs::remove_file(legacy)?;
+    }
+    Ok(store.describe())
+}
+
+/// Moves `root/.ghcr_token` into `store` and deletes it. Returns whether a
+/// token was migrated. Docker credential helpers key the entry by user as
+/// well, and storing it under an empty one would replace what `docker login`
+/// wrote; without `username` the file is left for a later login.
+pub fn migrate_legacy_token(
+    root: &Path,
+    store: &SecretStore,
+    username: Option<&str>,
+) -> Result<bool> {
+    let legacy = root.join(LEGACY_TOKEN_FILE);
+    let Ok(content) = fs::read_to_string(&legacy) else {
+        return Ok(false);
+    };
+    if matches!(store, SecretStore::DockerHelper(_)) && username.is_none() {
+        return Ok(false);
+    }
+
+    if let Some(token) = non_empty(&content) {
+        store.store(username.unwrap_or_default(), &token)?;
+    }
+    fs::remove_file(&legacy)?;
+    Ok(true)
+}
+
+fn legacy_token(root: &Path) -> Option<String> {
+    non_empty(&fs::read_to_string(root.join(LEGACY_TOKEN_FILE)).ok()?)
+}
+
+/// User and secret that `docker-credential-<helper>` holds for `server`, as
+/// `docker login` stored them.
+pub fn helper_credentials(helper: &str, server: &str) -> Option<(String, String)> {
+    let output = run_helper(helper, "get", server).ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let credentials: HelperCredentials = serde_json::from_slice(&output.stdout).ok()?;
+    Some((credentials.username, non_empty(&credentials.secret)?))
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+fn keyring_available() -> bool {
+    env::var_os("DBUS_SESSION_BUS_ADDRESS").is_some()
+        && Command::new("secret-tool")
+            .arg("--version")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+}
+
+fn docker_creds_store() -> Option<String> {
+    let home = env::var_os("HOME")?;
+    let config_dir = env::var_os("DOCKER_CONFIG")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(home).join(".docker"));
+    let content = fs::read_to_string(config_dir.join("config.json")).ok()?;
+    serde_json::from_str::<DockerConfig>(&content)
+        .ok()?
+        .creds_store
+        .filter(|store| !store.is_empty())
+}
+
+/// The user's config directory for the installer. There is no fallback: the
+/// encryption key must not end up next to the file it encrypts.
+fn config_dir() -> Result<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .map(|dir| dir.join(SERVICE))
+        .ok_or_else(|| {
+            eyre!("Neither XDG_CONFIG_HOME nor HOME is set, so there is nowhere private to keep the token's encryption key")
+        })
+}
+
+/// Registry whose docker credential helper entry holds the token.
+fn registry_host() -> String {
+    RegistryConfig::configured()
+        .map(|config| config.host)
+        .unwrap_or_else(|_| registry::GHCR_HOST.to_string())
+}
+
+/// Runs `docker-credential-<helper> <action>` with `input` on stdin. A helper
+/// given as a path is run as is.
+fn run_helper(helper: &str, action: &str, input: &str) -> Result<std::process::Output> {
+    let program = if helper.contains('/') {
+        helper.to_string()
+    } else {
+        format!("docker-credential-{}", helper)
+    };
+    command_with_input(Command::new(program).arg(action), input)
+}
+
+fn command_with_input(command: &mut Command, input: &str) -> Result<std::process::Output> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input.as_bytes())?;
+    }
+    Ok(child.wait_with_output()?)
+}
+
+fn check_output(what: &str, output: &std::process::Output) -> Result<()> {
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let message = if stderr.trim().is_empty() {
+        stdout
+    } else {
+        stderr
+    };
+    bail!("{} failed: {}", what, message.trim())
+}
+
+/// Writes `content` readable by the owner only, through a private temporary
+/// file renamed over `path` like [`crate::env_file::EnvFile::save`]. Missing
+/// parent directories are created for the owner only.
+fn write_private(path: &Path, content: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        let mut builder = fs::DirBuilder::new();
+        builder.recursive(true);
+        #[cfg(unix)]
+        builder.mode(0o700);
+        builder.create(parent)?;
+    }
+
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".tmp");
+    let temp = path.with_file_name(file_name);
+    let _ = fs::remove_file(&temp);
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let written = options.open(&temp).and_then(|mut file| {
+        file.write_all(content.as_bytes())?;
+        file.sync_all()
+    });
+    if let Err(e) = written.and_then(|()| fs::rename(&temp, path)) {
+        let _ = fs::remove_file(&temp);
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+fn read_key(path: &Path) -> Result<Option<Vec<u8>>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    Ok(Some(STANDARD.decode(fs::read_to_string(path)?.trim())?))
+}
+
+fn create_key(path: &Path) -> Result<Vec<u8>> {
+    let mut key = vec![0u8; CHACHA20_POLY1305.key_len()];
+    rand::rng().fill_bytes(&mut key);
+    write_private(path, &STANDARD.encode(&key))?;
+    Ok(key)
+}
+
+fn sealing_key(key: &[u8]) -> Result<LessSafeKey> {
+    let key = UnboundKey::new(&CHACHA20_POLY1305, key)
+        .map_err(|_| eyre!("The secret key has the wrong length"))?;
+    Ok(LessSafeKey::new(key))
+}
+
+/// `v1:` followed by base64 of the random nonce and the sealed secret.
+fn encrypt(key: &[u8], secret: &str) -> Result<String> {
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::rng().fill_bytes(&mut nonce);
+
+    let mut sealed = secret.as_bytes().to_vec();
+    sealing_key(key)?
+        .seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(TOKEN_ACCOUNT.as_bytes()),
+            &mut sealed,
+        )
+        .map_err(|_| eyre!("Failed to encrypt the token"))?;
+
+    let mut payload = nonce.to_vec();
+    payload.extend(sealed);
+    Ok(format!("{}{}", ENCRYPTED_PREFIX, STANDARD.encode(payload)))
+}
+
+fn decrypt(key: &[u8], content: &str) -> Result<String> {
+    let encoded = content
+        .strip_prefix(ENCRYPTED_PREFIX)
+        .ok_or_else(|| eyre!("Unrecognised encrypted token format"))?;
+    let payload = STANDARD.decode(encoded)?;
+    if payload.len() < NONCE_LEN {
+        bail!("The encrypted token is truncated");
+    }
+
+    let (nonce, sealed) = payload.split_at(NONCE_LEN);
+    let nonce = Nonce::try_assume_unique_for_key(nonce)
+        .map_err(|_| eyre!("The encrypted token is truncated"))?;
+    let mut sealed = sealed.to_vec();
+    let plain = sealing_key(key)?
+        .open_in_place(nonce, Aad::from(TOKEN_ACCOUNT.as_bytes()), &mut sealed)
+        .map_err(|_| eyre!("The token could not be decrypted with this machine's key"))?;
+    Ok(String::from_utf8(plain.to_vec())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypted_file_and_migration() {
+        let dir = env::temp_dir().join(format!("nqrust-identity-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let store = SecretStore::EncryptedFile {
+            path: dir.join(ENCRYPTED_TOKEN_FILE),
+            key_path: dir.join("config").join(KEY_FILE),
+        };
+        fs::write(dir.join(LEGACY_TOKEN_FILE), "ghp_legacy\n").unwrap();
+
+        let helper = SecretStore::DockerHelper("pass".to_string());
+        assert!(!migrate_legacy_token(&dir, &helper, None).unwrap());
+        assert_eq!(legacy_token(&dir).as_deref(), Some("ghp_legacy"));
+
+        assert!(migrate_legacy_token(&dir, &store, None).unwrap());
+        assert!(!dir.join(LEGACY_TOKEN_FILE).exists());
+        let on_disk = fs::read_to_string(dir.join(ENCRYPTED_TOKEN_FILE)).unwrap();
+        assert!(on_disk.starts_with(ENCRYPTED_PREFIX));
+        assert!(!on_disk.contains("ghp_legacy"));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = |path: PathBuf| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+            assert_eq!(mode(dir.join(ENCRYPTED_TOKEN_FILE)), 0o600);
+            assert_eq!(mode(dir.join("config").join(KEY_FILE)), 0o600);
+            assert_eq!(mode(dir.join("config")), 0o700);
+        }
+        assert_eq!(store.get().unwrap().as_deref(), Some("ghp_legacy"));
+
+        let other_key = create_key(&dir.join("other.key")).unwrap();
+        assert!(decrypt(&other_key, &on_disk).is_err());
+        assert!(!migrate_legacy_token(&dir, &store, None).unwrap());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_store_from_config() {
+        assert_eq!(
+            SecretStore::from_config(Some("keyring")).unwrap(),
+            SecretStore::Keyring
+        );
+        assert_eq!(
+            SecretStore::from_config(Some("docker:pass")).unwrap(),
+            SecretStore::DockerHelper("pass".to_string())
+        );
+        assert!(matches!(
+            SecretStore::from_config(Some("file")).unwrap(),
+            SecretStore::EncryptedFile { .. }
+        ));
+        assert!(SecretStore::from_config(Some("vault")).is_err());
+    }
+}
@@ -1,17 +1,10 @@
 mod ascii_art;
 
-pub use ascii_art::{ASCII_HEADER, get_orange_accent, get_orange_color};
 pub use crate::pages::{
-    ConfirmationView,
-    ErrorView,
-    InstallingView,
-    RegistrySetupView,
-    SuccessView,
-    UpdateListView,
-    render_confirmation,
-    render_error,
-    render_installing,
-    render_registry_setup,
-    render_success,
-    render_update_list,
+    BackupsView, ConfigureView, ConfirmationView, DashboardView, ErrorView, InstallingView,
+    LogViewerView, PgUpgradeView, PreflightView, RegistrySetupView, SuccessView, UninstallView,
+    UpdateListView, render_backups, render_configure, render_confirmation, render_dashboard,
+    render_error, render_installing, render_log_viewer, render_pg_upgrade, render_preflight,
+    render_registry_setup, render_success, render_uninstall, render_update_list,
 };
+pub use ascii_art::{ASCII_HEADER, get_orange_accent, get_orange_color};
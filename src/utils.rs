@@ -2,9 +2,43 @@ use std::fs;
 use std::path::{Path, PathBuf};
 
 use color_eyre::eyre::Result;
+use rand::Rng;
+use rand::distr::Alphanumeric;
+
+use crate::env_file::EnvFile;
+use crate::runtime::Runtime;
 
 pub const COMPOSE_TEMPLATE: &str = include_str!("../docker-compose.yaml");
 
+pub const ENV_FILE: &str = ".env";
+/// Compose file names, in the order the installer looks for them.
+pub const COMPOSE_FILES: &[&str] = &[
+    "docker-compose.yaml",
+    "docker-compose.yml",
+    "compose.yaml",
+    "compose.yml",
+];
+pub const ADMIN_USER_KEY: &str = "KEYCLOAK_ADMIN";
+pub const ADMIN_PASSWORD_KEY: &str = "KEYCLOAK_ADMIN_PASSWORD";
+pub const DB_PASSWORD_KEY: &str = "POSTGRES_PASSWORD";
+const DB_VOLUME_KEY: &str = "POSTGRES_VOLUME";
+
+const DEFAULT_ADMIN_USER: &str = "admin";
+/// The volume Compose created for the database before it could be renamed.
+const DEFAULT_DB_VOLUME: &str = "identity_identity_pgdata";
+/// Passwords the compose file hard-coded before they were generated; data
+/// created back then still expects them.
+const LEGACY_ADMIN_PASSWORD: &str = "admin";
+const LEGACY_DB_PASSWORD: &str = "identity";
+const SECRET_LENGTH: usize = 32;
+
+/// Admin credentials generated while scaffolding a fresh install.
+#[derive(Debug, Clone)]
+pub struct GeneratedCredentials {
+    pub admin_user: String,
+    pub admin_password: String,
+}
+
 pub fn project_root() -> PathBuf {
     let start = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
 
@@ -18,7 +52,7 @@ pub fn project_root() -> PathBuf {
     ];
 
     let mut current = start.as_path();
-    while let Some(dir) = current.parent().or_else(|| Some(current)) {
+    while let Some(dir) = current.parent().or(Some(current)) {
         if candidates.iter().any(|name| dir.join(name).exists()) {
             return dir.to_path_buf();
         }
@@ -36,28 +70,31 @@ pub fn project_root() -> PathBuf {
         .to_str()
         .map(|s| s.contains("target"))
         .unwrap_or(false)
+        && let Some(parent) = start.parent().and_then(|p| p.parent())
     {
-        if let Some(parent) = start.parent().and_then(|p| p.parent()) {
-            return parent.to_path_buf();
-        }
+        return parent.to_path_buf();
     }
 
     start
 }
 
-pub fn ensure_compose_bundle(root: &Path) -> Result<()> {
-    // Compose file: only scaffold if none of the common names already exist
-    let compose_candidates = [
-        "docker-compose.yaml",
-        "docker-compose.yml",
-        "compose.yaml",
-        "compose.yml",
-    ];
+pub fn find_file(name: &str) -> bool {
+    project_root().join(name).exists()
+}
 
-    if !compose_candidates
+/// The project's compose file, if there is one.
+pub fn compose_file(root: &Path) -> Option<PathBuf> {
+    COMPOSE_FILES
         .iter()
-        .any(|name| root.join(name).exists())
-    {
+        .map(|name| root.join(name))
+        .find(|path| path.exists())
+}
+
+/// Scaffolds the compose file and its `.env`. Returns the admin credentials
+/// when they were generated by this call, so they can be shown exactly once.
+pub async fn ensure_compose_bundle(root: &Path) -> Result<Option<GeneratedCredentials>> {
+    // Compose file: only scaffold if none of the common names already exist
+    if compose_file(root).is_none() {
         let compose_path = root.join("docker-compose.yaml");
         if let Some(parent) = compose_path.parent() {
             fs::create_dir_all(parent)?;
@@ -65,7 +102,75 @@ pub fn ensure_compose_bundle(root: &Path) -> Result<()> {
         fs::write(&compose_path, COMPOSE_TEMPLATE)?;
     }
 
-    Ok(())
+    let env_path = root.join(ENV_FILE);
+    let env = EnvFile::load(&env_path)?;
+    let existing_data = env.get(DB_PASSWORD_KEY).is_none()
+        && volume_exists(env.get(DB_VOLUME_KEY).unwrap_or(DEFAULT_DB_VOLUME)).await;
+    ensure_secrets(&env_path, existing_data)
+}
+
+async fn volume_exists(name: &str) -> bool {
+    Runtime::current()
+        .command()
+        .args(["volume", "inspect", name])
+        .output()
+        .await
+        .is_ok_and(|output| output.status.success())
+}
+
+/// Fills in any missing secrets in `.env`; existing values are never rotated
+/// because the database volume is initialised with the first password. When
+/// `existing_data` says the volume predates `.env`, the legacy passwords it
+/// was initialised with are written instead of fresh ones.
+fn ensure_secrets(env_path: &Path, existing_data: bool) -> Result<Option<GeneratedCredentials>> {
+    let mut env = EnvFile::load(env_path)?;
+    let mut changed = false;
+    let mut generated = None;
+
+    if env.get(ADMIN_USER_KEY).is_none() {
+        env.set(ADMIN_USER_KEY, DEFAULT_ADMIN_USER);
+        changed = true;
+    }
+
+    if env.get(ADMIN_PASSWORD_KEY).is_none() && existing_data {
+        env.set(ADMIN_PASSWORD_KEY, LEGACY_ADMIN_PASSWORD);
+        changed = true;
+    } else if env.get(ADMIN_PASSWORD_KEY).is_none() {
+        let password = generate_secret(SECRET_LENGTH);
+        env.set(ADMIN_PASSWORD_KEY, &password);
+        generated = Some(GeneratedCredentials {
+            admin_user: env
+                .get(ADMIN_USER_KEY)
+                .unwrap_or(DEFAULT_ADMIN_USER)
+                .to_string(),
+            admin_password: password,
+        });
+        changed = true;
+    }
+
+    if env.get(DB_PASSWORD_KEY).is_none() {
+        let password = if existing_data {
+            LEGACY_DB_PASSWORD.to_string()
+        } else {
+            generate_secret(SECRET_LENGTH)
+        };
+        env.set(DB_PASSWORD_KEY, &password);
+        changed = true;
+    }
+
+    if changed {
+        env.save(env_path)?;
+    }
+
+    Ok(generated)
+}
+
+pub fn generate_secret(length: usize) -> String {
+    rand::rng()
+        .sample_iter(&Alphanumeric)
+        .take(length)
+        .map(char::from)
+        .collect()
 }
 
 #[cfg(test)]
@@ -89,4 +194,48 @@ mod tests {
             "Should not find non-existent file"
         );
     }
+
+    #[test]
+    fn test_ensure_secrets_generates_once() {
+        let dir = std::env::temp_dir().join(format!("nqrust-identity-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let env_path = dir.join(ENV_FILE);
+
+        let first = ensure_secrets(&env_path, false)
+            .unwrap()
+            .expect("credentials generated");
+        assert_eq!(first.admin_user, "admin");
+        assert_eq!(first.admin_password.len(), SECRET_LENGTH);
+        assert!(ensure_secrets(&env_path, true).unwrap().is_none());
+
+        let env = EnvFile::load(&env_path).unwrap();
+        assert_eq!(
+            env.get(ADMIN_PASSWORD_KEY),
+            Some(first.admin_password.as_str())
+        );
+        assert!(env.get(DB_PASSWORD_KEY).is_some());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&env_path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_ensure_secrets_keeps_legacy_passwords_for_existing_data() {
+        let dir = std::env::temp_dir().join(format!("nqrust-identity-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let env_path = dir.join(ENV_FILE);
+
+        assert!(ensure_secrets(&env_path, true).unwrap().is_none());
+        let env = EnvFile::load(&env_path).unwrap();
+        assert_eq!(env.get(ADMIN_PASSWORD_KEY), Some(LEGACY_ADMIN_PASSWORD));
+        assert_eq!(env.get(DB_PASSWORD_KEY), Some(LEGACY_DB_PASSWORD));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }